@@ -0,0 +1,536 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct DealCreated {
+    pub deal: Pubkey,
+    pub bump: u8,
+    pub gig: Pubkey,
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub offer: u64,
+    pub client_fee: u64,
+    pub client_fee_percentage: u16,
+    pub fee_tier: Option<u8>,
+    pub deadline: i64,
+    pub deadline_duration: i64,
+    pub referrer: Option<Pubkey>,
+    pub terms_hash: [u8; 32],
+    pub nonce: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealAccepted {
+    pub deal: Pubkey,
+    pub freelancer: Pubkey,
+    pub terms_hash: [u8; 32],
+    pub stake_amount: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealCompleted {
+    pub deal: Pubkey,
+    pub freelancer_amount: u64,
+    pub treasury_amount: u64,
+    pub penalty_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealCancelled {
+    pub deal: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub refund_amount: u64,
+    pub cancel_fee: u64,
+    /// Whether an accepted deal was cancelled within `Config.client_cancel_window`.
+    pub within_free_window: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealRejected {
+    pub deal: Pubkey,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealRefunded {
+    pub deal: Pubkey,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealDisputed {
+    pub deal: Pubkey,
+    pub disputed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub deal: Pubkey,
+    pub client_bps: u16,
+    pub client_amount: u64,
+    pub freelancer_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeadlineExtended {
+    pub deal: Pubkey,
+    pub old_deadline: i64,
+    pub new_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferrerPaid {
+    pub deal: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawal {
+    pub mint: Option<Pubkey>,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub client_fee_percentage: u16,
+    pub freelancer_fee_percentage: u16,
+    pub client_fee_min: u64,
+    pub arbiter: Pubkey,
+    pub max_deal_offer: u64,
+    pub refund_grace_period: i64,
+    pub round_fees_up: bool,
+    pub referrer_cooldown: i64,
+    pub defer_freelancer_payouts: bool,
+    pub max_deadline_horizon: i64,
+    pub freelancer_cancel_penalty_bps: u16,
+    pub require_approval_above: u64,
+    pub settlement_delay: i64,
+    pub max_tvl: u64,
+    pub client_cancel_window: i64,
+    pub dispute_timeout: i64,
+    pub split_escrow: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseToggled {
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeliverableSubmitted {
+    pub deal: Pubkey,
+    pub delivery_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RevisionRequested {
+    pub deal: Pubkey,
+    pub revision_count: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewLeft {
+    pub deal: Pubkey,
+    pub reviewer: Pubkey,
+    pub stars: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GigUpdated {
+    pub gig: Pubkey,
+    pub asking: u64,
+    pub state: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CounterOffered {
+    pub deal: Pubkey,
+    pub offer: u64,
+    pub counter_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CounterAccepted {
+    pub deal: Pubkey,
+    pub offer: u64,
+    pub client_fee: u64,
+    pub top_up_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TipSent {
+    pub deal: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferInitiated {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferAccepted {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BlacklistUpdated {
+    pub user: Pubkey,
+    pub blacklisted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GigClosed {
+    pub gig: Pubkey,
+    pub reclaimed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeliveryFinalized {
+    pub deal: Pubkey,
+    pub finalized_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigInitialized {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferrerPayoutMintSet {
+    pub mint: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealClosed {
+    pub deal: Pubkey,
+    pub closed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OfferIncreased {
+    pub deal: Pubkey,
+    pub old_offer: u64,
+    pub new_offer: u64,
+    pub top_up_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealProposed {
+    pub deal: Pubkey,
+    pub gig: Pubkey,
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub offer: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalFunded {
+    pub deal: Pubkey,
+    pub client: Pubkey,
+    pub offer: u64,
+    pub client_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeTimedOut {
+    pub deal: Pubkey,
+    pub resolved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealMigrated {
+    pub deal: Pubkey,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyCreated {
+    pub bounty: Pubkey,
+    pub gig: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub client_fee: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyContributed {
+    pub bounty: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub client_fee: u64,
+    pub offer: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyDelivered {
+    pub bounty: Pubkey,
+    pub delivery_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyCompleted {
+    pub bounty: Pubkey,
+    pub freelancer_amount: u64,
+    pub treasury_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyRefunded {
+    pub bounty: Pubkey,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FreelancerReassigned {
+    pub deal: Pubkey,
+    pub old_freelancer: Pubkey,
+    pub new_freelancer: Pubkey,
+    pub old_gig: Pubkey,
+    pub new_gig: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub bid: Pubkey,
+    pub gig: Pubkey,
+    pub bidder: Pubkey,
+    pub offer: u64,
+    pub client_fee: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidSelected {
+    pub bid: Pubkey,
+    pub deal: Pubkey,
+    pub gig: Pubkey,
+    pub bidder: Pubkey,
+    pub offer: u64,
+    pub client_fee: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidReclaimed {
+    pub bid: Pubkey,
+    pub gig: Pubkey,
+    pub bidder: Pubkey,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExemptionSet {
+    pub client: Pubkey,
+    pub override_fee_bps: Option<u16>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EarningsWithdrawn {
+    pub freelancer: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FreelancerCancelled {
+    pub deal: Pubkey,
+    pub freelancer: Pubkey,
+    pub refund_amount: u64,
+    pub stake_forfeited: u64,
+    pub stake_returned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealPendingApproval {
+    pub deal: Pubkey,
+    pub client: Pubkey,
+    pub offer: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealApproved {
+    pub deal: Pubkey,
+    pub approved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DealFunded {
+    pub deal: Pubkey,
+    pub client: Pubkey,
+    pub offer: u64,
+    pub client_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GigAccountClosed {
+    pub gig: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SettlementPending {
+    pub deal: Pubkey,
+    pub settle_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SettlementFinalized {
+    pub deal: Pubkey,
+    pub finalized_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MutualSettlement {
+    pub deal: Pubkey,
+    pub freelancer_bps: u16,
+    pub freelancer_amount: u64,
+    pub client_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferrerUpdated {
+    pub deal: Pubkey,
+    pub old_referrer: Option<Pubkey>,
+    pub new_referrer: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct YieldStrategySet {
+    pub strategy: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDepositedToStrategy {
+    pub deal: Pubkey,
+    pub strategy: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowWithdrawnFromStrategy {
+    pub deal: Pubkey,
+    pub strategy: Pubkey,
+    pub principal: u64,
+    pub yield_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RetainerCreated {
+    pub retainer: Pubkey,
+    pub gig: Pubkey,
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub period_seconds: i64,
+    pub per_period_amount: u64,
+    pub per_period_client_fee: u64,
+    pub periods_total: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PeriodReleased {
+    pub retainer: Pubkey,
+    /// Number of the period released, starting at 1.
+    pub period: u16,
+    pub freelancer_amount: u64,
+    pub treasury_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RetainerCancelled {
+    pub retainer: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub periods_refunded: u16,
+    pub freelancer_amount: u64,
+    pub treasury_amount: u64,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttestorSet {
+    pub attestor: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeliveryAttested {
+    pub deal: Pubkey,
+    pub attestor: Pubkey,
+    pub delivery_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MilestoneReleased {
+    pub deal: Pubkey,
+    pub index: u8,
+    pub freelancer_amount: u64,
+    pub penalty_amount: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,35 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{events::AuthorityTransferAccepted, states::Config, CustomError};
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = config.pending_authority == Some(new_authority.key()) @ CustomError::Unauthorized,
+    )]
+    pub new_authority: Signer<'info>,
+}
+
+pub fn accept_authority_handler(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &mut ctx.accounts.config;
+    let old_authority = config.authority;
+
+    config.authority = ctx.accounts.new_authority.key();
+    config.pending_authority = None;
+
+    emit!(AuthorityTransferAccepted {
+        old_authority,
+        new_authority: config.authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,275 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::{CounterAccepted, DealPendingApproval},
+    states::{Config, Deal, DealStatus, Exemption, Gig, Reputation, Stats},
+    utils::{
+        client_fee_percentage, compute_client_fee, deposit_tokens, fee_escrow_of, freelancer_net,
+        Escrow,
+    },
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct AcceptCounterParams {
+    /// Fails the acceptance if the client fee comes out higher, eg. after a config update.
+    pub max_client_fee: Option<u64>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptCounter<'info> {
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::CounterOffered as u8,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key(),
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: compared against the price feed registered in the config for the gig's mint
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", client.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    /// Accepting the counter offer also accepts the deal on behalf of the freelancer.
+    /// Also picks the fee tier of the freelancer.
+    #[account(
+        init_if_needed,
+        payer = client,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump,
+        space = Reputation::len(),
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn accept_counter_handler(
+    ctx: Context<AcceptCounter>,
+    params: AcceptCounterParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &ctx.accounts.gig;
+    let offer = ctx.accounts.deal.counter_amount;
+
+    // Accepting a counter isn't signed by the freelancer, so there's no one to take the stake from.
+    if gig.required_stake > 0 {
+        return Err(error!(CustomError::StakeRequired));
+    }
+
+    // The counter becomes the offer, so it has to stay within the gig's range as well.
+    if matches!(gig.max_asking, Some(max_asking) if offer > max_asking) {
+        return Err(error!(CustomError::OfferTooHigh));
+    }
+
+    // The cap may have been lowered since the counter was made.
+    let max_deal_offer = ctx.accounts.config.max_deal_offer;
+
+    if max_deal_offer > 0 && offer > max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    let escrow_amount = Escrow::new(
+        &ctx.accounts.deal,
+        ctx.accounts.deal.mint.is_none(),
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+    )?
+    .checked_amount()?;
+
+    // The fee is recalculated for the new offer, the client only pays the difference.
+    let client_fee = compute_client_fee(
+        &ctx.accounts.config,
+        ctx.accounts.deal.mint,
+        ctx.accounts.mint.as_ref().map(|mint| mint.decimals),
+        ctx.accounts.price_feed.as_deref(),
+        client_fee_percentage(
+            &ctx.accounts.config,
+            Some(&ctx.accounts.reputation),
+            ctx.accounts.client_exemption.as_deref(),
+        ),
+        offer,
+        clock.unix_timestamp,
+    )?;
+
+    if matches!(params.max_client_fee, Some(max_client_fee) if client_fee > max_client_fee) {
+        return Err(error!(CustomError::FeeExceedsMax));
+    }
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.bump = *ctx.bumps.get("reputation").unwrap();
+    reputation.freelancer = ctx.accounts.deal.freelancer;
+
+    // Past the approval threshold the counter waits for `approve_deal` like a new deal would,
+    // `fund_deal` escrows the difference and the freelancer accepts the deal afterwards.
+    if ctx.accounts.config.needs_approval(offer) {
+        let deal = &mut ctx.accounts.deal;
+        deal.offer = offer;
+        deal.freelancer_net = freelancer_net(&ctx.accounts.config, offer)?;
+        deal.client_fee = client_fee;
+        deal.counter_amount = 0;
+        deal.state = DealStatus::PendingApproval as u8;
+
+        emit!(DealPendingApproval {
+            deal: deal.key(),
+            client: deal.client,
+            offer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        return Ok(());
+    }
+
+    let top_up_amount = offer
+        .checked_add(client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?
+        .saturating_sub(escrow_amount);
+
+    match ctx.accounts.deal.mint {
+        Some(_) => {
+            let (escrow, owner_wallet, token_program) = match (
+                &ctx.accounts.escrow,
+                &ctx.accounts.owner_wallet,
+                &ctx.accounts.token_program,
+            ) {
+                (Some(escrow), Some(owner_wallet), Some(token_program)) => {
+                    (escrow, owner_wallet, token_program)
+                }
+                _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+            };
+
+            deposit_tokens(
+                owner_wallet,
+                &ctx.accounts.client.to_account_info(),
+                escrow,
+                fee_escrow_of(&ctx.accounts.deal, ctx.accounts.fee_escrow.as_deref())?,
+                token_program,
+                top_up_amount,
+                client_fee,
+            )?;
+        }
+        None => {
+            let transfer_ix = system_program::Transfer {
+                from: ctx.accounts.client.to_account_info(),
+                to: ctx.accounts.deal.to_account_info(),
+            };
+
+            let cpi_ctx =
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+            system_program::transfer(cpi_ctx, top_up_amount)?;
+        }
+    }
+
+    let deal = &mut ctx.accounts.deal;
+    deal.offer = offer;
+    deal.freelancer_net = freelancer_net(&ctx.accounts.config, offer)?;
+    deal.client_fee = client_fee;
+    // Never below what's already escrowed, if the fee came out lower the treasury keeps the rest.
+    deal.total_escrow = escrow_amount
+        .checked_add(top_up_amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    deal.counter_amount = 0;
+    deal.time_accepted = clock.unix_timestamp;
+
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_escrowed(top_up_amount, ctx.accounts.config.max_tvl)?;
+
+    deal.start_deadline(ctx.accounts.gig.min_completion_time, &ctx.accounts.config)?;
+
+    deal.state = DealStatus::Accepted as u8;
+
+    // The top-up must leave the escrow holding exactly what the deal now records.
+    if let Some(escrow) = ctx.accounts.escrow.as_mut() {
+        escrow.reload()?;
+    }
+
+    if let Some(fee_escrow) = ctx.accounts.fee_escrow.as_mut() {
+        fee_escrow.reload()?;
+    }
+
+    Escrow::new(
+        &ctx.accounts.deal,
+        ctx.accounts.deal.mint.is_none(),
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+    )?
+    .checked_amount()?;
+
+    let deal = &ctx.accounts.deal;
+    let gig = &mut ctx.accounts.gig;
+    gig.pending_deals = gig
+        .pending_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    emit!(CounterAccepted {
+        deal: deal.key(),
+        offer,
+        client_fee,
+        top_up_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,33 +1,150 @@
-use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
 
-use crate::states::{Deal, Gig};
+use crate::{
+    events::DealAccepted,
+    states::{Config, Deal, DealStatus, Gig, Reputation},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct AcceptDealParams {
+    /// Has to match the terms hash the client created the deal with.
+    pub terms_hash: [u8; 32],
+}
 
 #[derive(Accounts)]
+#[instruction(params: AcceptDealParams)]
 pub struct AcceptDeal<'info> {
-    #[account(
-        mut,
-        constraint = gig.owner.key() == freelancer.key(),
-    )]
+    #[account(mut)]
     pub gig: Account<'info, Gig>,
 
     #[account(
         mut,
         constraint = deal.gig.key() == gig.key(),
-        constraint = deal.state == 1,
+        constraint = deal.state == DealStatus::Created as u8,
     )]
     pub deal: Account<'info, Deal>,
 
+    #[account(
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
     pub freelancer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Created the first time the freelancer accepts a deal, so every ongoing deal has one.
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump,
+        space = Reputation::len(),
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    /// Only needed to stake on SPL gigs, native stakes are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = freelancer_wallet.owner == freelancer.key(),
+        constraint = deal.mint == Some(freelancer_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub freelancer_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
 }
 
-pub fn accept_deal_handler(ctx: Context<AcceptDeal>) -> Result<()> {
+pub fn accept_deal_handler(ctx: Context<AcceptDeal>, params: AcceptDealParams) -> Result<()> {
     let clock = clock::Clock::get()?;
     let gig = &mut ctx.accounts.gig;
     let deal = &mut ctx.accounts.deal;
 
-    gig.pending_deals += 1;
+    if params.terms_hash != deal.terms_hash {
+        return Err(error!(CustomError::TermsMismatch));
+    }
+
+    // The stake sits in the deal's escrow next to the offer until the deal settles.
+    if gig.required_stake > 0 {
+        match deal.mint {
+            Some(_) => {
+                let (escrow, freelancer_wallet, token_program) = match (
+                    &ctx.accounts.escrow,
+                    &ctx.accounts.freelancer_wallet,
+                    &ctx.accounts.token_program,
+                ) {
+                    (Some(escrow), Some(freelancer_wallet), Some(token_program)) => {
+                        (escrow, freelancer_wallet, token_program)
+                    }
+                    _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+                };
+
+                let transfer_ix = Transfer {
+                    from: freelancer_wallet.to_account_info(),
+                    to: escrow.to_account_info(),
+                    authority: ctx.accounts.freelancer.to_account_info(),
+                };
+
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), transfer_ix);
+
+                anchor_spl::token::transfer(cpi_ctx, gig.required_stake)?;
+            }
+            None => {
+                let transfer_ix = system_program::Transfer {
+                    from: ctx.accounts.freelancer.to_account_info(),
+                    to: deal.to_account_info(),
+                };
+
+                let cpi_ctx =
+                    CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+                system_program::transfer(cpi_ctx, gig.required_stake)?;
+            }
+        }
+    }
+
+    let reputation = &mut ctx.accounts.reputation;
+
+    reputation.bump = *ctx.bumps.get("reputation").unwrap();
+    reputation.freelancer = deal.freelancer;
+
+    gig.pending_deals = gig
+        .pending_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    deal.stake_amount = gig.required_stake;
     deal.time_accepted = clock.unix_timestamp;
-    deal.state = 3;
+
+    deal.start_deadline(gig.min_completion_time, &ctx.accounts.config)?;
+
+    deal.state = DealStatus::Accepted as u8;
+
+    emit!(DealAccepted {
+        deal: deal.key(),
+        freelancer: deal.freelancer,
+        terms_hash: deal.terms_hash,
+        stake_amount: deal.stake_amount,
+        deadline: deal.deadline,
+        timestamp: clock.unix_timestamp,
+    });
 
     Ok(())
 }
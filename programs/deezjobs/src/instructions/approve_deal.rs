@@ -0,0 +1,43 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::DealApproved,
+    states::{Config, Deal, DealStatus},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct ApproveDeal<'info> {
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::PendingApproval as u8 @ CustomError::InvalidState,
+    )]
+    pub deal: Account<'info, Deal>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+}
+
+/// Clears a deal above `config.require_approval_above` so the client can fund it.
+pub fn approve_deal_handler(ctx: Context<ApproveDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+
+    deal.state = DealStatus::Approved as u8;
+
+    emit!(DealApproved {
+        deal: deal.key(),
+        approved_by: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
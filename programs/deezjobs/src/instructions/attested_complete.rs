@@ -0,0 +1,137 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        clock, ed25519_program,
+        instruction::Instruction,
+        sysvar::{
+            self,
+            instructions::{load_current_index_checked, load_instruction_at_checked},
+        },
+    },
+};
+
+// Also brings in the client modules generated for `CompleteDeal`, nested below.
+use super::*;
+use crate::{events::DeliveryAttested, states::DealStatus, CustomError};
+
+/// Signature count and padding at the start of an ed25519 program instruction.
+const ED25519_HEADER_LEN: usize = 2;
+/// Offsets of a signature in an ed25519 program instruction, seven u16.
+const ED25519_OFFSETS_LEN: usize = 14;
+/// Instruction index of an offset pointing into the ed25519 instruction itself.
+const ED25519_SELF_INDEX: usize = u16::MAX as usize;
+const ED25519_PUBKEY_LEN: usize = 32;
+
+#[derive(Accounts)]
+pub struct AttestedComplete<'info> {
+    pub complete: CompleteDeal<'info>,
+
+    /// CHECK: the instructions sysvar, holding the ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Completes a delivered deal without the client's approval once `config.attestor` signed off
+/// on the delivery, eg. an oracle that saw the work merged. The instruction right before this
+/// one must be the ed25519 program verifying the attestor's signature over the deal address,
+/// its `delivery_hash` and its `time_delivered`. The timestamp ties the attestation to one
+/// submission, so it can't be replayed once the work was sent back and resubmitted.
+pub fn attested_complete_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AttestedComplete<'info>>,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &ctx.accounts.complete.deal;
+
+    if deal.status()? != DealStatus::Delivered {
+        return Err(error!(CustomError::InvalidState));
+    }
+
+    let attestor = ctx
+        .accounts
+        .complete
+        .config
+        .attestor
+        .ok_or(error!(CustomError::AttestorNotSet))?;
+
+    let instructions = &ctx.accounts.instructions;
+
+    let current_index = usize::from(load_current_index_checked(instructions)?);
+    let previous_index = current_index
+        .checked_sub(1)
+        .ok_or(error!(CustomError::InvalidAttestation))?;
+    let ed25519_ix = load_instruction_at_checked(previous_index, instructions)?;
+
+    let (signer, message) = ed25519_signed_message(&ed25519_ix)?;
+
+    if signer != attestor.as_ref() {
+        return Err(error!(CustomError::InvalidAttestation));
+    }
+
+    let deal_key = deal.key();
+    let delivery_hash = deal.delivery_hash;
+    let expected_message = [
+        deal_key.as_ref(),
+        &delivery_hash,
+        &deal.time_delivered.to_le_bytes(),
+    ]
+    .concat();
+
+    if message != expected_message.as_slice() {
+        return Err(error!(CustomError::AttestationMismatch));
+    }
+
+    settle_or_hold(Context::new(
+        ctx.program_id,
+        &mut ctx.accounts.complete,
+        ctx.remaining_accounts,
+        ctx.bumps,
+    ))?;
+
+    emit!(DeliveryAttested {
+        deal: deal_key,
+        attestor,
+        delivery_hash,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Public key and message of the one signature verified by an ed25519 program instruction.
+/// Both have to be in the instruction's own data, the runtime doesn't check which instruction
+/// the offsets point into.
+fn ed25519_signed_message(ix: &Instruction) -> Result<(&[u8], &[u8])> {
+    let data = &ix.data;
+
+    if ix.program_id != ed25519_program::ID
+        || data.len() < ED25519_HEADER_LEN + ED25519_OFFSETS_LEN
+        || data[0] != 1
+    {
+        return Err(error!(CustomError::InvalidAttestation));
+    }
+
+    let offset = |index: usize| {
+        let start = ED25519_HEADER_LEN + index * 2;
+
+        usize::from(u16::from_le_bytes([data[start], data[start + 1]]))
+    };
+
+    // signature_offset, signature_instruction_index, public_key_offset,
+    // public_key_instruction_index, message_data_offset, message_data_size,
+    // message_instruction_index
+    if offset(1) != ED25519_SELF_INDEX
+        || offset(3) != ED25519_SELF_INDEX
+        || offset(6) != ED25519_SELF_INDEX
+    {
+        return Err(error!(CustomError::InvalidAttestation));
+    }
+
+    let public_key = data
+        .get(offset(2)..offset(2) + ED25519_PUBKEY_LEN)
+        .ok_or(error!(CustomError::InvalidAttestation))?;
+    let message = data
+        .get(offset(4)..offset(4) + offset(5))
+        .ok_or(error!(CustomError::InvalidAttestation))?;
+
+    Ok((public_key, message))
+}
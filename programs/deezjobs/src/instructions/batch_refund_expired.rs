@@ -0,0 +1,153 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{Token, TokenAccount},
+};
+
+use crate::{
+    events::DealRefunded,
+    states::{Config, Deal, DealStatus, Gig, Reputation, Stats},
+    utils::refund_escrow,
+    CustomError,
+};
+
+/// Each deal is passed through `remaining_accounts` as the group
+/// `[deal, gig, client, escrow, owner_wallet, reputation, fee_escrow]`, the same accounts as
+/// `refund_expired_deal`. Accounts that are not needed, eg. the escrow of native SOL deals, are set
+/// to the program ID.
+pub const ACCOUNTS_PER_REFUND: usize = 7;
+
+#[derive(Accounts)]
+pub struct BatchRefundExpired<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Refunds every expired deal of the batch, skipping deals that can't be refunded (yet).
+/// Returns how many deals were refunded.
+pub fn batch_refund_expired_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchRefundExpired<'info>>,
+) -> Result<u32> {
+    let clock = clock::Clock::get()?;
+    let config = &ctx.accounts.config;
+
+    let groups = ctx.remaining_accounts.chunks_exact(ACCOUNTS_PER_REFUND);
+
+    if !groups.remainder().is_empty() {
+        return Err(error!(CustomError::InvalidBatchAccounts));
+    }
+
+    let mut refunded = 0;
+
+    for accounts in groups {
+        let mut deal = Account::<Deal>::try_from(&accounts[0])?;
+        let status = deal.status()?;
+
+        let refundable_after = deal
+            .deadline
+            .checked_add(config.refund_grace_period)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        if !matches!(
+            status,
+            DealStatus::Created | DealStatus::CounterOffered | DealStatus::Accepted
+        )
+            || deal.deadline == 0
+            || clock.unix_timestamp <= refundable_after
+        {
+            continue;
+        }
+
+        let mut gig = Account::<Gig>::try_from(&accounts[1])?;
+        let client = &accounts[2];
+
+        if gig.key() != deal.gig || client.key() != deal.client {
+            return Err(error!(CustomError::InvalidBatchAccounts));
+        }
+
+        let (escrow, owner_wallet, fee_escrow) = match deal.mint {
+            Some(mint) => {
+                let escrow = Account::<TokenAccount>::try_from(&accounts[3])?;
+                let owner_wallet = Account::<TokenAccount>::try_from(&accounts[4])?;
+
+                if escrow.key() != get_associated_token_address(&deal.key(), &mint)
+                    || owner_wallet.owner != deal.client
+                    || owner_wallet.mint != mint
+                {
+                    return Err(error!(CustomError::InvalidBatchAccounts));
+                }
+
+                // Checked against `deal.fee_escrow` by the refund.
+                let fee_escrow = match deal.fee_escrow {
+                    Some(_) => Some(Account::<TokenAccount>::try_from(&accounts[6])?),
+                    None => None,
+                };
+
+                (Some(escrow), Some(owner_wallet), fee_escrow)
+            }
+            None => (None, None, None),
+        };
+
+        if status == DealStatus::Accepted {
+            let mut reputation = Account::<Reputation>::try_from(&accounts[5])?;
+            let reputation_key = Pubkey::create_program_address(
+                &[b"reputation", deal.freelancer.as_ref(), &[reputation.bump]],
+                ctx.program_id,
+            )
+            .map_err(|_| error!(CustomError::InvalidBatchAccounts))?;
+
+            if reputation.key() != reputation_key {
+                return Err(error!(CustomError::InvalidBatchAccounts));
+            }
+
+            reputation.cancelled_deals = reputation
+                .cancelled_deals
+                .checked_add(1)
+                .ok_or(error!(CustomError::MathOverflow))?;
+            reputation.exit(ctx.program_id)?;
+
+            gig.pending_deals = gig.pending_deals.saturating_sub(1);
+        }
+
+        // Includes any stake, forfeited to the client like in `refund_expired_deal`.
+        let refund_amount = refund_escrow(
+            &deal,
+            client,
+            escrow.as_ref(),
+            fee_escrow.as_ref(),
+            owner_wallet.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            deal.mint.is_none(),
+        )?;
+
+        deal.state = DealStatus::Refunded as u8;
+        gig.active_deals = gig.active_deals.saturating_sub(1);
+        ctx.accounts.stats.load_mut()?.record_closed(deal.total_escrow);
+
+        // Written back right away, the next group may share the same gig or reputation.
+        deal.exit(ctx.program_id)?;
+        gig.exit(ctx.program_id)?;
+
+        emit!(DealRefunded {
+            deal: deal.key(),
+            refund_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        refunded += 1;
+    }
+
+    Ok(refunded)
+}
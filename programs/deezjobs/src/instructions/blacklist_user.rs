@@ -0,0 +1,59 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::BlacklistUpdated,
+    states::{Blacklist, Config},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct BlacklistUserParams {
+    pub user: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(params: BlacklistUserParams)]
+pub struct BlacklistUser<'info> {
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"blacklist", params.user.as_ref()],
+        bump,
+        space = Blacklist::len(),
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn blacklist_user_handler(
+    ctx: Context<BlacklistUser>,
+    params: BlacklistUserParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let blacklist = &mut ctx.accounts.blacklist;
+
+    blacklist.bump = *ctx.bumps.get("blacklist").unwrap();
+    blacklist.user = params.user;
+    blacklist.time_added = clock.unix_timestamp;
+
+    emit!(BlacklistUpdated {
+        user: params.user,
+        blacklisted: true,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::DealCancelled,
+    states::{Config, Deal, DealIndex, DealStatus, Gig, Stats},
+    utils::{percentage_of, Escrow},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct CancelDeal<'info> {
+    /// Offers can be cancelled, including offers the freelancer countered. Once accepted the
+    /// freelancer may already be working on it, so past `config.client_cancel_window` it takes
+    /// a gig with a cancel fee.
+    #[account(
+        mut,
+        constraint = matches!(
+            deal.status()?,
+            DealStatus::Created | DealStatus::CounterOffered | DealStatus::Accepted
+        ),
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key(),
+    )]
+    /// CHECK: should be the freelancer of the deal
+    pub freelancer: UncheckedAccount<'info>,
+
+    /// Receives the cancel fee, only needed for SPL gigs.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer,
+    )]
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key(),
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    /// Updated with the deal's new state if passed, see `DealIndex`.
+    #[account(
+        mut,
+        seeds = [b"deal_index", deal.freelancer.as_ref()],
+        bump = deal_index.bump,
+    )]
+    pub deal_index: Option<Box<Account<'info, DealIndex>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn cancel_deal_handler(ctx: Context<CancelDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &ctx.accounts.gig;
+    let is_accepted = ctx.accounts.deal.state == DealStatus::Accepted as u8;
+
+    // Right after acceptance the client can still back out, the freelancer has barely started.
+    let cancel_window = ctx.accounts.config.client_cancel_window;
+    let time_accepted = ctx.accounts.deal.time_accepted;
+    let free_until = time_accepted.saturating_add(cancel_window);
+    let within_free_window = is_accepted && cancel_window > 0 && clock.unix_timestamp <= free_until;
+
+    if is_accepted && !within_free_window && gig.cancel_fee_bps == 0 {
+        return Err(error!(CustomError::InvalidState));
+    }
+
+    // Offers the freelancer hasn't taken yet are refunded in full.
+    let cancel_fee = if is_accepted && !within_free_window {
+        percentage_of(ctx.accounts.deal.unreleased_offer()?, gig.cancel_fee_bps)?
+    } else {
+        0
+    };
+
+    let escrow = Escrow::new(
+        &ctx.accounts.deal,
+        ctx.accounts.deal.mint.is_none(),
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+
+    let escrow_amount = escrow.checked_amount()?;
+    // The freelancer didn't fail the deal, so any stake goes back to them with the fee.
+    let freelancer_amount = cancel_fee
+        .checked_add(ctx.accounts.deal.stake_amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    let refund_amount = escrow_amount
+        .checked_sub(freelancer_amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if freelancer_amount > 0 {
+        escrow.release(
+            ctx.accounts.freelancer_token_account.as_deref(),
+            &ctx.accounts.freelancer,
+            freelancer_amount,
+        )?;
+    }
+
+    escrow.release_fee(
+        ctx.accounts.owner_wallet.as_deref(),
+        &ctx.accounts.client,
+        refund_amount,
+    )?;
+    escrow.sweep(ctx.accounts.owner_wallet.as_deref(), &ctx.accounts.client)?;
+    escrow.close(&ctx.accounts.client)?;
+
+    let deal = &mut ctx.accounts.deal;
+    deal.cancel_fee_paid = cancel_fee;
+    deal.state = DealStatus::Cancelled as u8;
+
+    if let Some(deal_index) = ctx.accounts.deal_index.as_mut() {
+        deal_index.record(deal.key(), deal.state);
+    }
+
+    let gig = &mut ctx.accounts.gig;
+    gig.active_deals = gig.active_deals.saturating_sub(1);
+
+    if is_accepted {
+        gig.pending_deals = gig.pending_deals.saturating_sub(1);
+    }
+
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_closed(deal.total_escrow);
+
+    emit!(DealCancelled {
+        deal: deal.key(),
+        cancelled_by: ctx.accounts.client.key(),
+        refund_amount,
+        cancel_fee,
+        within_free_window,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::RetainerCancelled,
+    states::{Config, DealStatus, Retainer, Stats},
+    utils::{freelancer_net, lamports_above_rent, transfer_lamports},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct CancelRetainer<'info> {
+    /// The periods not yet started and the rent go back to the client.
+    #[account(
+        mut,
+        close = client,
+        constraint = retainer.state == DealStatus::Created as u8 @ CustomError::InvalidState,
+    )]
+    pub retainer: Box<Account<'info, Retainer>>,
+
+    /// Either the client or the freelancer.
+    #[account(
+        constraint = signer.key() == retainer.client
+            || signer.key() == retainer.freelancer @ CustomError::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = client.key() == retainer.client.key(),
+    )]
+    /// CHECK: should be the client of the retainer
+    pub client: UncheckedAccount<'info>,
+
+    /// Paid for the part of the current period already worked.
+    #[account(
+        mut,
+        constraint = freelancer.key() == retainer.freelancer.key(),
+    )]
+    /// CHECK: should be the freelancer of the retainer
+    pub freelancer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury.key() @ CustomError::InvalidTreasury,
+    )]
+    /// CHECK: constraint to the config's treasury
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+}
+
+/// Ends the retainer. The freelancer is paid for the time elapsed in the current period, the
+/// rest of it and every later period are refunded. A period that's already due has to be
+/// released first so the freelancer is paid for it in full.
+pub fn cancel_retainer_handler(ctx: Context<CancelRetainer>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let retainer = &ctx.accounts.retainer;
+    let retainer_info = retainer.to_account_info();
+
+    if clock.unix_timestamp >= retainer.next_release_ts {
+        return Err(error!(CustomError::RetainerPeriodDue));
+    }
+
+    let escrow_amount = lamports_above_rent(&retainer_info)?;
+
+    if Some(escrow_amount) != retainer.expected_escrow() {
+        return Err(error!(CustomError::EscrowBalanceMismatch));
+    }
+
+    let period_start = retainer.next_release_ts - retainer.period_seconds;
+    let elapsed = (clock.unix_timestamp - period_start).max(0);
+    let pro_rata = |amount: u64| {
+        u128::from(amount)
+            .checked_mul(elapsed as u128)
+            .and_then(|amount| amount.checked_div(retainer.period_seconds as u128))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(error!(CustomError::MathOverflow))
+    };
+
+    let earned_amount = pro_rata(retainer.per_period_amount)?;
+    let earned_client_fee = pro_rata(retainer.per_period_client_fee)?;
+    let earned_escrow = earned_amount
+        .checked_add(earned_client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    let freelancer_pay = freelancer_net(&ctx.accounts.config, earned_amount)?;
+    let treasury_pay = earned_escrow
+        .checked_sub(freelancer_pay)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    let refund_amount = escrow_amount
+        .checked_sub(earned_escrow)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    transfer_lamports(&retainer_info, &ctx.accounts.freelancer, freelancer_pay)?;
+    transfer_lamports(&retainer_info, &ctx.accounts.treasury, treasury_pay)?;
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_fees(treasury_pay)?;
+    stats.record_closed(escrow_amount);
+
+    let retainer = &mut ctx.accounts.retainer;
+    retainer.state = DealStatus::Cancelled as u8;
+
+    emit!(RetainerCancelled {
+        retainer: retainer.key(),
+        cancelled_by: ctx.accounts.signer.key(),
+        periods_refunded: retainer.periods_total - retainer.periods_released,
+        freelancer_amount: freelancer_pay,
+        treasury_amount: treasury_pay,
+        refund_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,20 +1,37 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::clock};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount, Transfer, CloseAccount},
+    token::{Mint, Token, TokenAccount},
 };
 
-use crate::states::{Deal, Gig};
+use crate::{
+    events::{DealCancelled, DealClosed},
+    states::{Deal, DealStatus, Gig, Stats},
+    utils::{lamports_above_rent, refund_escrow, transfer_lamports, Escrow},
+    CustomError,
+};
 
 #[derive(Accounts)]
 pub struct CloseDeal<'info> {
+    /// Offers are refunded before closing. Settled deals can be closed once indexers have
+    /// picked up their final state, giving the rent back to the client.
+    /// Unfunded proposals can be withdrawn by the freelancer who paid their rent.
+    /// Deals waiting for approval or funding are withdrawn like offers, with nothing to refund.
     #[account(
         mut,
-        constraint = deal.state & 2 != 2,
+        close = client,
+        constraint = matches!(
+            deal.status()?,
+            DealStatus::Created
+                | DealStatus::Proposed
+                | DealStatus::PendingApproval
+                | DealStatus::Approved
+        ) || deal.status()?.is_terminal(),
     )]
     pub deal: Account<'info, Deal>,
 
     #[account(
+        mut,
         constraint = gig.key() == deal.gig.key(),
     )]
     pub gig: Account<'info, Gig>,
@@ -26,24 +43,31 @@ pub struct CloseDeal<'info> {
     /// CHECK: should be the owner of the deal
     pub client: UncheckedAccount<'info>,
 
+    /// Not needed for native SOL gigs, the escrowed lamports are refunded when the deal is closed.
+    /// Already closed for settled deals.
     #[account(
         mut,
         associated_token::mint = mint,
-        associated_token::authority = deal,      
+        associated_token::authority = deal,
     )]
-    pub escrow: Account<'info, TokenAccount>,
+    pub escrow: Option<Account<'info, TokenAccount>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
         constraint = owner_wallet.owner == client.key(),
-        constraint = owner_wallet.mint == mint.key(),
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
     )]
-    pub owner_wallet: Account<'info, TokenAccount>,
+    pub owner_wallet: Option<Account<'info, TokenAccount>>,
 
     #[account(
-        constraint = mint.key() == gig.mint.unwrap().key(),
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
     )]
-    pub mint: Account<'info, Mint>,
+    pub mint: Option<Account<'info, Mint>>,
 
     #[account(
         mut,
@@ -51,75 +75,100 @@ pub struct CloseDeal<'info> {
     )]
     pub signer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
 }
 
 pub fn close_deal_handler(ctx: Context<CloseDeal>) -> Result<()> {
-    let client = &mut ctx.accounts.client;
-    let deal = &mut ctx.accounts.deal;
-    let client_wallet = &mut ctx.accounts.owner_wallet;
-    let escrow = &mut ctx.accounts.escrow;
-    let gig = &ctx.accounts.gig;
-
-    // Transfer escrow funds back to the client
-
-    let gig_key = gig.key();
-    let client_key = client.key();
-    let deal_bump = deal.bump.to_le_bytes();
-
-    let inner = vec![
-        b"deal".as_ref(),
-        client_key.as_ref(),
-        gig_key.as_ref(),
-        deal_bump.as_ref(),
-    ];
-
-    let deal_sig = vec![inner.as_slice()];
-
-    let transfer_ix = Transfer {
-        from: escrow.to_account_info(),
-        to: client_wallet.to_account_info(),
-        authority: deal.to_account_info(),
-    };
-
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        transfer_ix,
-        deal_sig.as_slice(),
-    );
-
-    anchor_spl::token::transfer(cpi_ctx, escrow.amount)?;
-
-    // Close escrow account
-
-    let cpi_close = CloseAccount {
-        account: escrow.to_account_info(),
-        destination: client.to_account_info(),
-        authority: deal.to_account_info(),
-    };
-
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(), 
-        cpi_close, 
-        deal_sig.as_slice()
-    );
-
-    anchor_spl::token::close_account(cpi_ctx)?;
-
-    // Close deal account
-
-    let source_account_info = deal.to_account_info();
-    let dest_account_info = client.to_account_info();
-
-    let dest_starting_lamports = dest_account_info.lamports();
-    **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
-        .checked_add(source_account_info.lamports())
-        .unwrap();
-    **source_account_info.lamports.borrow_mut() = 0;
-
-    let mut source_data = source_account_info.data.borrow_mut();
-    source_data.fill(0);
+    let client = ctx.accounts.client.to_account_info();
+    let deal = &ctx.accounts.deal;
+    let gig = &mut ctx.accounts.gig;
+    let clock = clock::Clock::get()?;
+
+    if matches!(
+        deal.status()?,
+        DealStatus::Created | DealStatus::PendingApproval | DealStatus::Approved
+    ) {
+        let refund_amount = refund_escrow(
+            deal,
+            &client,
+            ctx.accounts.escrow.as_ref(),
+            ctx.accounts.fee_escrow.as_ref(),
+            ctx.accounts.owner_wallet.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            deal.mint.is_none(),
+        )?;
+
+        gig.active_deals = gig.active_deals.saturating_sub(1);
+        ctx.accounts
+            .stats
+            .load_mut()?
+            .record_closed(ctx.accounts.deal.total_escrow);
+
+        emit!(DealCancelled {
+            deal: deal.key(),
+            cancelled_by: ctx.accounts.signer.key(),
+            refund_amount,
+            cancel_fee: 0,
+            within_free_window: false,
+            timestamp: clock.unix_timestamp,
+        });
+
+        return Ok(());
+    }
+
+    // Nothing was escrowed yet, the rent goes back to the freelancer instead of the client.
+    if deal.state == DealStatus::Proposed as u8 {
+        if ctx.accounts.signer.key() != deal.freelancer {
+            return Err(error!(CustomError::Unauthorized));
+        }
+
+        let deal_info = deal.to_account_info();
+        transfer_lamports(&deal_info, &ctx.accounts.signer, deal_info.lamports())?;
+
+        emit!(DealClosed {
+            deal: deal.key(),
+            closed_by: ctx.accounts.signer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        return Ok(());
+    }
+
+    // Closing never sweeps funds along with the rent, whatever is left has to be settled first.
+    if deal.mint.is_none() && lamports_above_rent(&deal.to_account_info())? > 0 {
+        return Err(error!(CustomError::EscrowNotEmpty));
+    }
+
+    // Settlement closes the escrow already, only an empty one may still be around.
+    if let Some(escrow) = &ctx.accounts.escrow {
+        let escrow = Escrow::new(
+            deal,
+            false,
+            Some(escrow),
+            ctx.accounts.fee_escrow.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+
+        if escrow.amount()? > 0 {
+            return Err(error!(CustomError::EscrowNotEmpty));
+        }
+
+        escrow.close(&client)?;
+    }
+
+    emit!(DealClosed {
+        deal: deal.key(),
+        closed_by: ctx.accounts.signer.key(),
+        timestamp: clock.unix_timestamp,
+    });
 
     Ok(())
 }
@@ -1,12 +1,21 @@
-use crate::states::{Config, Gig};
-use anchor_lang::prelude::*;
+use crate::{
+    events::GigClosed,
+    states::{Config, Gig, GigStatus},
+    CustomError,
+};
+use anchor_lang::{prelude::*, solana_program::clock};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct CloseGigParams {
+    /// Also close the account and return the rent to the payer, only once it has no active deals.
+    /// Otherwise the gig is kept in `Closed` state so its deals can still be settled.
+    pub reclaim_rent: bool,
+}
 
 #[derive(Accounts)]
+#[instruction(params: CloseGigParams)]
 pub struct CloseGig<'info> {
-    #[account(
-        mut,
-        constraint = gig.pending_deals == 0,
-    )]
+    #[account(mut)]
     pub gig: Account<'info, Gig>,
 
     #[account(
@@ -25,7 +34,25 @@ pub struct CloseGig<'info> {
     pub config: Account<'info, Config>,
 }
 
-pub fn close_gig_handler(ctx: Context<CloseGig>) -> Result<()> {
+pub fn close_gig_handler(ctx: Context<CloseGig>, params: CloseGigParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+
+    if params.reclaim_rent && ctx.accounts.gig.active_deals > 0 {
+        return Err(error!(CustomError::GigHasActiveDeals));
+    }
+
+    ctx.accounts.gig.state = GigStatus::Closed as u8;
+
+    emit!(GigClosed {
+        gig: ctx.accounts.gig.key(),
+        reclaimed: params.reclaim_rent,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if !params.reclaim_rent {
+        return Ok(());
+    }
+
     let source_account_info = ctx.accounts.gig.to_account_info();
     let dest_account_info = ctx.accounts.payer.to_account_info();
 
@@ -0,0 +1,44 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::GigAccountClosed,
+    states::{Gig, GigStatus},
+    CustomError,
+};
+
+/// Closes a gig left in `Closed` state by `close_gig`, once none of its deals are active any more.
+#[derive(Accounts)]
+pub struct CloseGigAccount<'info> {
+    #[account(
+        mut,
+        close = payer,
+        constraint = gig.state == GigStatus::Closed as u8 @ CustomError::GigNotClosable,
+        constraint = gig.active_deals == 0 @ CustomError::GigNotClosable,
+    )]
+    pub gig: Account<'info, Gig>,
+
+    /// Gets the rent back, the owner unless the gig was paid for by someone else.
+    #[account(
+        mut,
+        constraint = payer.key() == gig.payer.key() @ CustomError::PayerMismatch,
+    )]
+    /// CHECK: constraint to gig's payer
+    pub payer: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = owner.key() == gig.owner.key() @ CustomError::Unauthorized,
+    )]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_gig_account_handler(ctx: Context<CloseGigAccount>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+
+    emit!(GigAccountClosed {
+        gig: ctx.accounts.gig.key(),
+        owner: ctx.accounts.owner.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
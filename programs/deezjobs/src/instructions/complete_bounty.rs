@@ -0,0 +1,110 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::BountyCompleted,
+    states::{Bounty, Config, DealStatus, Reputation, Stats},
+    utils::{freelancer_net, lamports_above_rent, transfer_lamports},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct CompleteBounty<'info> {
+    /// Settled in one go, the rent goes back to the creator.
+    #[account(
+        mut,
+        close = creator,
+        constraint = bounty.state == DealStatus::Delivered as u8 @ CustomError::InvalidState,
+    )]
+    pub bounty: Box<Account<'info, Bounty>>,
+
+    #[account(
+        mut,
+        constraint = creator.key() == bounty.creator.key() @ CustomError::Unauthorized,
+    )]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == bounty.freelancer.key(),
+    )]
+    /// CHECK: should be the freelancer of the bounty
+    pub freelancer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury.key(),
+    )]
+    /// CHECK:
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists once the freelancer accepted a deal, the bounty isn't recorded without it.
+    #[account(
+        mut,
+        seeds = [b"reputation", bounty.freelancer.as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Option<Box<Account<'info, Reputation>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+}
+
+/// Pays the pooled offer to the freelancer, the client fees and freelancer fee go to the treasury.
+/// The freelancer has to have submitted the work with `submit_bounty` first.
+pub fn complete_bounty_handler(ctx: Context<CompleteBounty>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let bounty = &ctx.accounts.bounty;
+    let bounty_info = bounty.to_account_info();
+
+    let escrow_amount = lamports_above_rent(&bounty_info)?;
+
+    if Some(escrow_amount) != bounty.expected_escrow() {
+        return Err(error!(CustomError::EscrowBalanceMismatch));
+    }
+
+    let freelancer_pay = freelancer_net(&ctx.accounts.config, bounty.offer)?;
+    let treasury_pay = escrow_amount
+        .checked_sub(freelancer_pay)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    transfer_lamports(&bounty_info, &ctx.accounts.freelancer, freelancer_pay)?;
+    transfer_lamports(&bounty_info, &ctx.accounts.treasury, treasury_pay)?;
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_fees(treasury_pay)?;
+    stats.record_closed(escrow_amount);
+
+    if let Some(reputation) = ctx.accounts.reputation.as_mut() {
+        reputation.completed_deals = reputation
+            .completed_deals
+            .checked_add(1)
+            .ok_or(error!(CustomError::MathOverflow))?;
+        reputation.total_earned = reputation
+            .total_earned
+            .checked_add(freelancer_pay)
+            .ok_or(error!(CustomError::MathOverflow))?;
+    }
+
+    let bounty = &mut ctx.accounts.bounty;
+    bounty.state = DealStatus::Completed as u8;
+    bounty.time_completed = clock.unix_timestamp;
+
+    emit!(BountyCompleted {
+        bounty: bounty.key(),
+        freelancer_amount: freelancer_pay,
+        treasury_amount: treasury_pay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,7 +1,15 @@
-use anchor_lang::prelude::*;
-use anchor_spl::{token::{Mint, TokenAccount, Token, Transfer, CloseAccount}, associated_token::AssociatedToken};
-
-use crate::states::{Config, Gig, Deal};
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    events::{DealCompleted, ReferrerPaid, SettlementPending},
+    states::{Config, Deal, DealIndex, DealStatus, FreelancerVault, Gig, Reputation, Stats},
+    utils::{percentage_of, referral_payout_amount, Escrow},
+    CustomError,
+};
 
 #[derive(Accounts)]
 pub struct CompleteDeal<'info> {
@@ -13,12 +21,17 @@ pub struct CompleteDeal<'info> {
 
     #[account(
         mut,
-        constraint = deal.state & 2 == 2,
+        constraint = matches!(
+            deal.status()?,
+            DealStatus::Accepted | DealStatus::Delivered | DealStatus::PendingSettlement
+        ),
+        constraint = deal.milestones.is_empty() @ CustomError::DealHasMilestones,
     )]
     pub deal: Box<Account<'info, Deal>>,
 
     #[account(
-        constraint = freelancer.key() == gig.owner.key(),
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key(),
     )]
     /// CHECK:
     pub freelancer: UncheckedAccount<'info>,
@@ -31,51 +44,103 @@ pub struct CompleteDeal<'info> {
     pub client: UncheckedAccount<'info>,
 
     #[account(
-        constraint = mint.key() == gig.mint.unwrap().key(),
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
     )]
-    pub mint: Box<Account<'info, Mint>>,
+    pub mint: Option<Box<Account<'info, Mint>>>,
 
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
     #[account(
         mut,
         associated_token::mint = mint,
         associated_token::authority = deal,
     )]
-    pub escrow: Box<Account<'info, TokenAccount>>,
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         init_if_needed,
-        payer = client, 
-        associated_token::mint = mint, 
+        payer = signer,
+        associated_token::mint = mint,
         associated_token::authority = freelancer,
     )]
-    pub freelancer_token_account: Box<Account<'info, TokenAccount>>,
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
 
+    /// Receives the freelancer's pay instead of their wallet while
+    /// `config.defer_freelancer_payouts` is set.
     #[account(
-        constraint = referrer.key() == deal.referrer.unwrap().key(),
+        init_if_needed,
+        payer = signer,
+        seeds = [b"freelancer_vault", deal.freelancer.as_ref()],
+        bump,
+        space = FreelancerVault::len(),
     )]
-    /// CHECK:
-    pub referrer: Option<UncheckedAccount<'info>>,
+    pub freelancer_vault: Option<Box<Account<'info, FreelancerVault>>>,
 
     #[account(
         init_if_needed,
-        payer = client, 
-        associated_token::mint = mint, 
-        associated_token::authority = referrer,
+        payer = signer,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer_vault,
     )]
+    pub freelancer_vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Receives the late penalty of SPL deals, only needed if the deal is completed after its deadline.
+    #[account(
+        mut,
+        constraint = client_token_account.owner == client.key(),
+        constraint = deal.mint == Some(client_token_account.mint) @ CustomError::MintMismatch,
+    )]
+    pub client_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Receives the referral fee of native SOL deals, ignored if it isn't `deal.referrer`.
+    #[account(mut)]
+    /// CHECK: compared against the deal's referrer
+    pub referrer: Option<UncheckedAccount<'info>>,
+
+    /// Receives the referral fee of SPL deals, ignored if it isn't owned by `deal.referrer`.
+    /// Must be of `config.referrer_payout_mint` if set, for native SOL deals as well.
+    #[account(mut)]
     pub referrer_token_account: Option<Box<Account<'info, TokenAccount>>>,
 
+    /// Pays the referral fee instead of the escrow when `config.referrer_payout_mint` is set.
+    #[account(
+        mut,
+        seeds = [b"referrer_rewards", referrer_rewards_vault.mint.as_ref()],
+        bump,
+        constraint = config.referrer_payout_mint == Some(referrer_rewards_vault.mint),
+    )]
+    pub referrer_rewards_vault: Option<Box<Account<'info, TokenAccount>>>,
+
     #[account(
+        constraint = config.referrer_payout_mint == Some(referrer_payout_mint.key()),
+    )]
+    pub referrer_payout_mint: Option<Box<Account<'info, Mint>>>,
+
+    /// Converts the referral fee into the payout mint, needed if the gig's mint has a feed.
+    /// CHECK: compared against the price feed registered in the config for the gig's mint
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
         constraint = treasury.key() == config.treasury.key(),
     )]
     /// CHECK:
     pub treasury: UncheckedAccount<'info>,
 
+    /// The treasury's associated token account for the deal's mint, it holds one for each mint.
     #[account(
         mut,
-        associated_token::mint = mint, 
-        associated_token::authority = treasury,
+        constraint = deal.mint == Some(treasury_token_account.mint) @ CustomError::TreasuryMintMismatch,
+        constraint = treasury_token_account.owner == config.key() @ CustomError::InvalidTreasury,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.key(), &treasury_token_account.mint)
+            @ CustomError::InvalidTreasury,
     )]
-    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub treasury_token_account: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         seeds = [b"config"],
@@ -83,141 +148,338 @@ pub struct CompleteDeal<'info> {
     )]
     pub config: Box<Account<'info, Config>>,
 
+    /// Created when the freelancer accepted the deal.
     #[account(
         mut,
-        constraint = signer.key() == client.key() || signer.key() == config.authority.key(),
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = reputation.bump,
     )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    /// The client, or anyone once the review window of delivered work is over, see `finalize_delivery`.
+    /// Also anyone paying out a deal pending settlement, see `finalize_settlement`.
+    #[account(mut)]
     pub signer: Signer<'info>,
 
+    /// Updated with the deal's new state if passed, see `DealIndex`.
+    #[account(
+        mut,
+        seeds = [b"deal_index", deal.freelancer.as_ref()],
+        bump = deal_index.bump,
+    )]
+    pub deal_index: Option<Box<Account<'info, DealIndex>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 pub fn complete_deal_handler(ctx: Context<CompleteDeal>) -> Result<()> {
+    if ctx.accounts.signer.key() != ctx.accounts.client.key() {
+        return Err(error!(CustomError::Unauthorized));
+    }
 
-    let gig = &mut ctx.accounts.gig;
+    settle_or_hold(ctx)
+}
+
+/// Settles the deal, or holds it until `config.settlement_delay` has passed if one is set.
+pub fn settle_or_hold(ctx: Context<CompleteDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let settlement_delay = ctx.accounts.config.settlement_delay;
     let deal = &mut ctx.accounts.deal;
-    let client = &mut ctx.accounts.client;
-    let config = &ctx.accounts.config;
 
-    let escrow = &mut ctx.accounts.escrow;
-    let freelancer_token_account = &mut ctx.accounts.freelancer_token_account;
-    let referrer_token_account = &mut ctx.accounts.referrer_token_account;
-    let treasury_token_account = &mut ctx.accounts.treasury_token_account;
+    // Already completed, waiting for `finalize_settlement`.
+    if deal.status()? == DealStatus::PendingSettlement {
+        return Err(error!(CustomError::InvalidState));
+    }
 
-    gig.pending_deals -= 1;
-    deal.state |= 8;
+    if settlement_delay == 0 {
+        return settle_deal(ctx);
+    }
 
-    // Compute fees.
+    deal.state = DealStatus::PendingSettlement as u8;
+    deal.time_completed = clock.unix_timestamp;
+    deal.settle_after = clock
+        .unix_timestamp
+        .checked_add(settlement_delay)
+        .ok_or(error!(CustomError::MathOverflow))?;
 
-    let gig_key = gig.key();
-    let client_key = client.key();
-    let deal_bump = deal.bump.to_le_bytes();
+    if let Some(deal_index) = ctx.accounts.deal_index.as_mut() {
+        deal_index.record(deal.key(), deal.state);
+    }
 
-    let inner = vec![
-        b"deal".as_ref(),
-        client_key.as_ref(),
-        gig_key.as_ref(),
-        deal_bump.as_ref(),
-    ];
+    emit!(SettlementPending {
+        deal: deal.key(),
+        settle_after: deal.settle_after,
+        timestamp: clock.unix_timestamp,
+    });
 
-    let deal_sig = vec![inner.as_slice()];
+    Ok(())
+}
 
-    let freelancer_fee_percentage: u64 = config.freelancer_fee_percentage.try_into().unwrap();    
+/// Pays out the escrow of the deal and marks it completed, the caller checks who may do so.
+pub fn settle_deal(ctx: Context<CompleteDeal>) -> Result<()> {
+    let deal = &ctx.accounts.deal;
+    let gig = &ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let clock = clock::Clock::get()?;
+
+    let escrow = Escrow::new(
+        deal,
+        deal.mint.is_none(),
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+
+    // Compute fees. Both were locked in when the offer was made, so fee changes in the meantime
+    // don't affect the split.
+
+    let escrow_amount = escrow.checked_amount()?;
+    let client_fee = deal.client_fee;
+
+    // A deal held for settlement was completed when the client approved it, not when it's paid.
+    let completed_at = match deal.status()? {
+        DealStatus::PendingSettlement => deal.time_completed,
+        _ => clock.unix_timestamp,
+    };
 
-    let freelancer_fee = freelancer_fee_percentage
-        .checked_mul(deal.offer)
-        .unwrap()
-        .checked_div(100_00)
-        .unwrap();
+    // Completing after the deadline refunds the gig's late penalty to the client,
+    // taken out of the freelancer's pay.
+    let penalty = if completed_at > deal.deadline {
+        percentage_of(deal.offer, gig.late_penalty_bps)?.min(deal.freelancer_net)
+    } else {
+        0
+    };
 
-    let referral_pay = match referrer_token_account {
-        Some(_) => {
-            let referral_fee_percentage: u64 = config.referral_fee_percentage.try_into().unwrap();    
+    let freelancer_pay = deal
+        .freelancer_net
+        .checked_sub(penalty)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // The referrer gets a cut of the client fee. If its account is missing or doesn't match
+    // the deal's referrer, the whole fee goes to the treasury instead.
+    let payout_mint = config.referrer_payout_mint;
+    let referrer_wallet = ctx
+        .accounts
+        .referrer
+        .as_ref()
+        .filter(|referrer| deal.referrer == Some(referrer.key()));
+
+    let referrer_token_account =
+        ctx.accounts
+            .referrer_token_account
+            .as_deref()
+            .filter(|token_account| {
+                deal.referrer == Some(token_account.owner)
+                    && payout_mint.or(deal.mint) == Some(token_account.mint)
+            });
+
+    let referrer = match (
+        payout_mint.or(deal.mint),
+        referrer_wallet,
+        referrer_token_account,
+    ) {
+        (None, Some(referrer_wallet), _) => Some((None, referrer_wallet.to_account_info())),
+        (Some(_), _, Some(token_account)) => {
+            Some((Some(token_account), token_account.to_account_info()))
+        }
+        _ => None,
+    };
 
-            referral_fee_percentage
-                .checked_mul(deal.offer)
-                .unwrap()
-                .checked_div(100_00)
-                .unwrap()
-        },
+    let referral_pay = match referrer {
+        Some(_) => percentage_of(client_fee, config.referral_fee_percentage)?,
         None => 0,
     };
 
-    let freelancer_pay = deal.offer - freelancer_fee - referral_pay;
-    let treasury_pay = escrow.amount - freelancer_pay - referral_pay;
+    // Paid out of the rewards vault in the payout mint, the escrow keeps it for the treasury.
+    let escrow_referral_pay = match payout_mint {
+        Some(_) => 0,
+        None => referral_pay,
+    };
 
-    // Transfer to freelancer.
+    // Remainder of the escrow amount, this includes the client fee less the referral pay.
+    let treasury_pay = escrow_amount
+        .checked_sub(freelancer_pay)
+        .and_then(|amount| amount.checked_sub(escrow_referral_pay))
+        .and_then(|amount| amount.checked_sub(penalty))
+        .and_then(|amount| amount.checked_sub(deal.stake_amount))
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // The freelancer's stake is returned along with the pay.
+    let freelancer_amount = freelancer_pay
+        .checked_add(deal.stake_amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if config.defer_freelancer_payouts {
+        let vault = ctx
+            .accounts
+            .freelancer_vault
+            .as_ref()
+            .ok_or(error!(CustomError::MissingFreelancerVault))?;
+
+        escrow.release(
+            ctx.accounts.freelancer_vault_token_account.as_deref(),
+            &vault.to_account_info(),
+            freelancer_amount,
+        )?;
+    } else {
+        escrow.release(
+            ctx.accounts.freelancer_token_account.as_deref(),
+            &ctx.accounts.freelancer,
+            freelancer_amount,
+        )?;
+    }
 
-    let transfer_ix = Transfer {
-        from: escrow.to_account_info(),
-        to: freelancer_token_account.to_account_info(),
-        authority: deal.to_account_info(),
-    };
+    if penalty > 0 {
+        escrow.release(
+            ctx.accounts.client_token_account.as_deref(),
+            &ctx.accounts.client,
+            penalty,
+        )?;
+    }
 
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        transfer_ix,
-        deal_sig.as_slice(),
-    );
-
-    anchor_spl::token::transfer(cpi_ctx, freelancer_pay)?;
-
-    // Transfer to referrer.
-
-    match referrer_token_account {
-        Some(referrer_token_account) => {
-            let transfer_ix = Transfer {
-                from: escrow.to_account_info(),
-                to: referrer_token_account.to_account_info(),
-                authority: deal.to_account_info(),
-            };
-        
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                transfer_ix,
-                deal_sig.as_slice(),
-            );
-        
-            anchor_spl::token::transfer(cpi_ctx, referral_pay)?;
-        },
-        None => (),
+    if let Some((referrer_token_account, referrer_info)) = &referrer {
+        let amount = match referrer_token_account.filter(|_| payout_mint.is_some()) {
+            Some(token_account) => pay_from_rewards_vault(
+                ctx.accounts,
+                token_account,
+                referral_pay,
+                clock.unix_timestamp,
+            )?,
+            None => {
+                escrow.release_fee(*referrer_token_account, referrer_info, referral_pay)?;
+                referral_pay
+            }
+        };
+
+        emit!(ReferrerPaid {
+            deal: deal.key(),
+            referrer: deal.referrer.unwrap_or_default(),
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
     }
 
-    // Transfer to treasury.
-    // Remainder of the escrow amount, this should include the client's fee as well.
+    escrow.release_fee(
+        ctx.accounts.treasury_token_account.as_deref(),
+        &ctx.accounts.treasury,
+        treasury_pay,
+    )?;
 
-    let transfer_ix = Transfer {
-        from: escrow.to_account_info(),
-        to: treasury_token_account.to_account_info(),
-        authority: deal.to_account_info(),
+    let treasury_pay = treasury_pay
+        .checked_add(escrow.sweep(
+            ctx.accounts.treasury_token_account.as_deref(),
+            &ctx.accounts.treasury,
+        )?)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // Close escrow account, give back the rent to client.
+    escrow.close(&ctx.accounts.client)?;
+
+    if config.defer_freelancer_payouts {
+        if let Some(vault) = ctx.accounts.freelancer_vault.as_mut() {
+            vault.record_deposit(
+                *ctx.bumps.get("freelancer_vault").unwrap(),
+                deal.freelancer,
+                freelancer_amount,
+            )?;
+        }
+    }
+
+    let gig = &mut ctx.accounts.gig;
+    gig.pending_deals = gig.pending_deals.saturating_sub(1);
+    gig.active_deals = gig.active_deals.saturating_sub(1);
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_closed(ctx.accounts.deal.total_escrow);
+    stats.record_fees(treasury_pay)?;
+
+    let deal = &mut ctx.accounts.deal;
+    deal.state = DealStatus::Completed as u8;
+    deal.time_completed = completed_at;
+    deal.penalty_applied = penalty;
+
+    if let Some(deal_index) = ctx.accounts.deal_index.as_mut() {
+        deal_index.record(deal.key(), deal.state);
+    }
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.completed_deals = reputation
+        .completed_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    reputation.total_earned = reputation
+        .total_earned
+        .checked_add(freelancer_pay)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    emit!(DealCompleted {
+        deal: deal.key(),
+        freelancer_amount: freelancer_pay,
+        treasury_amount: treasury_pay,
+        penalty_amount: penalty,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Pays the referral fee, converted into `config.referrer_payout_mint`, out of the rewards vault.
+/// Returns the amount paid in the payout mint.
+fn pay_from_rewards_vault<'info>(
+    accounts: &CompleteDeal<'info>,
+    destination: &Account<'info, TokenAccount>,
+    referral_pay: u64,
+    now: i64,
+) -> Result<u64> {
+    let (vault, payout_mint, token_program) = match (
+        &accounts.referrer_rewards_vault,
+        &accounts.referrer_payout_mint,
+        &accounts.token_program,
+    ) {
+        (Some(vault), Some(payout_mint), Some(token_program)) => {
+            (vault, payout_mint, token_program)
+        }
+        _ => return Err(error!(CustomError::MissingEscrowAccounts)),
     };
 
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        transfer_ix,
-        deal_sig.as_slice(),
-    );
+    let amount = referral_payout_amount(
+        &accounts.config,
+        accounts.deal.mint,
+        accounts.mint.as_ref().map(|mint| mint.decimals),
+        accounts.price_feed.as_deref(),
+        payout_mint.decimals,
+        referral_pay,
+        now,
+    )?;
+
+    if amount > vault.amount {
+        return Err(error!(CustomError::InsufficientRewardsVault));
+    }
 
-    anchor_spl::token::transfer(cpi_ctx, treasury_pay)?;
-    
-    // Close escrow account, give back the rent to client.
+    let bump = [accounts.config.bump];
+    let config_seeds = [b"config".as_ref(), bump.as_ref()];
+    let config_sig = [config_seeds.as_slice()];
 
-    let cpi_close = CloseAccount {
-        account: escrow.to_account_info(),
-        destination: client.to_account_info(),
-        authority: deal.to_account_info(),
+    let transfer_ix = Transfer {
+        from: vault.to_account_info(),
+        to: destination.to_account_info(),
+        authority: accounts.config.to_account_info(),
     };
 
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(), 
-        cpi_close, 
-        deal_sig.as_slice()
-    );
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), transfer_ix, &config_sig);
 
-    anchor_spl::token::close_account(cpi_ctx)?;
+    anchor_spl::token::transfer(cpi_ctx, amount)?;
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(amount)
+}
@@ -0,0 +1,181 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+
+use crate::{
+    events::BountyContributed,
+    states::{
+        Bounty, Config, Contribution, DealStatus, Exemption, Reputation, Stats,
+        MAX_BOUNTY_CONTRIBUTORS,
+    },
+    utils::{client_fee_percentage, compute_client_fee},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct ContributeBountyParams {
+    /// Added to the bounty offer, the client fee is charged on top of it.
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: ContributeBountyParams)]
+pub struct ContributeBounty<'info> {
+    #[account(
+        mut,
+        constraint = bounty.state == DealStatus::Created as u8 @ CustomError::InvalidState,
+    )]
+    pub bounty: Box<Account<'info, Bounty>>,
+
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists if the contributor is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", contributor.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub contributor_blacklist: UncheckedAccount<'info>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", bounty.freelancer.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", contributor.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Adds to the contribution of a client who already funded the bounty, or adds a new one.
+pub fn contribute_bounty_handler(
+    ctx: Context<ContributeBounty>,
+    params: ContributeBountyParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &ctx.accounts.config;
+    let contributor = &ctx.accounts.contributor;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if !ctx.accounts.contributor_blacklist.data_is_empty() {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    if contributor.key() == ctx.accounts.bounty.freelancer {
+        return Err(error!(CustomError::Unauthorized));
+    }
+
+    if ctx.accounts.bounty.deadline <= clock.unix_timestamp {
+        return Err(error!(CustomError::DeadlineInPast));
+    }
+
+    if params.amount == 0 {
+        return Err(error!(CustomError::InvalidOffer));
+    }
+
+    let offer = ctx
+        .accounts
+        .bounty
+        .offer
+        .checked_add(params.amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if config.max_deal_offer > 0 && offer > config.max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    // Bounties are escrowed right away, they can't wait for `approve_deal`.
+    if config.needs_approval(offer) {
+        return Err(error!(CustomError::OfferNeedsApproval));
+    }
+
+    let client_fee = compute_client_fee(
+        config,
+        None,
+        None,
+        None,
+        client_fee_percentage(
+            config,
+            ctx.accounts.freelancer_reputation.as_deref(),
+            ctx.accounts.client_exemption.as_deref(),
+        ),
+        params.amount,
+        clock.unix_timestamp,
+    )?;
+
+    let escrow_amount = params
+        .amount
+        .checked_add(client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_volume(params.amount)?;
+    stats.record_escrowed(escrow_amount, config.max_tvl)?;
+
+    let transfer_ix = system_program::Transfer {
+        from: contributor.to_account_info(),
+        to: ctx.accounts.bounty.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+    system_program::transfer(cpi_ctx, escrow_amount)?;
+
+    let bounty = &mut ctx.accounts.bounty;
+    bounty.offer = offer;
+
+    match bounty
+        .contributors
+        .iter_mut()
+        .find(|contribution| contribution.contributor == contributor.key())
+    {
+        Some(contribution) => {
+            contribution.amount += params.amount;
+            contribution.client_fee += client_fee;
+        }
+        None => {
+            if bounty.contributors.len() >= MAX_BOUNTY_CONTRIBUTORS {
+                return Err(error!(CustomError::TooManyContributors));
+            }
+
+            bounty.contributors.push(Contribution {
+                contributor: contributor.key(),
+                amount: params.amount,
+                client_fee,
+            });
+        }
+    }
+
+    emit!(BountyContributed {
+        bounty: bounty.key(),
+        contributor: contributor.key(),
+        amount: params.amount,
+        client_fee,
+        offer,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
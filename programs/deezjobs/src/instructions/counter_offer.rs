@@ -0,0 +1,71 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::CounterOffered,
+    states::{Config, Deal, DealStatus, Gig},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct CounterOfferParams {
+    pub new_offer: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CounterOfferParams)]
+pub struct CounterOffer<'info> {
+    /// Milestones are split from the original offer, so those deals can't be countered.
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Created as u8,
+        constraint = deal.milestones.is_empty() @ CustomError::DealHasMilestones,
+    )]
+    pub deal: Account<'info, Deal>,
+
+    #[account(
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Account<'info, Gig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+}
+
+pub fn counter_offer_handler(ctx: Context<CounterOffer>, params: CounterOfferParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+
+    if params.new_offer <= deal.offer {
+        return Err(error!(CustomError::InvalidCounterOffer));
+    }
+
+    if matches!(ctx.accounts.gig.max_asking, Some(max_asking) if params.new_offer > max_asking) {
+        return Err(error!(CustomError::OfferTooHigh));
+    }
+
+    let max_deal_offer = ctx.accounts.config.max_deal_offer;
+
+    if max_deal_offer > 0 && params.new_offer > max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    deal.counter_amount = params.new_offer;
+    deal.state = DealStatus::CounterOffered as u8;
+
+    emit!(CounterOffered {
+        deal: deal.key(),
+        offer: deal.offer,
+        counter_amount: params.new_offer,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
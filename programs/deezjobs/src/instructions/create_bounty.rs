@@ -0,0 +1,197 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+
+use crate::{
+    events::BountyCreated,
+    states::{
+        Bounty, Config, Contribution, DealStatus, Exemption, Gig, GigStatus, Reputation, Stats,
+    },
+    utils::{client_fee_percentage, compute_client_fee},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct CreateBountyParams {
+    /// The creator's contribution, the client fee is charged on top of it.
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateBountyParams)]
+pub struct CreateBounty<'info> {
+    #[account(
+        init,
+        payer = creator,
+        seeds = [
+            b"bounty",
+            creator.key().as_ref(),
+            gig.key().as_ref(),
+        ],
+        bump,
+        space = Bounty::len()
+    )]
+    pub bounty: Box<Account<'info, Bounty>>,
+
+    #[account(
+        constraint = gig.state == GigStatus::Open as u8,
+        constraint = gig.mint.is_none() @ CustomError::BountyNotNative,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists if the creator is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", creator.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub client_blacklist: UncheckedAccount<'info>,
+
+    /// Only exists if the freelancer is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", gig.owner.as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub freelancer_blacklist: UncheckedAccount<'info>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", gig.owner.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", creator.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens the bounty with the creator's contribution. The lamports are held by the bounty
+/// account, SPL gigs are rejected with `BountyNotNative` until bounties get an escrow token
+/// account like deals.
+pub fn create_bounty_handler(ctx: Context<CreateBounty>, params: CreateBountyParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let creator = &ctx.accounts.creator;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if !ctx.accounts.client_blacklist.data_is_empty()
+        || !ctx.accounts.freelancer_blacklist.data_is_empty()
+    {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    if creator.key() == gig.owner {
+        return Err(error!(CustomError::Unauthorized));
+    }
+
+    if params.amount == 0 {
+        return Err(error!(CustomError::InvalidOffer));
+    }
+
+    if config.max_deal_offer > 0 && params.amount > config.max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    // Bounties are escrowed right away, they can't wait for `approve_deal`.
+    if config.needs_approval(params.amount) {
+        return Err(error!(CustomError::OfferNeedsApproval));
+    }
+
+    let min_deadline = clock
+        .unix_timestamp
+        .checked_add(gig.min_completion_time)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if min_deadline > params.deadline {
+        return Err(error!(CustomError::DeadlineTooShort));
+    }
+
+    config.check_deadline_horizon(clock.unix_timestamp, params.deadline)?;
+
+    let client_fee = compute_client_fee(
+        config,
+        None,
+        None,
+        None,
+        client_fee_percentage(
+            config,
+            ctx.accounts.freelancer_reputation.as_deref(),
+            ctx.accounts.client_exemption.as_deref(),
+        ),
+        params.amount,
+        clock.unix_timestamp,
+    )?;
+
+    let escrow_amount = params
+        .amount
+        .checked_add(client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_created(params.amount)?;
+    stats.record_escrowed(escrow_amount, config.max_tvl)?;
+
+    let transfer_ix = system_program::Transfer {
+        from: creator.to_account_info(),
+        to: ctx.accounts.bounty.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+    system_program::transfer(cpi_ctx, escrow_amount)?;
+
+    let bounty = &mut ctx.accounts.bounty;
+    bounty.bump = *ctx.bumps.get("bounty").unwrap();
+    bounty.creator = creator.key();
+    bounty.state = DealStatus::Created as u8;
+    bounty.freelancer = gig.owner;
+    bounty.gig = gig.key();
+    bounty.offer = params.amount;
+    bounty.deadline = params.deadline;
+    bounty.time_created = clock.unix_timestamp;
+    bounty.delivery_hash = [0; 32];
+    bounty.time_delivered = 0;
+    bounty.contributors = vec![Contribution {
+        contributor: creator.key(),
+        amount: params.amount,
+        client_fee,
+    }];
+
+    emit!(BountyCreated {
+        bounty: bounty.key(),
+        gig: bounty.gig,
+        creator: bounty.creator,
+        amount: params.amount,
+        client_fee,
+        deadline: bounty.deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,19 +1,49 @@
-use anchor_lang::{prelude::*, solana_program::clock};
+use std::collections::BTreeMap;
+
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount, Transfer},
+    token::{Mint, Token, TokenAccount},
 };
 
 use crate::{
-    states::{Config, Deal, Gig},
+    events::{DealCreated, DealPendingApproval},
+    states::{
+        Config, Deal, DealIndex, DealStatus, Exemption, Gig, GigStatus, Milestone, MilestoneDue,
+        ReferrerActivity, Reputation, Stats, DEAL_VERSION, MAX_MILESTONES,
+    },
+    utils::{
+        check_mint_supported, check_referrer, client_fee_percentage, compute_client_fee,
+        deposit_tokens, freelancer_net,
+    },
     CustomError,
 };
 
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct MilestoneParams {
+    pub amount: u64,
+    pub description_hash: [u8; 32],
+    /// Zero for no due date.
+    pub due_ts: i64,
+    /// Percentage of the milestone refunded to the client if it's released late, 2 decimal places.
+    pub late_penalty_bps: u16,
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize)]
 pub struct CreateDealParams {
     pub referrer: Option<Pubkey>,
     pub offer: u64,
     pub deadline: i64,
+    /// Leave empty to release the whole offer at once on `complete_deal`.
+    pub milestones: Vec<MilestoneParams>,
+    /// Fails the creation if the client fee comes out higher, eg. after a config update.
+    pub max_client_fee: Option<u64>,
+    /// Hash of the off-chain contract, the freelancer has to accept the same one.
+    pub terms_hash: [u8; 32],
+    /// Seconds the freelancer has from acceptance, `deadline` is ignored if set.
+    pub deadline_from_accept: Option<i64>,
+    /// Unused by the client's other deals for the gig, zero for their first one.
+    pub nonce: u64,
 }
 
 #[derive(Accounts)]
@@ -26,38 +56,61 @@ pub struct CreateDeal<'info> {
             b"deal",
             owner.key().as_ref(),
             gig.key().as_ref(),
+            Deal::nonce_seed(&params.nonce.to_le_bytes()),
         ],
         bump,
         space = Deal::len()
     )]
     pub deal: Box<Account<'info, Deal>>,
 
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
     #[account(
         init,
         payer = owner,
         associated_token::mint = mint,
         associated_token::authority = deal,
     )]
-    pub escrow: Box<Account<'info, TokenAccount>>,
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Only for SPL gigs while `config.split_escrow` is set, holds the client fee apart from the
+    /// offer, see `Deal.fee_escrow`.
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"fee_escrow", deal.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = deal,
+    )]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
 
+    /// Any token account of the client, checked explicitly so a wrong one fails with a named
+    /// error.
     #[account(
         mut,
+        constraint = owner_wallet.owner == owner.key() @ CustomError::WalletOwnerMismatch,
+        constraint = gig.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Optional for SPL gigs, a freelancer who can't receive the mint fails here instead of
+    /// at settlement.
+    #[account(
         associated_token::mint = mint,
-        associated_token::authority = owner,
+        associated_token::authority = gig.owner,
     )]
-    pub owner_wallet: Box<Account<'info, TokenAccount>>,
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
-        // TODO: will fail for native token
-        // Solution: possibly put every account involved to optional
-        constraint = mint.key() == gig.mint.unwrap().key(),
+        constraint = gig.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = gig.mint == Some(mint.key()) @ CustomError::MintMismatch,
+        constraint = config.allowed_mints.contains(&mint.key()) @ CustomError::MintNotAllowed,
     )]
-    pub mint: Box<Account<'info, Mint>>,
+    pub mint: Option<Box<Account<'info, Mint>>>,
 
     #[account(
         mut,
-        constraint = gig.state == 1,
-        constraint = gig.asking <= params.offer @ CustomError::InsufficientOffer,
+        constraint = gig.state == GigStatus::Open as u8 @ CustomError::GigNotOpen,
     )]
     pub gig: Box<Account<'info, Gig>>,
 
@@ -70,70 +123,475 @@ pub struct CreateDeal<'info> {
     )]
     pub config: Box<Account<'info, Config>>,
 
+    /// Only exists if the client is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", owner.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub client_blacklist: UncheckedAccount<'info>,
+
+    /// Only exists if the freelancer is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", gig.owner.as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub freelancer_blacklist: UncheckedAccount<'info>,
+
+    /// CHECK: compared against the price feed registered in the config for the gig's mint
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", gig.owner.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Needed if the gig requires completed deals of the client, counted as zero without it.
+    #[account(
+        seeds = [b"reputation", owner.key().as_ref()],
+        bump = client_reputation.bump,
+    )]
+    pub client_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", owner.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    /// Required with a referrer while `config.referrer_cooldown` is set.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [
+            b"referrer_activity",
+            params.referrer.unwrap_or_default().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        space = ReferrerActivity::len(),
+    )]
+    pub referrer_activity: Option<Box<Account<'info, ReferrerActivity>>>,
+
+    /// Lists the deal among the freelancer's recent deals, see `DealIndex`.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"deal_index", gig.owner.as_ref()],
+        bump,
+        space = DealIndex::len(),
+    )]
+    pub deal_index: Option<Box<Account<'info, DealIndex>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn create_deal_handler(ctx: Context<CreateDeal>, params: CreateDealParams) -> Result<()> {
-    let deal = &mut ctx.accounts.deal;
-    let escrow = &mut ctx.accounts.escrow;
-    let owner_wallet = &mut ctx.accounts.owner_wallet;
-    let gig = &ctx.accounts.gig;
-    let config = &ctx.accounts.config;
-    let client = &ctx.accounts.owner;
+/// Returns the address of the new deal, so CPI callers don't have to derive it.
+pub fn create_deal_handler(ctx: Context<CreateDeal>, params: CreateDealParams) -> Result<Pubkey> {
+    let accounts = ctx.accounts;
+
+    open_deal(
+        NewDeal {
+            deal: &mut accounts.deal,
+            gig: &mut accounts.gig,
+            client: &accounts.owner,
+            config: &accounts.config,
+            escrow: accounts.escrow.as_deref(),
+            fee_escrow: accounts.fee_escrow.as_deref(),
+            owner_wallet: accounts.owner_wallet.as_deref(),
+            mint: accounts.mint.as_deref(),
+            client_blacklist: &accounts.client_blacklist,
+            freelancer_blacklist: &accounts.freelancer_blacklist,
+            price_feed: accounts.price_feed.as_deref(),
+            freelancer_reputation: accounts.freelancer_reputation.as_deref(),
+            client_reputation: accounts.client_reputation.as_deref(),
+            client_exemption: accounts.client_exemption.as_deref(),
+            referrer_activity: accounts.referrer_activity.as_deref_mut(),
+            deal_index: accounts.deal_index.as_deref_mut(),
+            stats: &accounts.stats,
+            system_program: &accounts.system_program,
+            token_program: accounts.token_program.as_ref(),
+        },
+        &ctx.bumps,
+        &params,
+    )
+}
+
+/// Accounts `open_deal` needs, borrowed from the instruction creating the deal.
+pub struct NewDeal<'a, 'info> {
+    pub deal: &'a mut Account<'info, Deal>,
+    pub gig: &'a mut Account<'info, Gig>,
+    pub client: &'a Signer<'info>,
+    pub config: &'a Account<'info, Config>,
+    pub escrow: Option<&'a Account<'info, TokenAccount>>,
+    pub fee_escrow: Option<&'a Account<'info, TokenAccount>>,
+    pub owner_wallet: Option<&'a Account<'info, TokenAccount>>,
+    pub mint: Option<&'a Account<'info, Mint>>,
+    pub client_blacklist: &'a AccountInfo<'info>,
+    pub freelancer_blacklist: &'a AccountInfo<'info>,
+    pub price_feed: Option<&'a AccountInfo<'info>>,
+    pub freelancer_reputation: Option<&'a Account<'info, Reputation>>,
+    pub client_reputation: Option<&'a Account<'info, Reputation>>,
+    pub client_exemption: Option<&'a Account<'info, Exemption>>,
+    pub referrer_activity: Option<&'a mut Account<'info, ReferrerActivity>>,
+    pub deal_index: Option<&'a mut Account<'info, DealIndex>>,
+    pub stats: &'a AccountLoader<'info, Stats>,
+    pub system_program: &'a Program<'info, System>,
+    pub token_program: Option<&'a Program<'info, Token>>,
+}
+
+/// Validates the offer against the gig and config, escrows it with the client fee and fills in
+/// the new deal. Shared by `create_deal` and `create_gig_and_deal` so both enforce the same rules.
+pub fn open_deal(
+    mut accounts: NewDeal,
+    bumps: &BTreeMap<String, u8>,
+    params: &CreateDealParams,
+) -> Result<Pubkey> {
+    let deal = accounts.deal;
+    let gig = accounts.gig;
+    let config = accounts.config;
+    let client = accounts.client;
     let clock = clock::Clock::get()?;
 
-    let client_fee_percentage: u64 = config.client_fee_percentage.try_into().unwrap();
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if !accounts.client_blacklist.data_is_empty() || !accounts.freelancer_blacklist.data_is_empty()
+    {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    // Paying themselves would inflate the freelancer's reputation and the platform volume.
+    if client.key() == gig.owner {
+        return Err(error!(CustomError::SelfDeal));
+    }
+
+    if gig.max_concurrent_deals > 0 && gig.active_deals >= gig.max_concurrent_deals {
+        return Err(error!(CustomError::GigAtCapacity));
+    }
+
+    if gig.min_client_completed_deals > 0 {
+        let completed_deals = accounts
+            .client_reputation
+            .map_or(0, |reputation| reputation.completed_deals);
+
+        if completed_deals < gig.min_client_completed_deals as u64 {
+            return Err(error!(CustomError::ClientReputationTooLow));
+        }
+    }
+
+    if params.offer == 0 {
+        return Err(error!(CustomError::InvalidOffer));
+    }
+
+    if params.offer < gig.asking {
+        return Err(error!(CustomError::InsufficientOffer));
+    }
+
+    if matches!(gig.max_asking, Some(max_asking) if params.offer > max_asking) {
+        return Err(error!(CustomError::OfferTooHigh));
+    }
+
+    if config.max_deal_offer > 0 && params.offer > config.max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    // A deadline counted from acceptance is bounded by its duration, the deal can't be accepted
+    // before it's created.
+    let horizon = config.max_deadline_horizon;
+
+    match params.deadline_from_accept {
+        Some(duration) => {
+            if duration < gig.min_completion_time {
+                return Err(error!(CustomError::DeadlineTooShort));
+            }
+
+            if horizon > 0 && duration > horizon {
+                return Err(error!(CustomError::DeadlineTooFar));
+            }
+        }
+        None => {
+            if params.deadline <= clock.unix_timestamp {
+                return Err(error!(CustomError::DeadlineInPast));
+            }
+
+            let min_deadline = clock
+                .unix_timestamp
+                .checked_add(gig.min_completion_time)
+                .ok_or(error!(CustomError::MathOverflow))?;
+
+            if min_deadline > params.deadline {
+                return Err(error!(CustomError::DeadlineTooShort));
+            }
+
+            config.check_deadline_horizon(clock.unix_timestamp, params.deadline)?;
+        }
+    }
+
+    if params.milestones.len() > MAX_MILESTONES {
+        return Err(error!(CustomError::TooManyMilestones));
+    }
+
+    let milestones_sum = params
+        .milestones
+        .iter()
+        .try_fold(0u64, |sum, milestone| sum.checked_add(milestone.amount));
+
+    if !params.milestones.is_empty() && milestones_sum != Some(params.offer) {
+        return Err(error!(CustomError::MilestoneSumMismatch));
+    }
+
+    for milestone in &params.milestones {
+        if milestone.late_penalty_bps > 100_00 {
+            return Err(error!(CustomError::InvalidLatePenalty));
+        }
+
+        if milestone.due_ts != 0 && milestone.due_ts <= clock.unix_timestamp {
+            return Err(error!(CustomError::DeadlineInPast));
+        }
+    }
+
+    // Only reported in the event, `client_fee_percentage` picks the same tier.
+    let fee_tier = accounts
+        .freelancer_reputation
+        .and_then(|reputation| config.fee_tier(reputation.completed_deals));
+
+    let client_fee_percentage = client_fee_percentage(
+        config,
+        accounts.freelancer_reputation,
+        accounts.client_exemption,
+    );
 
-    let client_fee = client_fee_percentage
-        .checked_mul(params.offer)
-        .unwrap()
-        .checked_div(100_00)
-        .unwrap();
+    let client_fee = compute_client_fee(
+        config,
+        gig.mint,
+        accounts.mint.map(|mint| mint.decimals),
+        accounts.price_feed,
+        client_fee_percentage,
+        params.offer,
+        clock.unix_timestamp,
+    )?;
 
-    // TODO: client_fee_min is assumed to be USDC at the moment, possible source of bug
-    let client_fee = if client_fee < config.client_fee_min {
-        config.client_fee_min
+    if matches!(params.max_client_fee, Some(max_client_fee) if client_fee > max_client_fee) {
+        return Err(error!(CustomError::FeeExceedsMax));
+    }
+
+    // Each milestone is charged separately on release, so the net is summed the same way.
+    let freelancer_net = if params.milestones.is_empty() {
+        freelancer_net(config, params.offer)?
     } else {
-        client_fee
+        params.milestones.iter().try_fold(0u64, |sum, milestone| {
+            sum.checked_add(freelancer_net(config, milestone.amount)?)
+                .ok_or(error!(CustomError::MathOverflow))
+        })?
     };
 
-    let total_escrow_amount = params.offer + client_fee;
+    let total_escrow_amount = params
+        .offer
+        .checked_add(client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
 
-    let transfer_ix = Transfer {
-        from: owner_wallet.to_account_info(),
-        to: escrow.to_account_info(),
-        authority: client.to_account_info(),
-    };
+    // Native SOL deals keep the fee in the deal account along with the offer.
+    let split_escrow = config.split_escrow && gig.mint.is_some();
+
+    if split_escrow != accounts.fee_escrow.is_some() {
+        return Err(error!(CustomError::FeeEscrowMismatch));
+    }
+
+    // Large offers are escrowed by `fund_deal` once the authority approved them.
+    let needs_approval = config.needs_approval(params.offer);
+
+    if !needs_approval {
+        match gig.mint {
+            Some(mint) => {
+                let (escrow, owner_wallet, token_program) = match (
+                    accounts.escrow,
+                    accounts.owner_wallet,
+                    accounts.token_program,
+                ) {
+                    (Some(escrow), Some(owner_wallet), Some(token_program)) => {
+                        (escrow, owner_wallet, token_program)
+                    }
+                    _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+                };
+
+                // Also enforced by the account constraints, checked again so the escrow can never
+                // take another token should those be loosened.
+                if owner_wallet.mint != mint || escrow.mint != mint {
+                    return Err(error!(CustomError::MintMismatch));
+                }
 
-    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_ix);
+                if let Some(mint) = accounts.mint {
+                    check_mint_supported(&mint.to_account_info())?;
+                }
+
+                // The escrow is `init` rather than `init_if_needed`, so one someone else created
+                // and funded at this address is never adopted: its extra balance wouldn't match
+                // `deal.expected_escrow()`. Checked again in case the account wasn't new.
+                if escrow.amount != 0
+                    || matches!(accounts.fee_escrow, Some(fee_escrow) if fee_escrow.amount != 0)
+                {
+                    return Err(error!(CustomError::EscrowNotEmpty));
+                }
+
+                if owner_wallet.amount < total_escrow_amount {
+                    return Err(error!(CustomError::InsufficientFunds));
+                }
+
+                deposit_tokens(
+                    owner_wallet,
+                    &client.to_account_info(),
+                    escrow,
+                    accounts.fee_escrow,
+                    token_program,
+                    total_escrow_amount,
+                    client_fee,
+                )?;
+            }
+            None => {
+                // Native SOL is escrowed by the deal account on top of its rent-exempt balance,
+                // the rent is paid separately by the `init` above so the full amount stays
+                // claimable.
+                if client.lamports() < total_escrow_amount {
+                    return Err(error!(CustomError::InsufficientFunds));
+                }
 
-    anchor_spl::token::transfer(cpi_ctx, u64::from(total_escrow_amount))?;
+                let transfer_ix = system_program::Transfer {
+                    from: client.to_account_info(),
+                    to: deal.to_account_info(),
+                };
 
-    deal.bump = *ctx.bumps.get("deal").unwrap();
+                let cpi_ctx =
+                    CpiContext::new(accounts.system_program.to_account_info(), transfer_ix);
+
+                system_program::transfer(cpi_ctx, total_escrow_amount)?;
+            }
+        }
+    }
+
+    deal.bump = *bumps.get("deal").unwrap();
+    deal.version = DEAL_VERSION;
     deal.offer = params.offer;
-    deal.state = 1;
+    deal.freelancer_net = freelancer_net;
+    deal.client_fee = client_fee;
+    if needs_approval {
+        deal.state = DealStatus::PendingApproval as u8;
+    } else {
+        deal.total_escrow = total_escrow_amount;
+        deal.state = DealStatus::Created as u8;
+    }
     deal.gig = gig.key();
+    deal.mint = gig.mint;
+    deal.fee_escrow = accounts.fee_escrow.map(|fee_escrow| fee_escrow.key());
     deal.freelancer = gig.owner.key();
     deal.client = client.key();
     deal.time_created = clock.unix_timestamp;
-    deal.deadline = params.deadline;
+    // Set on accept if it's counted from acceptance.
+    deal.deadline = match params.deadline_from_accept {
+        Some(_) => 0,
+        None => params.deadline,
+    };
+    deal.deadline_duration = params.deadline_from_accept.unwrap_or(0);
+    deal.terms_hash = params.terms_hash;
+    deal.nonce = params.nonce;
+    deal.milestones = params
+        .milestones
+        .iter()
+        .map(|milestone| Milestone {
+            amount: milestone.amount,
+            released: false,
+            description_hash: milestone.description_hash,
+        })
+        .collect();
+    deal.milestone_dues = params
+        .milestones
+        .iter()
+        .map(|milestone| MilestoneDue {
+            due_ts: milestone.due_ts,
+            late_penalty_bps: milestone.late_penalty_bps,
+            penalty_applied: false,
+        })
+        .collect();
 
-    // TODO: referrer could be the client itself, exploiting the pay
-    match params.referrer {
-        Some(referrer) => {
-            if client.key() != referrer {
-                deal.referrer = Some(referrer);
+    gig.active_deals = gig
+        .active_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    let mut stats = accounts.stats.load_mut()?;
+    stats.record_created(params.offer)?;
+    stats.record_escrowed(deal.total_escrow, config.max_tvl)?;
+
+    if let Some(referrer) = params.referrer {
+        check_referrer(&referrer, &client.key(), &gig.owner, &gig.allowed_referrers)?;
+
+        deal.referrer = Some(referrer);
+
+        // A referrer credited for the same client within the cooldown is dropped instead of
+        // failing the deal, so referral rings can't farm fees on repeated deals.
+        if config.referrer_cooldown > 0 {
+            let referrer_activity = accounts
+                .referrer_activity
+                .as_mut()
+                .ok_or(error!(CustomError::MissingReferrerActivity))?;
+
+            if referrer_activity.in_cooldown(config.referrer_cooldown, clock.unix_timestamp) {
+                deal.referrer = None;
+            } else {
+                referrer_activity.bump = *bumps.get("referrer_activity").unwrap();
+                referrer_activity.referrer = referrer;
+                referrer_activity.client = client.key();
+                referrer_activity.last_referral_ts = clock.unix_timestamp;
             }
         }
-        None => (),
     }
 
-    if deal.time_created + gig.min_completion_time > params.deadline {
-        return Err(error!(CustomError::DeadlineTooShort));
+    if let Some(deal_index) = accounts.deal_index.as_mut() {
+        deal_index.bump = *bumps.get("deal_index").unwrap();
+        deal_index.freelancer = deal.freelancer;
+        deal_index.record(deal.key(), deal.state);
+    }
+
+    emit!(DealCreated {
+        deal: deal.key(),
+        bump: deal.bump,
+        gig: deal.gig,
+        client: deal.client,
+        freelancer: deal.freelancer,
+        offer: deal.offer,
+        client_fee,
+        client_fee_percentage: client_fee_percentage.unwrap_or(0),
+        fee_tier: fee_tier.map(|index| index as u8),
+        deadline: deal.deadline,
+        deadline_duration: deal.deadline_duration,
+        referrer: deal.referrer,
+        terms_hash: deal.terms_hash,
+        nonce: deal.nonce,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if needs_approval {
+        emit!(DealPendingApproval {
+            deal: deal.key(),
+            client: deal.client,
+            offer: deal.offer,
+            timestamp: clock.unix_timestamp,
+        });
     }
 
-    Ok(())
+    Ok(deal.key())
 }
@@ -1,4 +1,7 @@
-use crate::states::Gig;
+use crate::{
+    states::{Config, Gig, GigStatus, MAX_METADATA_URI_LEN},
+    CustomError,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
 
@@ -8,6 +11,24 @@ pub struct CreateGigParams {
     pub skills: u64,
     pub asking: u64,
     pub min_completion_time: i64,
+    pub metadata_hash: [u8; 32],
+    /// Bytes used of `metadata_uri`, at most `MAX_METADATA_URI_LEN`.
+    pub metadata_uri_len: u8,
+    /// Zero padded past `metadata_uri_len`.
+    pub metadata_uri: [u8; MAX_METADATA_URI_LEN],
+    /// Zero for unlimited.
+    pub max_concurrent_deals: u16,
+    /// None for no upper bound, otherwise at least `asking`.
+    pub max_asking: Option<u64>,
+    /// Percentage of the offer refunded to the client for late completion, 2 decimal places.
+    pub late_penalty_bps: u16,
+    /// Staked by the freelancer on accept, zero for none. Decimal places depends on the mint.
+    pub required_stake: u64,
+    /// Percentage of the offer the client pays to cancel an accepted deal, 2 decimal places.
+    /// Zero to not allow cancelling accepted deals.
+    pub cancel_fee_bps: u16,
+    /// Completed deals required of clients, zero for anyone.
+    pub min_client_completed_deals: u16,
 }
 
 #[derive(Accounts)]
@@ -34,28 +55,106 @@ pub struct CreateGig<'info> {
 
     pub id: Signer<'info>,
 
+    #[account(
+        constraint = config.allowed_mints.contains(&mint.key()) @ CustomError::MintNotAllowed,
+    )]
     pub mint: Option<Account<'info, Mint>>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
     pub system_program: Program<'info, System>,
 }
 
 pub fn create_gig_handler(ctx: Context<CreateGig>, params: CreateGigParams) -> Result<()> {
-    let gig = &mut ctx.accounts.gig;
-    gig.bump = *ctx.bumps.get("gig").unwrap();
-    gig.nonce = ctx.accounts.id.key().to_bytes()[..8].try_into().unwrap();
-    gig.payer = ctx.accounts.payer.key();
-    gig.owner = ctx.accounts.owner.key();
-    gig.mint = match &ctx.accounts.mint {
-        Some(mint) => Some(mint.key()),
-        None => None,
-    };
-
-    gig.state = 1; // if params.is_published { 1 } else { 0 };
+    open_gig(
+        &mut ctx.accounts.gig,
+        *ctx.bumps.get("gig").unwrap(),
+        ctx.accounts.id.key(),
+        ctx.accounts.payer.key(),
+        ctx.accounts.owner.key(),
+        ctx.accounts.mint.as_ref().map(|mint| mint.key()),
+        &params,
+    )
+}
+
+/// Validates the params and fills in the new gig, shared by `create_gig` and `create_gig_and_deal`.
+pub fn open_gig(
+    gig: &mut Account<Gig>,
+    bump: u8,
+    id: Pubkey,
+    payer: Pubkey,
+    owner: Pubkey,
+    mint: Option<Pubkey>,
+    params: &CreateGigParams,
+) -> Result<()> {
+    check_gig_terms(
+        params.asking,
+        params.max_asking,
+        params.min_completion_time,
+        params.late_penalty_bps,
+        params.cancel_fee_bps,
+    )?;
+
+    Gig::validate_metadata_uri(params.metadata_uri_len, &params.metadata_uri)?;
+
+    gig.bump = bump;
+    gig.nonce = id.to_bytes()[..8].try_into().unwrap();
+    gig.payer = payer;
+    gig.owner = owner;
+    gig.mint = mint;
+
+    gig.state = GigStatus::Open as u8;
     gig.pending_deals = 0;
     gig.category = params.category;
     gig.skills = params.skills;
     gig.asking = params.asking;
     gig.min_completion_time = params.min_completion_time;
+    gig.metadata_hash = params.metadata_hash;
+    gig.metadata_uri_len = params.metadata_uri_len;
+    gig.metadata_uri = params.metadata_uri;
+    gig.active_deals = 0;
+    gig.max_concurrent_deals = params.max_concurrent_deals;
+    gig.max_asking = params.max_asking;
+    gig.late_penalty_bps = params.late_penalty_bps;
+    gig.required_stake = params.required_stake;
+    gig.allowed_referrers = vec![];
+    gig.cancel_fee_bps = params.cancel_fee_bps;
+    gig.min_client_completed_deals = params.min_client_completed_deals;
+
+    Ok(())
+}
+
+/// Checks the pricing and penalty terms of a gig, also those a `GigTemplate` provides.
+pub fn check_gig_terms(
+    asking: u64,
+    max_asking: Option<u64>,
+    min_completion_time: i64,
+    late_penalty_bps: u16,
+    cancel_fee_bps: u16,
+) -> Result<()> {
+    if asking == 0 {
+        return Err(error!(CustomError::InvalidAsking));
+    }
+
+    if matches!(max_asking, Some(max_asking) if max_asking < asking) {
+        return Err(error!(CustomError::InvalidAsking));
+    }
+
+    if late_penalty_bps > 100_00 {
+        return Err(error!(CustomError::InvalidLatePenalty));
+    }
+
+    if cancel_fee_bps > 100_00 {
+        return Err(error!(CustomError::InvalidCancelFee));
+    }
+
+    if min_completion_time <= 0 {
+        return Err(error!(CustomError::InvalidCompletionTime));
+    }
 
     Ok(())
 }
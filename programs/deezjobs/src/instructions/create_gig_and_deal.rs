@@ -0,0 +1,228 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use super::{open_deal, open_gig, CreateDealParams, CreateGigParams, NewDeal};
+use crate::{
+    states::{Config, Deal, DealIndex, Exemption, Gig, ReferrerActivity, Reputation, Stats},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct CreateGigAndDealParams {
+    pub gig: CreateGigParams,
+    pub deal: CreateDealParams,
+}
+
+/// Same accounts as `create_gig` and `create_deal` together, the freelancer pays for the gig and
+/// the client for the deal and its escrow.
+#[derive(Accounts)]
+#[instruction(params: CreateGigAndDealParams)]
+pub struct CreateGigAndDeal<'info> {
+    #[account(
+        init,
+        payer = owner,
+        seeds = [
+            b"gig",
+            owner.key().as_ref(),
+            &id.key().to_bytes()[..8],
+        ],
+        bump,
+        space = Gig::len()
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    /// Freelancer the gig is created for.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub id: Signer<'info>,
+
+    #[account(
+        init,
+        payer = client,
+        seeds = [
+            b"deal",
+            client.key().as_ref(),
+            gig.key().as_ref(),
+            Deal::nonce_seed(&params.deal.nonce.to_le_bytes()),
+        ],
+        bump,
+        space = Deal::len()
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        init,
+        payer = client,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Only for SPL gigs while `config.split_escrow` is set, holds the client fee apart from the
+    /// offer, see `Deal.fee_escrow`.
+    #[account(
+        init,
+        payer = client,
+        seeds = [b"fee_escrow", deal.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = deal,
+    )]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = client,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Optional for SPL gigs, a freelancer who can't receive the mint fails here instead of
+    /// at settlement.
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Sets the gig's mint, native SOL without it.
+    #[account(
+        constraint = config.allowed_mints.contains(&mint.key()) @ CustomError::MintNotAllowed,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists if the client is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", client.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub client_blacklist: UncheckedAccount<'info>,
+
+    /// Only exists if the freelancer is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", owner.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub freelancer_blacklist: UncheckedAccount<'info>,
+
+    /// CHECK: compared against the price feed registered in the config for the gig's mint
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", owner.key().as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Needed if the gig requires completed deals of the client, counted as zero without it.
+    #[account(
+        seeds = [b"reputation", client.key().as_ref()],
+        bump = client_reputation.bump,
+    )]
+    pub client_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", client.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    /// Required with a referrer while `config.referrer_cooldown` is set.
+    #[account(
+        init_if_needed,
+        payer = client,
+        seeds = [
+            b"referrer_activity",
+            params.deal.referrer.unwrap_or_default().as_ref(),
+            client.key().as_ref(),
+        ],
+        bump,
+        space = ReferrerActivity::len(),
+    )]
+    pub referrer_activity: Option<Box<Account<'info, ReferrerActivity>>>,
+
+    /// Lists the deal among the freelancer's recent deals, see `DealIndex`.
+    #[account(
+        init_if_needed,
+        payer = client,
+        seeds = [b"deal_index", owner.key().as_ref()],
+        bump,
+        space = DealIndex::len(),
+    )]
+    pub deal_index: Option<Box<Account<'info, DealIndex>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Creates the gig and funds its first deal at once, so the gig can't be changed in between.
+/// Returns the address of the new deal like `create_deal`.
+pub fn create_gig_and_deal_handler(
+    ctx: Context<CreateGigAndDeal>,
+    params: CreateGigAndDealParams,
+) -> Result<Pubkey> {
+    let accounts = ctx.accounts;
+
+    open_gig(
+        &mut accounts.gig,
+        *ctx.bumps.get("gig").unwrap(),
+        accounts.id.key(),
+        accounts.owner.key(),
+        accounts.owner.key(),
+        accounts.mint.as_ref().map(|mint| mint.key()),
+        &params.gig,
+    )?;
+
+    open_deal(
+        NewDeal {
+            deal: &mut accounts.deal,
+            gig: &mut accounts.gig,
+            client: &accounts.client,
+            config: &accounts.config,
+            escrow: accounts.escrow.as_deref(),
+            fee_escrow: accounts.fee_escrow.as_deref(),
+            owner_wallet: accounts.owner_wallet.as_deref(),
+            mint: accounts.mint.as_deref(),
+            client_blacklist: &accounts.client_blacklist,
+            freelancer_blacklist: &accounts.freelancer_blacklist,
+            price_feed: accounts.price_feed.as_deref(),
+            freelancer_reputation: accounts.freelancer_reputation.as_deref(),
+            client_reputation: accounts.client_reputation.as_deref(),
+            client_exemption: accounts.client_exemption.as_deref(),
+            referrer_activity: accounts.referrer_activity.as_deref_mut(),
+            deal_index: accounts.deal_index.as_deref_mut(),
+            stats: &accounts.stats,
+            system_program: &accounts.system_program,
+            token_program: accounts.token_program.as_ref(),
+        },
+        &ctx.bumps,
+        &params.deal,
+    )
+}
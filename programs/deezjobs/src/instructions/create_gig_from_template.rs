@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use super::{open_gig, CreateGigParams};
+use crate::{
+    states::{Config, Gig, GigTemplate, MAX_METADATA_URI_LEN},
+    CustomError,
+};
+
+/// The fields of `CreateGigParams` the template doesn't provide, and overrides of those it does.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct CreateGigFromTemplateParams {
+    pub category: Option<u8>,
+    pub asking: Option<u64>,
+    pub min_completion_time: Option<i64>,
+    pub late_penalty_bps: Option<u16>,
+    pub cancel_fee_bps: Option<u16>,
+    pub skills: u64,
+    pub metadata_hash: [u8; 32],
+    /// Bytes used of `metadata_uri`, at most `MAX_METADATA_URI_LEN`.
+    pub metadata_uri_len: u8,
+    /// Zero padded past `metadata_uri_len`.
+    pub metadata_uri: [u8; MAX_METADATA_URI_LEN],
+    /// Zero for unlimited.
+    pub max_concurrent_deals: u16,
+    /// None for no upper bound, otherwise at least the asking price.
+    pub max_asking: Option<u64>,
+    /// Staked by the freelancer on accept, zero for none. Decimal places depends on the mint.
+    pub required_stake: u64,
+    /// Completed deals required of clients, zero for anyone.
+    pub min_client_completed_deals: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateGigFromTemplateParams)]
+pub struct CreateGigFromTemplate<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            b"gig",
+            owner.key().as_ref(),
+            &id.key().to_bytes()[..8],
+        ],
+        bump,
+        space = Gig::len()
+    )]
+    pub gig: Account<'info, Gig>,
+
+    #[account(
+        seeds = [
+            b"gig_template",
+            owner.key().as_ref(),
+            &template.template_id.to_le_bytes(),
+        ],
+        bump = template.bump,
+    )]
+    pub template: Account<'info, GigTemplate>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub id: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates a gig with the template's defaults, the overrides are validated like `create_gig`.
+pub fn create_gig_from_template_handler(
+    ctx: Context<CreateGigFromTemplate>,
+    params: CreateGigFromTemplateParams,
+) -> Result<()> {
+    let template = &ctx.accounts.template;
+
+    // The mint may have been delisted since the template was saved.
+    if matches!(template.mint, Some(mint) if !ctx.accounts.config.allowed_mints.contains(&mint)) {
+        return Err(error!(CustomError::MintNotAllowed));
+    }
+
+    let gig_params = CreateGigParams {
+        category: params.category.unwrap_or(template.category),
+        skills: params.skills,
+        asking: params.asking.unwrap_or(template.asking),
+        min_completion_time: params
+            .min_completion_time
+            .unwrap_or(template.min_completion_time),
+        metadata_hash: params.metadata_hash,
+        metadata_uri_len: params.metadata_uri_len,
+        metadata_uri: params.metadata_uri,
+        max_concurrent_deals: params.max_concurrent_deals,
+        max_asking: params.max_asking,
+        late_penalty_bps: params.late_penalty_bps.unwrap_or(template.late_penalty_bps),
+        required_stake: params.required_stake,
+        cancel_fee_bps: params.cancel_fee_bps.unwrap_or(template.cancel_fee_bps),
+        min_client_completed_deals: params.min_client_completed_deals,
+    };
+
+    open_gig(
+        &mut ctx.accounts.gig,
+        *ctx.bumps.get("gig").unwrap(),
+        ctx.accounts.id.key(),
+        ctx.accounts.payer.key(),
+        ctx.accounts.owner.key(),
+        template.mint,
+        &gig_params,
+    )
+}
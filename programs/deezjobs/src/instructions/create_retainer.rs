@@ -0,0 +1,213 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+
+use crate::{
+    events::RetainerCreated,
+    states::{Config, DealStatus, Exemption, Gig, GigStatus, Reputation, Retainer, Stats},
+    utils::{client_fee_percentage, compute_client_fee},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct CreateRetainerParams {
+    /// Seconds until each period can be released, the first one counted from creation.
+    pub period_seconds: i64,
+    /// Offer for each period, the client fee is charged on top of it.
+    pub per_period_amount: u64,
+    pub periods_total: u16,
+    /// Lets anyone release the periods once due, so the freelancer isn't kept waiting.
+    pub allow_crank: bool,
+    /// Unused by the client's other retainers for the gig.
+    pub nonce: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateRetainerParams)]
+pub struct CreateRetainer<'info> {
+    #[account(
+        init,
+        payer = client,
+        seeds = [
+            b"retainer",
+            client.key().as_ref(),
+            gig.key().as_ref(),
+            &params.nonce.to_le_bytes(),
+        ],
+        bump,
+        space = Retainer::len()
+    )]
+    pub retainer: Box<Account<'info, Retainer>>,
+
+    #[account(
+        constraint = gig.state == GigStatus::Open as u8 @ CustomError::GigNotOpen,
+        constraint = gig.mint.is_none() @ CustomError::RetainerNotNative,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists if the client is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", client.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub client_blacklist: UncheckedAccount<'info>,
+
+    /// Only exists if the freelancer is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", gig.owner.as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub freelancer_blacklist: UncheckedAccount<'info>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", gig.owner.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", client.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Escrows every period of the retainer upfront, each with its client fee. The lamports are held
+/// by the retainer account, SPL gigs are rejected with `RetainerNotNative` until retainers get
+/// an escrow token account like deals.
+pub fn create_retainer_handler(
+    ctx: Context<CreateRetainer>,
+    params: CreateRetainerParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let client = &ctx.accounts.client;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if !ctx.accounts.client_blacklist.data_is_empty()
+        || !ctx.accounts.freelancer_blacklist.data_is_empty()
+    {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    if client.key() == gig.owner {
+        return Err(error!(CustomError::SelfDeal));
+    }
+
+    if params.period_seconds <= 0 || params.per_period_amount == 0 || params.periods_total == 0 {
+        return Err(error!(CustomError::InvalidRetainerSchedule));
+    }
+
+    let offer = params
+        .per_period_amount
+        .checked_mul(u64::from(params.periods_total))
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if config.max_deal_offer > 0 && offer > config.max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    // Every period is escrowed right away, a retainer can't wait for `approve_deal`.
+    if config.needs_approval(offer) {
+        return Err(error!(CustomError::OfferNeedsApproval));
+    }
+
+    let per_period_client_fee = compute_client_fee(
+        config,
+        None,
+        None,
+        None,
+        client_fee_percentage(
+            config,
+            ctx.accounts.freelancer_reputation.as_deref(),
+            ctx.accounts.client_exemption.as_deref(),
+        ),
+        params.per_period_amount,
+        clock.unix_timestamp,
+    )?;
+
+    let next_release_ts = clock
+        .unix_timestamp
+        .checked_add(params.period_seconds)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // The last period is what the deadline is to a deal.
+    let last_release_ts = params
+        .period_seconds
+        .checked_mul(i64::from(params.periods_total))
+        .and_then(|duration| clock.unix_timestamp.checked_add(duration))
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    config.check_deadline_horizon(clock.unix_timestamp, last_release_ts)?;
+
+    let retainer = &mut ctx.accounts.retainer;
+    retainer.bump = *ctx.bumps.get("retainer").unwrap();
+    retainer.client = client.key();
+    retainer.freelancer = gig.owner;
+    retainer.gig = gig.key();
+    retainer.state = DealStatus::Created as u8;
+    retainer.period_seconds = params.period_seconds;
+    retainer.per_period_amount = params.per_period_amount;
+    retainer.per_period_client_fee = per_period_client_fee;
+    retainer.periods_total = params.periods_total;
+    retainer.periods_released = 0;
+    retainer.next_release_ts = next_release_ts;
+    retainer.allow_crank = params.allow_crank;
+    retainer.nonce = params.nonce;
+    retainer.time_created = clock.unix_timestamp;
+
+    let total_escrow_amount = retainer
+        .expected_escrow()
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    let transfer_ix = system_program::Transfer {
+        from: client.to_account_info(),
+        to: retainer.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+    system_program::transfer(cpi_ctx, total_escrow_amount)?;
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_created(offer)?;
+    stats.record_escrowed(total_escrow_amount, config.max_tvl)?;
+
+    emit!(RetainerCreated {
+        retainer: retainer.key(),
+        gig: retainer.gig,
+        client: retainer.client,
+        freelancer: retainer.freelancer,
+        period_seconds: retainer.period_seconds,
+        per_period_amount: retainer.per_period_amount,
+        per_period_client_fee,
+        periods_total: retainer.periods_total,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
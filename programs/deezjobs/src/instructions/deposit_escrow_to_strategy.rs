@@ -0,0 +1,128 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::EscrowDepositedToStrategy,
+    states::{Config, Deal, DealStatus},
+    utils::{fee_escrow_of, invoke_strategy},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct DepositEscrowToStrategyParams {
+    pub amount: u64,
+}
+
+/// The strategy's own accounts follow as remaining accounts, passed on as they are.
+#[derive(Accounts)]
+pub struct DepositEscrowToStrategy<'info> {
+    /// Only deals waiting on the freelancer, settled ones release the escrow right away.
+    #[account(
+        mut,
+        constraint = matches!(
+            deal.status()?,
+            DealStatus::Created | DealStatus::Accepted
+        ) @ CustomError::InvalidState,
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        executable,
+        constraint = config.yield_strategy == Some(strategy.key()) @ CustomError::InvalidStrategy,
+    )]
+    /// CHECK: the strategy registered in the config
+    pub strategy: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Parks `amount` of an idle escrow in the yield strategy, see `withdraw_escrow_from_strategy`.
+pub fn deposit_escrow_to_strategy_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositEscrowToStrategy<'info>>,
+    params: DepositEscrowToStrategyParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let escrow_amount = ctx.accounts.escrow.amount;
+    let deposited = ctx.accounts.deal.strategy_deposited;
+
+    if params.amount == 0 || params.amount > escrow_amount {
+        return Err(error!(CustomError::InvalidStrategyDeposit));
+    }
+
+    // What's in escrow and in the strategy has to add up before more is moved. The client fee of
+    // a split escrow stays put, only the offer is deposited.
+    let fee_amount = fee_escrow_of(&ctx.accounts.deal, ctx.accounts.fee_escrow.as_deref())?
+        .map_or(0, |fee_escrow| fee_escrow.amount);
+
+    let recoverable_amount = escrow_amount
+        .checked_add(deposited)
+        .and_then(|amount| amount.checked_add(fee_amount))
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if recoverable_amount != ctx.accounts.deal.expected_escrow()? {
+        return Err(error!(CustomError::EscrowBalanceMismatch));
+    }
+
+    // One strategy at a time, the config may have moved on since the last deposit.
+    if deposited > 0 && ctx.accounts.deal.strategy != Some(ctx.accounts.strategy.key()) {
+        return Err(error!(CustomError::InvalidStrategy));
+    }
+
+    invoke_strategy(
+        &ctx.accounts.strategy,
+        &ctx.accounts.deal,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+        "deposit",
+        &params.amount.to_le_bytes(),
+    )?;
+
+    ctx.accounts.escrow.reload()?;
+
+    if escrow_amount.checked_sub(ctx.accounts.escrow.amount) != Some(params.amount) {
+        return Err(error!(CustomError::StrategyTransferMismatch));
+    }
+
+    let deal = &mut ctx.accounts.deal;
+    deal.strategy = Some(ctx.accounts.strategy.key());
+    deal.strategy_deposited = deposited
+        .checked_add(params.amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    emit!(EscrowDepositedToStrategy {
+        deal: deal.key(),
+        strategy: ctx.accounts.strategy.key(),
+        amount: params.amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
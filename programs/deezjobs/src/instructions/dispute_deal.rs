@@ -0,0 +1,41 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::DealDisputed,
+    states::{Deal, DealStatus},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct DisputeDeal<'info> {
+    #[account(
+        mut,
+        constraint = matches!(
+            deal.status()?,
+            DealStatus::Accepted | DealStatus::Delivered | DealStatus::PendingSettlement
+        ),
+    )]
+    pub deal: Account<'info, Deal>,
+
+    #[account(
+        constraint = signer.key() == deal.client.key()
+            || signer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+}
+
+pub fn dispute_deal_handler(ctx: Context<DisputeDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+
+    deal.state = DealStatus::Disputed as u8;
+    deal.time_disputed = clock.unix_timestamp;
+
+    emit!(DealDisputed {
+        deal: deal.key(),
+        disputed_by: ctx.accounts.signer.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
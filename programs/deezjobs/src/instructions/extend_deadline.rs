@@ -0,0 +1,73 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::DeadlineExtended,
+    states::{Config, Deal, DealStatus, Gig},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct ExtendDeadlineParams {
+    pub new_deadline: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: ExtendDeadlineParams)]
+pub struct ExtendDeadline<'info> {
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Accepted as u8,
+        constraint = params.new_deadline > deal.deadline @ CustomError::DeadlineNotExtended,
+    )]
+    pub deal: Account<'info, Deal>,
+
+    #[account(
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Account<'info, Gig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+}
+
+pub fn extend_deadline_handler(
+    ctx: Context<ExtendDeadline>,
+    params: ExtendDeadlineParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+    let gig = &ctx.accounts.gig;
+
+    if deal.time_created + gig.min_completion_time > params.new_deadline {
+        return Err(error!(CustomError::DeadlineTooShort));
+    }
+
+    ctx.accounts
+        .config
+        .check_deadline_horizon(deal.time_created, params.new_deadline)?;
+
+    let old_deadline = deal.deadline;
+    deal.deadline = params.new_deadline;
+
+    emit!(DeadlineExtended {
+        deal: deal.key(),
+        old_deadline,
+        new_deadline: params.new_deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
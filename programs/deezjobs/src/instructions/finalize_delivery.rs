@@ -0,0 +1,31 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use super::{settle_or_hold, CompleteDeal};
+use crate::{events::DeliveryFinalized, states::DealStatus, CustomError};
+
+/// Completes a delivered deal on the freelancer's behalf once the client let the review window
+/// pass without approving, requesting a revision or disputing. Anyone can call it.
+pub fn finalize_delivery_handler(ctx: Context<CompleteDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &ctx.accounts.deal;
+
+    if deal.status()? != DealStatus::Delivered
+        || deal.review_deadline == 0
+        || clock.unix_timestamp < deal.review_deadline
+    {
+        return Err(error!(CustomError::ReviewWindowOpen));
+    }
+
+    let deal_key = deal.key();
+    let finalized_by = ctx.accounts.signer.key();
+
+    settle_or_hold(ctx)?;
+
+    emit!(DeliveryFinalized {
+        deal: deal_key,
+        finalized_by,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use super::{settle_deal, CompleteDeal};
+use crate::{events::SettlementFinalized, states::DealStatus, CustomError};
+
+/// Pays out a deal held by `config.settlement_delay` once the delay has passed.
+/// Anyone can call it, a dispute raised in the meantime takes the deal out of settlement.
+pub fn finalize_settlement_handler(ctx: Context<CompleteDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &ctx.accounts.deal;
+
+    if deal.status()? != DealStatus::PendingSettlement {
+        return Err(error!(CustomError::InvalidState));
+    }
+
+    if clock.unix_timestamp < deal.settle_after {
+        return Err(error!(CustomError::SettlementDelayNotElapsed));
+    }
+
+    let deal_key = deal.key();
+    let finalized_by = ctx.accounts.signer.key();
+
+    settle_deal(ctx)?;
+
+    emit!(SettlementFinalized {
+        deal: deal_key,
+        finalized_by,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
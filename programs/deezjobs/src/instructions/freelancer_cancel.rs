@@ -0,0 +1,176 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::FreelancerCancelled,
+    states::{Config, Deal, DealIndex, DealStatus, Gig, Reputation, Stats},
+    utils::{percentage_of, Escrow},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct FreelancerCancel<'info> {
+    /// Before accepting the freelancer can just leave the offer, see `reject_deal`.
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Accepted as u8 @ CustomError::InvalidState,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key(),
+    )]
+    /// CHECK: should be the client of the deal
+    pub client: UncheckedAccount<'info>,
+
+    /// Receives what's left of the stake, only needed for SPL gigs.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer,
+    )]
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key(),
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Created when the freelancer accepted the deal.
+    #[account(
+        mut,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    /// Updated with the deal's new state if passed, see `DealIndex`.
+    #[account(
+        mut,
+        seeds = [b"deal_index", deal.freelancer.as_ref()],
+        bump = deal_index.bump,
+    )]
+    pub deal_index: Option<Box<Account<'info, DealIndex>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Lets the freelancer back out of an accepted deal. The client gets the whole escrow back along
+/// with `config.freelancer_cancel_penalty_bps` of the stake, the rest of the stake is returned.
+pub fn freelancer_cancel_handler(ctx: Context<FreelancerCancel>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let stake = ctx.accounts.deal.stake_amount;
+
+    let escrow = Escrow::new(
+        &ctx.accounts.deal,
+        ctx.accounts.deal.mint.is_none(),
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+
+    let escrow_amount = escrow.checked_amount()?;
+    let stake_forfeited = percentage_of(stake, ctx.accounts.config.freelancer_cancel_penalty_bps)?;
+    let stake_returned = stake
+        .checked_sub(stake_forfeited)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    // Offer, client fee and the forfeited stake.
+    let refund_amount = escrow_amount
+        .checked_sub(stake_returned)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if stake_returned > 0 {
+        escrow.release(
+            ctx.accounts.freelancer_token_account.as_deref(),
+            &ctx.accounts.freelancer,
+            stake_returned,
+        )?;
+    }
+
+    escrow.release_fee(
+        ctx.accounts.owner_wallet.as_deref(),
+        &ctx.accounts.client,
+        refund_amount,
+    )?;
+    escrow.sweep(ctx.accounts.owner_wallet.as_deref(), &ctx.accounts.client)?;
+    escrow.close(&ctx.accounts.client)?;
+
+    let deal = &mut ctx.accounts.deal;
+    deal.state = DealStatus::Cancelled as u8;
+
+    if let Some(deal_index) = ctx.accounts.deal_index.as_mut() {
+        deal_index.record(deal.key(), deal.state);
+    }
+
+    let gig = &mut ctx.accounts.gig;
+    gig.active_deals = gig.active_deals.saturating_sub(1);
+    gig.pending_deals = gig.pending_deals.saturating_sub(1);
+
+    ctx.accounts.reputation.cancelled_deals = ctx
+        .accounts
+        .reputation
+        .cancelled_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_closed(deal.total_escrow);
+
+    emit!(FreelancerCancelled {
+        deal: deal.key(),
+        freelancer: deal.freelancer,
+        refund_amount,
+        stake_forfeited,
+        stake_returned,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
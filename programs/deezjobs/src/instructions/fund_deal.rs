@@ -0,0 +1,184 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::DealFunded,
+    states::{Config, Deal, DealStatus, Gig, Stats},
+    utils::{check_mint_supported, deposit_tokens, fee_escrow_of, lamports_above_rent},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct FundDeal<'info> {
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Approved as u8 @ CustomError::InvalidState,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    /// Created empty along with the deal.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = client,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        constraint = gig.key() == deal.gig.key() @ CustomError::DealGigMismatch,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Escrows an approved deal with the offer and client fee locked in by `create_deal`, the
+/// freelancer can then accept it as usual.
+pub fn fund_deal_handler(ctx: Context<FundDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+    let client = &ctx.accounts.client;
+
+    if ctx.accounts.config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    // Counted from acceptance otherwise, see `deal.deadline_duration`.
+    if deal.deadline_duration == 0 && deal.deadline <= clock.unix_timestamp {
+        return Err(error!(CustomError::DeadlineInPast));
+    }
+
+    let total_escrow_amount = deal
+        .offer
+        .checked_add(deal.client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // Deals raised past the approval threshold after funding only need the difference.
+    let top_up_amount = total_escrow_amount.saturating_sub(deal.total_escrow);
+
+    match deal.mint {
+        Some(mint) => {
+            let (escrow, owner_wallet, token_program) = match (
+                &ctx.accounts.escrow,
+                &ctx.accounts.owner_wallet,
+                &ctx.accounts.token_program,
+            ) {
+                (Some(escrow), Some(owner_wallet), Some(token_program)) => {
+                    (escrow, owner_wallet, token_program)
+                }
+                _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+            };
+
+            // Also enforced by the account constraints, checked again so the escrow can never
+            // take another token should those be loosened.
+            if owner_wallet.mint != mint || escrow.mint != mint {
+                return Err(error!(CustomError::MintMismatch));
+            }
+
+            if let Some(mint) = &ctx.accounts.mint {
+                check_mint_supported(&mint.to_account_info())?;
+            }
+
+            let fee_escrow = fee_escrow_of(deal, ctx.accounts.fee_escrow.as_deref())?;
+
+            let escrow_amount = escrow
+                .amount
+                .checked_add(fee_escrow.map_or(0, |fee_escrow| fee_escrow.amount))
+                .ok_or(error!(CustomError::MathOverflow))?;
+
+            // Anything sent to the escrow meanwhile would be paid out along with the deal.
+            if escrow_amount != deal.total_escrow {
+                return Err(error!(CustomError::EscrowNotEmpty));
+            }
+
+            if owner_wallet.amount < top_up_amount {
+                return Err(error!(CustomError::InsufficientFunds));
+            }
+
+            deposit_tokens(
+                owner_wallet,
+                &client.to_account_info(),
+                escrow,
+                fee_escrow,
+                token_program,
+                top_up_amount,
+                deal.client_fee,
+            )?;
+        }
+        None => {
+            if lamports_above_rent(&deal.to_account_info())? != deal.total_escrow {
+                return Err(error!(CustomError::EscrowNotEmpty));
+            }
+
+            if client.lamports() < top_up_amount {
+                return Err(error!(CustomError::InsufficientFunds));
+            }
+
+            let transfer_ix = system_program::Transfer {
+                from: client.to_account_info(),
+                to: deal.to_account_info(),
+            };
+
+            let cpi_ctx =
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+            system_program::transfer(cpi_ctx, top_up_amount)?;
+        }
+    }
+
+    deal.total_escrow = total_escrow_amount.max(deal.total_escrow);
+    deal.state = DealStatus::Created as u8;
+
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_escrowed(top_up_amount, ctx.accounts.config.max_tvl)?;
+
+    emit!(DealFunded {
+        deal: deal.key(),
+        client: deal.client,
+        offer: deal.offer,
+        client_fee: deal.client_fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
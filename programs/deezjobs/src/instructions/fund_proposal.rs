@@ -0,0 +1,266 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    events::{DealPendingApproval, ProposalFunded},
+    states::{Config, Deal, DealStatus, Exemption, Gig, GigStatus, Reputation, Stats},
+    utils::{
+        check_mint_supported, client_fee_percentage, compute_client_fee, deposit_tokens,
+        freelancer_net,
+    },
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct FundProposal<'info> {
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Proposed as u8 @ CustomError::InvalidState,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        init,
+        payer = client,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Only for SPL gigs while `config.split_escrow` is set, holds the client fee apart from the
+    /// offer, see `Deal.fee_escrow`.
+    #[account(
+        init,
+        payer = client,
+        seeds = [b"fee_escrow", deal.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = deal,
+    )]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = client,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+        constraint = config.allowed_mints.contains(&mint.key()) @ CustomError::MintNotAllowed,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+        constraint = gig.state == GigStatus::Open as u8,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: compared against the price feed registered in the config for the gig's mint
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", client.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Escrows the proposed offer, the freelancer already agreed to it so the deal is accepted.
+pub fn fund_proposal_handler(ctx: Context<FundProposal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+    let gig = &mut ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let client = &ctx.accounts.client;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if gig.max_concurrent_deals > 0 && gig.active_deals >= gig.max_concurrent_deals {
+        return Err(error!(CustomError::GigAtCapacity));
+    }
+
+    if deal.deadline <= clock.unix_timestamp {
+        return Err(error!(CustomError::DeadlineInPast));
+    }
+
+    let client_fee = compute_client_fee(
+        config,
+        deal.mint,
+        ctx.accounts.mint.as_ref().map(|mint| mint.decimals),
+        ctx.accounts.price_feed.as_deref(),
+        client_fee_percentage(
+            config,
+            ctx.accounts.freelancer_reputation.as_deref(),
+            ctx.accounts.client_exemption.as_deref(),
+        ),
+        deal.offer,
+        clock.unix_timestamp,
+    )?;
+
+    let total_escrow_amount = deal
+        .offer
+        .checked_add(client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // Like `create_deal`, native SOL deals keep the fee in the deal account along with the offer.
+    let split_escrow = config.split_escrow && deal.mint.is_some();
+
+    if split_escrow != ctx.accounts.fee_escrow.is_some() {
+        return Err(error!(CustomError::FeeEscrowMismatch));
+    }
+
+    deal.fee_escrow = ctx
+        .accounts
+        .fee_escrow
+        .as_ref()
+        .map(|fee_escrow| fee_escrow.key());
+
+    // Past the approval threshold the proposal waits for `approve_deal` like a new deal would,
+    // `fund_deal` escrows it and the freelancer accepts the deal afterwards.
+    if config.needs_approval(deal.offer) {
+        deal.freelancer_net = freelancer_net(config, deal.offer)?;
+        deal.client_fee = client_fee;
+        deal.state = DealStatus::PendingApproval as u8;
+
+        gig.active_deals = gig
+            .active_deals
+            .checked_add(1)
+            .ok_or(error!(CustomError::MathOverflow))?;
+        ctx.accounts.stats.load_mut()?.record_created(deal.offer)?;
+
+        emit!(DealPendingApproval {
+            deal: deal.key(),
+            client: deal.client,
+            offer: deal.offer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        return Ok(());
+    }
+
+    match deal.mint {
+        Some(mint) => {
+            let (escrow, owner_wallet, token_program) = match (
+                &ctx.accounts.escrow,
+                &ctx.accounts.owner_wallet,
+                &ctx.accounts.token_program,
+            ) {
+                (Some(escrow), Some(owner_wallet), Some(token_program)) => {
+                    (escrow, owner_wallet, token_program)
+                }
+                _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+            };
+
+            // Also enforced by the account constraints, checked again so the escrow can never
+            // take another token should those be loosened.
+            if owner_wallet.mint != mint || escrow.mint != mint {
+                return Err(error!(CustomError::MintMismatch));
+            }
+
+            if let Some(mint) = &ctx.accounts.mint {
+                check_mint_supported(&mint.to_account_info())?;
+            }
+
+            // Only ever holds what the deal escrows, checked in case the account wasn't new.
+            let fee_escrow = ctx.accounts.fee_escrow.as_deref();
+
+            if escrow.amount != 0
+                || matches!(fee_escrow, Some(fee_escrow) if fee_escrow.amount != 0)
+            {
+                return Err(error!(CustomError::EscrowNotEmpty));
+            }
+
+            deposit_tokens(
+                owner_wallet,
+                &client.to_account_info(),
+                escrow,
+                fee_escrow,
+                token_program,
+                total_escrow_amount,
+                client_fee,
+            )?;
+        }
+        None => {
+            let transfer_ix = system_program::Transfer {
+                from: client.to_account_info(),
+                to: deal.to_account_info(),
+            };
+
+            let cpi_ctx =
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+            system_program::transfer(cpi_ctx, total_escrow_amount)?;
+        }
+    }
+
+    deal.freelancer_net = freelancer_net(config, deal.offer)?;
+    deal.client_fee = client_fee;
+    deal.total_escrow = total_escrow_amount;
+    deal.time_accepted = clock.unix_timestamp;
+    deal.state = DealStatus::Accepted as u8;
+
+    gig.active_deals = gig
+        .active_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    gig.pending_deals = gig
+        .pending_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_created(deal.offer)?;
+    stats.record_escrowed(total_escrow_amount, config.max_tvl)?;
+
+    emit!(ProposalFunded {
+        deal: deal.key(),
+        client: deal.client,
+        offer: deal.offer,
+        client_fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
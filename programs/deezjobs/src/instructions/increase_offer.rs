@@ -0,0 +1,234 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::{DealPendingApproval, OfferIncreased},
+    states::{Config, Deal, DealStatus, Exemption, Gig, Reputation, Stats},
+    utils::{
+        client_fee_percentage, compute_client_fee, deposit_tokens, fee_escrow_of, freelancer_net,
+    },
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct IncreaseOfferParams {
+    /// Added to the offer, the client fee on top of it is charged as well.
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: IncreaseOfferParams)]
+pub struct IncreaseOffer<'info> {
+    /// Accepted deals keep the terms the freelancer agreed to.
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Created as u8 @ CustomError::InvalidState,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key(),
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: compared against the price feed registered in the config for the gig's mint
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", client.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn increase_offer_handler(
+    ctx: Context<IncreaseOffer>,
+    params: IncreaseOfferParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let old_offer = ctx.accounts.deal.offer;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if params.amount == 0 {
+        return Err(error!(CustomError::InvalidOffer));
+    }
+
+    // Milestones have to add up to the offer, they can't be rebalanced after creation.
+    if !ctx.accounts.deal.milestones.is_empty() {
+        return Err(error!(CustomError::MilestoneSumMismatch));
+    }
+
+    let offer = old_offer
+        .checked_add(params.amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if matches!(gig.max_asking, Some(max_asking) if offer > max_asking) {
+        return Err(error!(CustomError::OfferTooHigh));
+    }
+
+    if config.max_deal_offer > 0 && offer > config.max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    let client_fee = compute_client_fee(
+        config,
+        ctx.accounts.deal.mint,
+        ctx.accounts.mint.as_ref().map(|mint| mint.decimals),
+        ctx.accounts.price_feed.as_deref(),
+        client_fee_percentage(
+            config,
+            ctx.accounts.freelancer_reputation.as_deref(),
+            ctx.accounts.client_exemption.as_deref(),
+        ),
+        offer,
+        clock.unix_timestamp,
+    )?;
+
+    let total_escrow = offer
+        .checked_add(client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // Past the approval threshold the deal waits for `approve_deal` again, `fund_deal` then
+    // escrows the increase.
+    let needs_approval = config.needs_approval(offer);
+
+    // The client only pays the increase and the fee on it.
+    let top_up_amount = if needs_approval {
+        0
+    } else {
+        total_escrow.saturating_sub(ctx.accounts.deal.total_escrow)
+    };
+
+    if !needs_approval {
+        match ctx.accounts.deal.mint {
+            Some(_) => {
+                let (escrow, owner_wallet, token_program) = match (
+                    &ctx.accounts.escrow,
+                    &ctx.accounts.owner_wallet,
+                    &ctx.accounts.token_program,
+                ) {
+                    (Some(escrow), Some(owner_wallet), Some(token_program)) => {
+                        (escrow, owner_wallet, token_program)
+                    }
+                    _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+                };
+
+                deposit_tokens(
+                    owner_wallet,
+                    &ctx.accounts.client.to_account_info(),
+                    escrow,
+                    fee_escrow_of(&ctx.accounts.deal, ctx.accounts.fee_escrow.as_deref())?,
+                    token_program,
+                    top_up_amount,
+                    client_fee,
+                )?;
+            }
+            None => {
+                let transfer_ix = system_program::Transfer {
+                    from: ctx.accounts.client.to_account_info(),
+                    to: ctx.accounts.deal.to_account_info(),
+                };
+
+                let cpi_ctx =
+                    CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+                system_program::transfer(cpi_ctx, top_up_amount)?;
+            }
+        }
+    }
+
+    let deal = &mut ctx.accounts.deal;
+    deal.offer = offer;
+    deal.freelancer_net = freelancer_net(config, offer)?;
+    deal.client_fee = client_fee;
+    if needs_approval {
+        deal.state = DealStatus::PendingApproval as u8;
+    } else {
+        // Never below what's already escrowed, if the fee came out lower the treasury keeps the rest.
+        deal.total_escrow = total_escrow.max(deal.total_escrow);
+    }
+
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_escrowed(top_up_amount, config.max_tvl)?;
+
+    emit!(OfferIncreased {
+        deal: deal.key(),
+        old_offer,
+        new_offer: offer,
+        top_up_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if needs_approval {
+        emit!(DealPendingApproval {
+            deal: deal.key(),
+            client: deal.client,
+            offer,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
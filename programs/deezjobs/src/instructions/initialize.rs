@@ -1,13 +1,20 @@
-use crate::{program::Deezjobs, states::Config};
-use anchor_lang::prelude::*;
+use crate::{events::ConfigInitialized, program::Deezjobs, states::{Config, Stats}, CustomError};
+use anchor_lang::{prelude::*, solana_program::clock};
 use anchor_spl::{token::{Token, TokenAccount, Mint}, associated_token::AssociatedToken};
 
 #[derive(AnchorDeserialize, AnchorSerialize)]
 pub struct InitializeParams {
     pub client_fee_percentage: u16,
-    pub client_fee_min: u64, // USDC
+    pub client_fee_min: u64, // in `mint`
     pub freelancer_fee_percentage: u16,
     pub referral_fee_percentage: u16,
+    pub fee_min_usd: u64,
+    pub max_price_age: u64,
+    pub refund_grace_period: i64,
+    pub max_revisions: u8,
+    pub review_window: i64,
+    pub dispute_timeout: i64,
+    pub dispute_default_client_bps: u16,
 }
 
 #[derive(Accounts)]
@@ -22,14 +29,20 @@ pub struct Initialize<'info> {
     )]
     pub config: Account<'info, Config>,
 
-    /// CHECK: 
-    pub treasury: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = upgrade_authority,
+        seeds = [b"stats"],
+        bump,
+        space = Stats::len()
+    )]
+    pub stats: AccountLoader<'info, Stats>,
 
     #[account(
         init_if_needed,
         payer = upgrade_authority, 
         associated_token::mint = mint, 
-        associated_token::authority = treasury,
+        associated_token::authority = config,
     )]
     pub treasury_token_account: Account<'info, TokenAccount>,
 
@@ -55,16 +68,72 @@ pub struct Initialize<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+/// The config is created with `init` rather than `init_if_needed`, so running it twice fails
+/// instead of resetting the protocol settings.
 pub fn initialize_handler(ctx: Context<Initialize>, params: InitializeParams) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.bump = *ctx.bumps.get("config").unwrap();
 
+    // Every other instruction trusts `bump = config.bump`, so make sure the stored bump is the
+    // canonical one and the config can only ever be derived at a single address.
+    let (_, canonical_bump) = Pubkey::find_program_address(&[b"config"], ctx.program_id);
+
+    if config.bump != canonical_bump {
+        return Err(error!(CustomError::ConfigBumpMismatch));
+    }
+
     config.authority = ctx.accounts.upgrade_authority.key();
-    config.treasury = ctx.accounts.treasury.key();
+    config.arbiter = ctx.accounts.upgrade_authority.key();
+    config.treasury = config.key();
     config.client_fee_percentage = params.client_fee_percentage;
     config.client_fee_min = params.client_fee_min;
+    config.fee_mint = ctx.accounts.mint.key();
     config.freelancer_fee_percentage = params.freelancer_fee_percentage;
     config.referral_fee_percentage = params.referral_fee_percentage;
+    config.allowed_mints = vec![ctx.accounts.mint.key()];
+    config.price_feeds = vec![Pubkey::default()];
+    config.fee_min_usd = params.fee_min_usd;
+    config.max_price_age = params.max_price_age;
+    config.refund_grace_period = params.refund_grace_period;
+    config.paused = false;
+    config.max_revisions = params.max_revisions;
+    config.pending_authority = None;
+    config.review_window = params.review_window;
+    config.referrer_payout_mint = None;
+    config.max_deal_offer = 0;
+    config.fee_tiers = vec![];
+    config.round_fees_up = false;
+    config.referrer_cooldown = 0;
+    config.defer_freelancer_payouts = false;
+    config.max_deadline_horizon = 0;
+    config.freelancer_cancel_penalty_bps = 0;
+    config.require_approval_above = 0;
+    config.settlement_delay = 0;
+    config.max_tvl = 0;
+    config.yield_strategy = None;
+    config.client_cancel_window = 0;
+    config.attestor = None;
+    config.split_escrow = false;
+    config.dispute_timeout = params.dispute_timeout;
+    config.dispute_default_client_bps = params.dispute_default_client_bps;
+
+    if config.dispute_default_client_bps > 100_00 {
+        return Err(error!(CustomError::InvalidSplit));
+    }
+
+    if config.refund_grace_period < 0 {
+        return Err(error!(CustomError::InvalidGracePeriod));
+    }
+
+    config.check_fees()?;
+
+    let mut stats = ctx.accounts.stats.load_init()?;
+    stats.bump = *ctx.bumps.get("stats").unwrap();
+
+    emit!(ConfigInitialized {
+        authority: config.authority,
+        timestamp: clock::Clock::get()?.unix_timestamp,
+    });
 
     Ok(())
 }
@@ -0,0 +1,78 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::ReviewLeft,
+    states::{Deal, DealStatus, Reputation, Review},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct LeaveReviewParams {
+    pub stars: u8,
+    pub comment_hash: [u8; 32],
+}
+
+#[derive(Accounts)]
+#[instruction(params: LeaveReviewParams)]
+pub struct LeaveReview<'info> {
+    #[account(
+        constraint = deal.state == DealStatus::Completed as u8,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    /// The seeds only allow one review per deal.
+    #[account(
+        init,
+        payer = client,
+        seeds = [b"review", deal.key().as_ref()],
+        bump,
+        space = Review::len(),
+    )]
+    pub review: Box<Account<'info, Review>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn leave_review_handler(ctx: Context<LeaveReview>, params: LeaveReviewParams) -> Result<()> {
+    if !(1..=5).contains(&params.stars) {
+        return Err(error!(CustomError::InvalidRating));
+    }
+
+    let clock = clock::Clock::get()?;
+    let review = &mut ctx.accounts.review;
+
+    review.bump = *ctx.bumps.get("review").unwrap();
+    review.deal = ctx.accounts.deal.key();
+    review.reviewer = ctx.accounts.client.key();
+    review.stars = params.stars;
+    review.comment_hash = params.comment_hash;
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.review_count = reputation
+        .review_count
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    reputation.total_stars += u64::from(params.stars);
+
+    emit!(ReviewLeft {
+        deal: review.deal,
+        reviewer: review.reviewer,
+        stars: params.stars,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+
+use crate::{
+    events::DealMigrated,
+    states::{Deal, Gig, DEAL_VERSION},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct MigrateDeal<'info> {
+    /// Loaded by hand once resized, a stale layout can't be deserialized as a `Deal`.
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    /// CHECK: the discriminator is checked when it's loaded
+    pub deal: UncheckedAccount<'info>,
+
+    /// Gig of the deal, older deals take their mint from it.
+    pub gig: Account<'info, Gig>,
+
+    /// Either party of the deal, pays for the extra rent.
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows `deal` to the current layout, new fields start out zeroed. Does nothing if it's current.
+pub fn migrate_deal_handler(ctx: Context<MigrateDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal_info = ctx.accounts.deal.to_account_info();
+    let old_len = deal_info.data_len();
+    let new_len = Deal::len();
+
+    if old_len < new_len {
+        let rent = Rent::get()?;
+        let top_up_amount = rent
+            .minimum_balance(new_len)
+            .saturating_sub(rent.minimum_balance(old_len));
+
+        let transfer_ix = system_program::Transfer {
+            from: ctx.accounts.signer.to_account_info(),
+            to: deal_info.clone(),
+        };
+
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+        system_program::transfer(cpi_ctx, top_up_amount)?;
+    }
+
+    if !upgrade_deal(
+        &deal_info,
+        &ctx.accounts.gig.key(),
+        ctx.accounts.gig.mint,
+        &ctx.accounts.signer.key(),
+        ctx.program_id,
+    )? {
+        return Ok(());
+    }
+
+    emit!(DealMigrated {
+        deal: deal_info.key(),
+        version: DEAL_VERSION,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Reallocs `deal_info` to the current `Deal::len()` and bumps it to `DEAL_VERSION`, false if it
+/// was current already. The rent has to be topped up first.
+fn upgrade_deal(
+    deal_info: &AccountInfo,
+    gig: &Pubkey,
+    gig_mint: Option<Pubkey>,
+    signer: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<bool> {
+    let old_len = deal_info.data_len();
+    let new_len = Deal::len();
+
+    if old_len < new_len {
+        deal_info.realloc(new_len, true)?;
+    }
+
+    // Everything is reverted if the signer turns out not to be part of the deal.
+    let mut deal = Account::<Deal>::try_from(deal_info)?;
+
+    if *signer != deal.client && *signer != deal.freelancer {
+        return Err(error!(CustomError::Unauthorized));
+    }
+
+    if *gig != deal.gig {
+        return Err(error!(CustomError::DealGigMismatch));
+    }
+
+    if old_len >= new_len && deal.version >= DEAL_VERSION {
+        return Ok(false);
+    }
+
+    // Deals didn't record their mint before version 8.
+    if deal.version < 8 {
+        deal.mint = gig_mint;
+    }
+
+    deal.version = DEAL_VERSION;
+    deal.exit(program_id)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+    use super::*;
+    use crate::states::DealStatus;
+
+    /// Space taken by `settle_after`, `mint`, `strategy`, `strategy_deposited` and `fee_escrow`,
+    /// the fields added after version 7.
+    const NEW_FIELDS_LEN: usize = 8 + 33 + 33 + 8 + 33;
+
+    fn stale_deal(client: Pubkey, gig: Pubkey) -> Deal {
+        Deal {
+            bump: 255,
+            client,
+            state: DealStatus::Accepted as u8,
+            freelancer: Pubkey::new_unique(),
+            gig,
+            offer: 1_000,
+            deadline: 100,
+            time_created: 10,
+            time_accepted: 20,
+            referrer: None,
+            time_completed: 0,
+            time_disputed: 0,
+            milestones: vec![],
+            delivery_hash: [0; 32],
+            time_delivered: 0,
+            revision_count: 0,
+            counter_amount: 0,
+            freelancer_net: 950,
+            client_fee: 20,
+            total_escrow: 1_020,
+            penalty_applied: 0,
+            review_deadline: 0,
+            terms_hash: [0; 32],
+            stake_amount: 0,
+            version: 7,
+            cancel_fee_paid: 0,
+            deadline_duration: 0,
+            origin_gig: Pubkey::default(),
+            nonce: 0,
+            milestone_dues: vec![],
+            settle_after: 0,
+            mint: None,
+            strategy: None,
+            strategy_deposited: 0,
+            fee_escrow: None,
+        }
+    }
+
+    #[test]
+    fn stale_deal_is_grown_and_bumped_to_the_current_version() {
+        let client = Pubkey::new_unique();
+        let gig = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let old_len = Deal::len() - NEW_FIELDS_LEN;
+
+        let mut serialized = vec![];
+        stale_deal(client, gig)
+            .try_serialize(&mut serialized)
+            .unwrap();
+        assert!(serialized.len() <= old_len);
+
+        // Laid out the way the runtime serializes accounts: realloc writes the new length in the
+        // 8 bytes in front of the data, and reads the original length in the 4 bytes in front of
+        // the key. u64 words keep the length aligned.
+        let mut key_buf = [0u8; 36];
+        key_buf[..4].copy_from_slice(&(old_len as u32).to_le_bytes());
+        key_buf[4..].copy_from_slice(Pubkey::new_unique().as_ref());
+        let key = unsafe { &*(key_buf[4..].as_ptr() as *const Pubkey) };
+
+        let mut data_buf = vec![0u64; (8 + old_len + MAX_PERMITTED_DATA_INCREASE) / 8 + 1];
+        let data_bytes = unsafe {
+            std::slice::from_raw_parts_mut(data_buf.as_mut_ptr() as *mut u8, data_buf.len() * 8)
+        };
+        data_bytes[..8].copy_from_slice(&(old_len as u64).to_le_bytes());
+        data_bytes[8..8 + serialized.len()].copy_from_slice(&serialized);
+
+        let mut lamports = 0;
+        let deal_info = AccountInfo::new(
+            key,
+            false,
+            true,
+            &mut lamports,
+            &mut data_bytes[8..8 + old_len],
+            &crate::ID,
+            false,
+            0,
+        );
+
+        assert!(upgrade_deal(&deal_info, &gig, Some(mint), &client, &crate::ID).unwrap());
+
+        let deal = Account::<Deal>::try_from(&deal_info).unwrap();
+
+        assert_eq!(deal_info.data_len(), Deal::len());
+        assert_eq!(deal.version, DEAL_VERSION);
+        assert_eq!(deal.mint, Some(mint));
+        assert_eq!(deal.total_escrow, 1_020);
+
+        // Migrating it again is a no-op.
+        assert!(!upgrade_deal(&deal_info, &gig, Some(mint), &client, &crate::ID).unwrap());
+    }
+}
@@ -1,15 +1,135 @@
+mod accept_authority;
+mod accept_counter;
 mod accept_deal;
+mod approve_deal;
+mod attested_complete;
+mod batch_refund_expired;
+mod blacklist_user;
+mod cancel_deal;
+mod cancel_retainer;
 mod close_deal;
 mod close_gig;
+mod close_gig_account;
+mod complete_bounty;
 mod complete_deal;
+mod contribute_bounty;
+mod counter_offer;
+mod create_bounty;
 mod create_deal;
 mod create_gig;
+mod create_gig_and_deal;
+mod create_gig_from_template;
+mod create_retainer;
+mod deposit_escrow_to_strategy;
+mod dispute_deal;
+mod extend_deadline;
+mod finalize_delivery;
+mod finalize_settlement;
+mod freelancer_cancel;
+mod fund_deal;
+mod fund_proposal;
+mod increase_offer;
 mod initialize;
+mod leave_review;
+mod migrate_deal;
+mod mutual_settle;
+mod place_bid;
+mod propose_deal;
+mod quote_deal;
+mod reassign_freelancer;
+mod reclaim_bid;
+mod refund_bounty;
+mod refund_expired_deal;
+mod reject_deal;
+mod release_period;
+mod release_milestone;
+mod request_revision;
+mod resolve_dispute;
+mod resolve_timeout;
+mod select_bid;
+mod set_attestor;
+mod set_exemption;
+mod set_paused;
+mod set_referrer;
+mod set_referrer_payout_mint;
+mod set_yield_strategy;
+mod submit_bounty;
+mod submit_deliverable;
+mod tip_freelancer;
+mod transfer_authority;
+mod unblacklist_user;
+mod update_allowed_mints;
+mod update_config;
+mod update_gig;
+mod update_template;
+mod withdraw_earnings;
+mod withdraw_escrow_from_strategy;
+mod withdraw_treasury_fees;
 
+pub use accept_authority::*;
+pub use accept_counter::*;
 pub use accept_deal::*;
+pub use approve_deal::*;
+pub use attested_complete::*;
+pub use batch_refund_expired::*;
+pub use blacklist_user::*;
+pub use cancel_deal::*;
+pub use cancel_retainer::*;
 pub use close_deal::*;
 pub use close_gig::*;
+pub use close_gig_account::*;
+pub use complete_bounty::*;
 pub use complete_deal::*;
+pub use contribute_bounty::*;
+pub use counter_offer::*;
+pub use create_bounty::*;
 pub use create_deal::*;
 pub use create_gig::*;
+pub use create_gig_and_deal::*;
+pub use create_gig_from_template::*;
+pub use create_retainer::*;
+pub use deposit_escrow_to_strategy::*;
+pub use dispute_deal::*;
+pub use extend_deadline::*;
+pub use finalize_delivery::*;
+pub use finalize_settlement::*;
+pub use freelancer_cancel::*;
+pub use fund_deal::*;
+pub use fund_proposal::*;
+pub use increase_offer::*;
 pub use initialize::*;
+pub use leave_review::*;
+pub use migrate_deal::*;
+pub use mutual_settle::*;
+pub use place_bid::*;
+pub use propose_deal::*;
+pub use quote_deal::*;
+pub use reassign_freelancer::*;
+pub use reclaim_bid::*;
+pub use refund_bounty::*;
+pub use refund_expired_deal::*;
+pub use reject_deal::*;
+pub use release_period::*;
+pub use release_milestone::*;
+pub use request_revision::*;
+pub use resolve_dispute::*;
+pub use resolve_timeout::*;
+pub use select_bid::*;
+pub use set_attestor::*;
+pub use set_exemption::*;
+pub use set_paused::*;
+pub use set_referrer::*;
+pub use set_referrer_payout_mint::*;
+pub use set_yield_strategy::*;
+pub use submit_bounty::*;
+pub use submit_deliverable::*;
+pub use tip_freelancer::*;
+pub use transfer_authority::*;
+pub use unblacklist_user::*;
+pub use update_allowed_mints::*;
+pub use update_config::*;
+pub use update_gig::*;
+pub use update_template::*;
+pub use withdraw_earnings::*;
+pub use withdraw_escrow_from_strategy::*;
+pub use withdraw_treasury_fees::*;
@@ -0,0 +1,284 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    events::MutualSettlement,
+    states::{Config, Deal, DealIndex, DealStatus, FreelancerVault, Gig, Reputation, Stats},
+    utils::{freelancer_net, percentage_of, Escrow},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct MutualSettleParams {
+    /// Share of the offer paid to the freelancer. Percentage with 2 decimal places (0 to 10000).
+    pub freelancer_bps: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(params: MutualSettleParams)]
+pub struct MutualSettle<'info> {
+    /// Also settles a dispute the arbiter hasn't resolved yet.
+    #[account(
+        mut,
+        constraint = matches!(
+            deal.status()?,
+            DealStatus::Accepted | DealStatus::Delivered | DealStatus::Disputed
+        ) @ CustomError::InvalidState,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key() @ CustomError::DealGigMismatch,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key() @ CustomError::WalletOwnerMismatch,
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer,
+    )]
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Receives the freelancer's pay instead of their wallet while
+    /// `config.defer_freelancer_payouts` is set.
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        seeds = [b"freelancer_vault", deal.freelancer.as_ref()],
+        bump,
+        space = FreelancerVault::len(),
+    )]
+    pub freelancer_vault: Option<Box<Account<'info, FreelancerVault>>>,
+
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer_vault,
+    )]
+    pub freelancer_vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury.key() @ CustomError::InvalidTreasury,
+    )]
+    /// CHECK:
+    pub treasury: UncheckedAccount<'info>,
+
+    /// The treasury's associated token account for the deal's mint, it holds one for each mint.
+    #[account(
+        mut,
+        constraint = deal.mint == Some(treasury_token_account.mint) @ CustomError::TreasuryMintMismatch,
+        constraint = treasury_token_account.owner == config.key() @ CustomError::InvalidTreasury,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.key(), &treasury_token_account.mint)
+            @ CustomError::InvalidTreasury,
+    )]
+    pub treasury_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Created when the freelancer accepted the deal.
+    #[account(
+        mut,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    /// Updated with the deal's new state if passed, see `DealIndex`.
+    #[account(
+        mut,
+        seeds = [b"deal_index", deal.freelancer.as_ref()],
+        bump = deal_index.bump,
+    )]
+    pub deal_index: Option<Box<Account<'info, DealIndex>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Completes the deal with a split both parties signed off on, without the arbiter. The
+/// freelancer is paid `freelancer_bps` of the unreleased offer less their fee, the client gets
+/// the rest of the offer back and the treasury keeps the fees.
+pub fn mutual_settle_handler(ctx: Context<MutualSettle>, params: MutualSettleParams) -> Result<()> {
+    if params.freelancer_bps > 100_00 {
+        return Err(error!(CustomError::InvalidSplit));
+    }
+
+    let clock = clock::Clock::get()?;
+    let deal = &ctx.accounts.deal;
+
+    let escrow = Escrow::new(
+        deal,
+        ctx.accounts.deal.mint.is_none(),
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+
+    let escrow_amount = escrow.checked_amount()?;
+
+    // Milestones already released are not part of the settlement.
+    let offer = deal.unreleased_offer()?;
+
+    let freelancer_share = percentage_of(offer, params.freelancer_bps)?;
+    let freelancer_amount = freelancer_net(&ctx.accounts.config, freelancer_share)?;
+
+    let client_amount = offer
+        .checked_sub(freelancer_share)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // Remainder of the escrow amount is the client fee and the freelancer's fee on their share.
+    let treasury_amount = escrow_amount
+        .checked_sub(client_amount)
+        .and_then(|amount| amount.checked_sub(freelancer_amount))
+        .and_then(|amount| amount.checked_sub(deal.stake_amount))
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    escrow.release(
+        ctx.accounts.owner_wallet.as_deref(),
+        &ctx.accounts.client,
+        client_amount,
+    )?;
+
+    let freelancer_payout = freelancer_amount
+        .checked_add(deal.stake_amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if ctx.accounts.config.defer_freelancer_payouts {
+        let vault = ctx
+            .accounts
+            .freelancer_vault
+            .as_ref()
+            .ok_or(error!(CustomError::MissingFreelancerVault))?;
+
+        escrow.release(
+            ctx.accounts.freelancer_vault_token_account.as_deref(),
+            &vault.to_account_info(),
+            freelancer_payout,
+        )?;
+    } else {
+        escrow.release(
+            ctx.accounts.freelancer_token_account.as_deref(),
+            &ctx.accounts.freelancer,
+            freelancer_payout,
+        )?;
+    }
+
+    escrow.release_fee(
+        ctx.accounts.treasury_token_account.as_deref(),
+        &ctx.accounts.treasury,
+        treasury_amount,
+    )?;
+
+    let treasury_amount = treasury_amount
+        .checked_add(escrow.sweep(
+            ctx.accounts.treasury_token_account.as_deref(),
+            &ctx.accounts.treasury,
+        )?)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    escrow.close(&ctx.accounts.client)?;
+
+    if ctx.accounts.config.defer_freelancer_payouts {
+        if let Some(vault) = ctx.accounts.freelancer_vault.as_mut() {
+            vault.record_deposit(
+                *ctx.bumps.get("freelancer_vault").unwrap(),
+                ctx.accounts.deal.freelancer,
+                freelancer_payout,
+            )?;
+        }
+    }
+
+    let gig = &mut ctx.accounts.gig;
+    gig.pending_deals = gig.pending_deals.saturating_sub(1);
+    gig.active_deals = gig.active_deals.saturating_sub(1);
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_closed(ctx.accounts.deal.total_escrow);
+    stats.record_fees(treasury_amount)?;
+
+    let deal = &mut ctx.accounts.deal;
+    deal.state = DealStatus::Completed as u8;
+    deal.time_completed = clock.unix_timestamp;
+
+    if let Some(deal_index) = ctx.accounts.deal_index.as_mut() {
+        deal_index.record(deal.key(), deal.state);
+    }
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.completed_deals = reputation
+        .completed_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    reputation.total_earned = reputation
+        .total_earned
+        .checked_add(freelancer_amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    emit!(MutualSettlement {
+        deal: deal.key(),
+        freelancer_bps: params.freelancer_bps,
+        freelancer_amount,
+        client_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
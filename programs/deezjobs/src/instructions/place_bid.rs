@@ -0,0 +1,180 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+
+use crate::{
+    events::BidPlaced,
+    states::{Bid, Config, Exemption, Gig, GigStatus, Reputation, Stats},
+    utils::{client_fee_percentage, compute_client_fee},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct PlaceBidParams {
+    /// At least the asking price of the gig, the client fee is charged on top of it.
+    pub offer: u64,
+    pub deadline: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: PlaceBidParams)]
+pub struct PlaceBid<'info> {
+    #[account(
+        init,
+        payer = bidder,
+        seeds = [
+            b"bid",
+            gig.key().as_ref(),
+            bidder.key().as_ref(),
+        ],
+        bump,
+        space = Bid::len()
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        constraint = gig.state == GigStatus::Open as u8,
+        constraint = gig.mint.is_none() @ CustomError::BidNotNative,
+        constraint = gig.asking <= params.offer @ CustomError::InsufficientOffer,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists if the bidder is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", bidder.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub client_blacklist: UncheckedAccount<'info>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", gig.owner.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Overrides the client fee of partner clients, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", bidder.key().as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn place_bid_handler(ctx: Context<PlaceBid>, params: PlaceBidParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let bidder = &ctx.accounts.bidder;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if !ctx.accounts.client_blacklist.data_is_empty() {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    if bidder.key() == gig.owner {
+        return Err(error!(CustomError::Unauthorized));
+    }
+
+    if params.offer == 0 {
+        return Err(error!(CustomError::InvalidOffer));
+    }
+
+    if matches!(gig.max_asking, Some(max_asking) if params.offer > max_asking) {
+        return Err(error!(CustomError::OfferTooHigh));
+    }
+
+    if config.max_deal_offer > 0 && params.offer > config.max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    let min_deadline = clock
+        .unix_timestamp
+        .checked_add(gig.min_completion_time)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if min_deadline > params.deadline {
+        return Err(error!(CustomError::DeadlineTooShort));
+    }
+
+    config.check_deadline_horizon(clock.unix_timestamp, params.deadline)?;
+
+    let client_fee = compute_client_fee(
+        config,
+        None,
+        None,
+        None,
+        client_fee_percentage(
+            config,
+            ctx.accounts.freelancer_reputation.as_deref(),
+            ctx.accounts.client_exemption.as_deref(),
+        ),
+        params.offer,
+        clock.unix_timestamp,
+    )?;
+
+    let transfer_ix = system_program::Transfer {
+        from: bidder.to_account_info(),
+        to: ctx.accounts.bid.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+    system_program::transfer(
+        cpi_ctx,
+        params
+            .offer
+            .checked_add(client_fee)
+            .ok_or(error!(CustomError::MathOverflow))?,
+    )?;
+
+    let bid = &mut ctx.accounts.bid;
+    bid.bump = *ctx.bumps.get("bid").unwrap();
+    bid.gig = gig.key();
+    bid.bidder = bidder.key();
+    bid.offer = params.offer;
+    bid.client_fee = client_fee;
+    bid.deadline = params.deadline;
+    bid.time_created = clock.unix_timestamp;
+
+    let escrow_amount = bid
+        .expected_escrow()
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_escrowed(escrow_amount, config.max_tvl)?;
+
+    emit!(BidPlaced {
+        bid: bid.key(),
+        gig: bid.gig,
+        bidder: bid.bidder,
+        offer: bid.offer,
+        client_fee,
+        deadline: bid.deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
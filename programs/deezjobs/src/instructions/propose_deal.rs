@@ -0,0 +1,158 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::DealProposed,
+    states::{Config, Deal, DealStatus, Gig, GigStatus, Reputation, DEAL_VERSION},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct ProposeDealParams {
+    /// Only this client can fund the proposal.
+    pub client: Pubkey,
+    pub offer: u64,
+    pub deadline: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: ProposeDealParams)]
+pub struct ProposeDeal<'info> {
+    /// Same address as a deal the client would create, nothing is escrowed until it's funded.
+    #[account(
+        init,
+        payer = freelancer,
+        seeds = [
+            b"deal",
+            params.client.as_ref(),
+            gig.key().as_ref(),
+        ],
+        bump,
+        space = Deal::len()
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        constraint = gig.state == GigStatus::Open as u8,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == gig.owner.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists if the client is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", params.client.as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub client_blacklist: UncheckedAccount<'info>,
+
+    /// Only exists if the freelancer is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", gig.owner.as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub freelancer_blacklist: UncheckedAccount<'info>,
+
+    /// The deal starts out accepted once funded, so it needs the freelancer's reputation.
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        seeds = [b"reputation", freelancer.key().as_ref()],
+        bump,
+        space = Reputation::len(),
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn propose_deal_handler(ctx: Context<ProposeDeal>, params: ProposeDealParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if !ctx.accounts.client_blacklist.data_is_empty()
+        || !ctx.accounts.freelancer_blacklist.data_is_empty()
+    {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    if params.client == gig.owner {
+        return Err(error!(CustomError::Unauthorized));
+    }
+
+    // Funding the proposal accepts it without the freelancer, so the stake would be skipped.
+    if gig.required_stake > 0 {
+        return Err(error!(CustomError::StakeRequired));
+    }
+
+    if params.offer == 0 {
+        return Err(error!(CustomError::InvalidOffer));
+    }
+
+    if params.offer < gig.asking {
+        return Err(error!(CustomError::InsufficientOffer));
+    }
+
+    if matches!(gig.max_asking, Some(max_asking) if params.offer > max_asking) {
+        return Err(error!(CustomError::OfferTooHigh));
+    }
+
+    if config.max_deal_offer > 0 && params.offer > config.max_deal_offer {
+        return Err(error!(CustomError::OfferExceedsCap));
+    }
+
+    let min_deadline = clock
+        .unix_timestamp
+        .checked_add(gig.min_completion_time)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if min_deadline > params.deadline {
+        return Err(error!(CustomError::DeadlineTooShort));
+    }
+
+    config.check_deadline_horizon(clock.unix_timestamp, params.deadline)?;
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.bump = *ctx.bumps.get("reputation").unwrap();
+    reputation.freelancer = gig.owner;
+
+    let deal = &mut ctx.accounts.deal;
+    deal.bump = *ctx.bumps.get("deal").unwrap();
+    deal.version = DEAL_VERSION;
+    deal.offer = params.offer;
+    deal.state = DealStatus::Proposed as u8;
+    deal.gig = gig.key();
+    deal.mint = gig.mint;
+    deal.freelancer = gig.owner;
+    deal.client = params.client;
+    deal.time_created = clock.unix_timestamp;
+    deal.deadline = params.deadline;
+
+    emit!(DealProposed {
+        deal: deal.key(),
+        gig: deal.gig,
+        client: deal.client,
+        freelancer: deal.freelancer,
+        offer: deal.offer,
+        deadline: deal.deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
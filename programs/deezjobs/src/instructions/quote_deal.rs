@@ -0,0 +1,131 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::Mint;
+
+use crate::{
+    states::{Config, Exemption, Gig, Reputation},
+    utils::{client_fee_percentage, compute_client_fee},
+    CustomError,
+};
+
+/// Maximum number of offers `quote_deals` prices at once.
+pub const MAX_QUOTE_OFFERS: usize = 10;
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct QuoteDealParams {
+    pub offer: u64,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct QuoteDealsParams {
+    /// Eg. the basic, standard and premium price of a gig, at most `MAX_QUOTE_OFFERS`.
+    pub offers: Vec<u64>,
+}
+
+/// What `create_deal` would charge for the same offer right now.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct DealQuote {
+    pub client_fee: u64,
+    /// Offer plus client fee, what the client deposits into the escrow.
+    pub total_escrow: u64,
+    pub fee_tier: Option<u8>,
+}
+
+/// Same accounts `create_deal` computes the fee from, nothing is written.
+/// Shared by `quote_deal` and `quote_deals`.
+#[derive(Accounts)]
+pub struct QuoteDeal<'info> {
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        constraint = gig.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = gig.mint == Some(mint.key()),
+        constraint = config.allowed_mints.contains(&mint.key()) @ CustomError::MintNotAllowed,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: compared against the price feed registered in the config for the gig's mint
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// Picks the fee tier of the freelancer, the base client fee applies without it.
+    #[account(
+        seeds = [b"reputation", gig.owner.as_ref()],
+        bump = freelancer_reputation.bump,
+    )]
+    pub freelancer_reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Quotes the fee of the client the exemption was set for, see `Exemption`.
+    #[account(
+        seeds = [b"exemption", client_exemption.client.as_ref()],
+        bump = client_exemption.bump,
+    )]
+    pub client_exemption: Option<Box<Account<'info, Exemption>>>,
+}
+
+/// Meant to be simulated, frontends show the exact fee instead of recomputing it.
+pub fn quote_deal_handler(ctx: Context<QuoteDeal>, params: QuoteDealParams) -> Result<DealQuote> {
+    let clock = clock::Clock::get()?;
+
+    quote_offer(ctx.accounts, params.offer, clock.unix_timestamp)
+}
+
+/// Quotes several offers in one simulation, the quotes are in the order of `params.offers`.
+pub fn quote_deals_handler(
+    ctx: Context<QuoteDeal>,
+    params: QuoteDealsParams,
+) -> Result<Vec<DealQuote>> {
+    let clock = clock::Clock::get()?;
+
+    if params.offers.len() > MAX_QUOTE_OFFERS {
+        return Err(error!(CustomError::TooManyOffers));
+    }
+
+    params
+        .offers
+        .iter()
+        .map(|offer| quote_offer(ctx.accounts, *offer, clock.unix_timestamp))
+        .collect()
+}
+
+/// Client fee of `offer`, computed the same way `create_deal` does.
+fn quote_offer(accounts: &QuoteDeal, offer: u64, now: i64) -> Result<DealQuote> {
+    let config = &accounts.config;
+
+    if offer == 0 {
+        return Err(error!(CustomError::InvalidOffer));
+    }
+
+    let fee_tier = accounts
+        .freelancer_reputation
+        .as_ref()
+        .and_then(|reputation| config.fee_tier(reputation.completed_deals));
+
+    let client_fee = compute_client_fee(
+        config,
+        accounts.gig.mint,
+        accounts.mint.as_ref().map(|mint| mint.decimals),
+        accounts.price_feed.as_deref(),
+        client_fee_percentage(
+            config,
+            accounts.freelancer_reputation.as_deref(),
+            accounts.client_exemption.as_deref(),
+        ),
+        offer,
+        now,
+    )?;
+
+    let total_escrow = offer
+        .checked_add(client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    Ok(DealQuote {
+        client_fee,
+        total_escrow,
+        fee_tier: fee_tier.map(|index| index as u8),
+    })
+}
@@ -0,0 +1,223 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    events::FreelancerReassigned,
+    states::{Deal, DealStatus, Gig, GigStatus, Reputation},
+    utils::Escrow,
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct ReassignFreelancer<'info> {
+    #[account(
+        mut,
+        constraint = matches!(deal.status()?, DealStatus::Accepted | DealStatus::Delivered),
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    /// Gig of the substitute, it has to be paid in the same mint.
+    #[account(
+        mut,
+        constraint = new_gig.owner == new_freelancer.key() @ CustomError::Unauthorized,
+        constraint = new_gig.state == GigStatus::Open as u8,
+        constraint = new_gig.mint == deal.mint @ CustomError::GigMintMismatch,
+    )]
+    pub new_gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+
+    #[account(mut)]
+    pub new_freelancer: Signer<'info>,
+
+    /// Only exists if the substitute is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", new_freelancer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub new_freelancer_blacklist: UncheckedAccount<'info>,
+
+    /// Settlement needs the reputation of whoever has the deal, see `accept_deal`.
+    #[account(
+        init_if_needed,
+        payer = new_freelancer,
+        seeds = [b"reputation", new_freelancer.key().as_ref()],
+        bump,
+        space = Reputation::len(),
+    )]
+    pub new_reputation: Box<Account<'info, Reputation>>,
+
+    /// Only needed to move stakes of SPL gigs, native stakes are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Gets back the stake of the original freelancer.
+    #[account(
+        mut,
+        constraint = freelancer_wallet.owner == freelancer.key(),
+        constraint = deal.mint == Some(freelancer_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub freelancer_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = new_freelancer_wallet.owner == new_freelancer.key(),
+        constraint = deal.mint == Some(new_freelancer_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub new_freelancer_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Hands the deal over to another freelancer, all three parties have to sign.
+/// The original freelancer's stake is returned and the substitute stakes what their gig requires.
+pub fn reassign_freelancer_handler(ctx: Context<ReassignFreelancer>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let new_gig = &ctx.accounts.new_gig;
+    let new_freelancer = &ctx.accounts.new_freelancer;
+
+    if !ctx.accounts.new_freelancer_blacklist.data_is_empty() {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    if new_freelancer.key() == ctx.accounts.deal.client
+        || new_freelancer.key() == ctx.accounts.deal.freelancer
+    {
+        return Err(error!(CustomError::Unauthorized));
+    }
+
+    if new_gig.max_concurrent_deals > 0 && new_gig.active_deals >= new_gig.max_concurrent_deals {
+        return Err(error!(CustomError::GigAtCapacity));
+    }
+
+    let old_stake = ctx.accounts.deal.stake_amount;
+    let new_stake = new_gig.required_stake;
+
+    {
+        let escrow = Escrow::new(
+            &ctx.accounts.deal,
+            ctx.accounts.deal.mint.is_none(),
+            ctx.accounts.escrow.as_deref(),
+            ctx.accounts.fee_escrow.as_deref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+
+        escrow.checked_amount()?;
+
+        if old_stake > 0 {
+            escrow.release(
+                ctx.accounts.freelancer_wallet.as_deref(),
+                &ctx.accounts.freelancer,
+                old_stake,
+            )?;
+        }
+    }
+
+    if new_stake > 0 {
+        match ctx.accounts.deal.mint {
+            Some(_) => {
+                let (escrow, new_freelancer_wallet, token_program) = match (
+                    &ctx.accounts.escrow,
+                    &ctx.accounts.new_freelancer_wallet,
+                    &ctx.accounts.token_program,
+                ) {
+                    (Some(escrow), Some(new_freelancer_wallet), Some(token_program)) => {
+                        (escrow, new_freelancer_wallet, token_program)
+                    }
+                    _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+                };
+
+                let transfer_ix = Transfer {
+                    from: new_freelancer_wallet.to_account_info(),
+                    to: escrow.to_account_info(),
+                    authority: new_freelancer.to_account_info(),
+                };
+
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), transfer_ix);
+
+                anchor_spl::token::transfer(cpi_ctx, new_stake)?;
+            }
+            None => {
+                let transfer_ix = system_program::Transfer {
+                    from: new_freelancer.to_account_info(),
+                    to: ctx.accounts.deal.to_account_info(),
+                };
+
+                let cpi_ctx =
+                    CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+                system_program::transfer(cpi_ctx, new_stake)?;
+            }
+        }
+    }
+
+    let new_reputation = &mut ctx.accounts.new_reputation;
+    new_reputation.bump = *ctx.bumps.get("new_reputation").unwrap();
+    new_reputation.freelancer = new_freelancer.key();
+
+    let gig = &mut ctx.accounts.gig;
+    gig.active_deals = gig.active_deals.saturating_sub(1);
+    gig.pending_deals = gig.pending_deals.saturating_sub(1);
+
+    let new_gig = &mut ctx.accounts.new_gig;
+    new_gig.active_deals = new_gig
+        .active_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    new_gig.pending_deals = new_gig
+        .pending_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    let deal = &mut ctx.accounts.deal;
+    let old_freelancer = deal.freelancer;
+    let old_gig = deal.gig;
+
+    // The deal's address stays derived from the gig it was created for.
+    deal.origin_gig = *deal.seed_gig();
+    deal.gig = new_gig.key();
+    deal.freelancer = new_freelancer.key();
+    deal.stake_amount = new_stake;
+
+    emit!(FreelancerReassigned {
+        deal: deal.key(),
+        old_freelancer,
+        new_freelancer: deal.freelancer,
+        old_gig,
+        new_gig: deal.gig,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
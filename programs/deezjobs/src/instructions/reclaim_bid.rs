@@ -0,0 +1,64 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::BidReclaimed,
+    states::{Bid, Stats},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct ReclaimBid<'info> {
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [b"bid", bid.gig.as_ref(), bid.bidder.as_ref()],
+        bump = bid.bump,
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        mut,
+        constraint = bidder.key() == bid.bidder.key(),
+    )]
+    /// CHECK: should be the bidder
+    pub bidder: UncheckedAccount<'info>,
+
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+}
+
+/// Gives the bidder back their bid, fee and rent included. Bids aren't binding so the bidder can
+/// withdraw at any time, anyone else can once the bid's deadline passed.
+pub fn reclaim_bid_handler(ctx: Context<ReclaimBid>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let bid = &ctx.accounts.bid;
+
+    if ctx.accounts.signer.key() != bid.bidder && clock.unix_timestamp <= bid.deadline {
+        return Err(error!(CustomError::Unauthorized));
+    }
+
+    let refund_amount = bid
+        .expected_escrow()
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_released(refund_amount);
+
+    emit!(BidReclaimed {
+        bid: bid.key(),
+        gig: bid.gig,
+        bidder: bid.bidder,
+        refund_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::BountyRefunded,
+    states::{Bounty, Config, DealStatus, Stats},
+    utils::{lamports_above_rent, transfer_lamports},
+    CustomError,
+};
+
+/// The wallet of each contributor is passed through `remaining_accounts`, in the order of
+/// `Bounty::contributors`.
+#[derive(Accounts)]
+pub struct RefundBounty<'info> {
+    #[account(
+        mut,
+        close = creator,
+        constraint = bounty.state == DealStatus::Created as u8
+            || bounty.state == DealStatus::Delivered as u8 @ CustomError::InvalidState,
+    )]
+    pub bounty: Box<Account<'info, Bounty>>,
+
+    #[account(
+        mut,
+        constraint = creator.key() == bounty.creator.key(),
+    )]
+    /// CHECK: should be the creator of the bounty
+    pub creator: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+}
+
+/// Gives every contributor back what they put in, fee included. Anyone can call it once the
+/// deadline and grace period have passed, also for work the creator never approved.
+pub fn refund_bounty_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, RefundBounty<'info>>,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let bounty = &ctx.accounts.bounty;
+    let bounty_info = bounty.to_account_info();
+
+    let refundable_after = bounty
+        .deadline
+        .checked_add(ctx.accounts.config.refund_grace_period)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if clock.unix_timestamp <= refundable_after {
+        return Err(error!(CustomError::DealNotExpired));
+    }
+
+    let escrow_amount = lamports_above_rent(&bounty_info)?;
+
+    if Some(escrow_amount) != bounty.expected_escrow() {
+        return Err(error!(CustomError::EscrowBalanceMismatch));
+    }
+
+    if ctx.remaining_accounts.len() != bounty.contributors.len() {
+        return Err(error!(CustomError::InvalidBatchAccounts));
+    }
+
+    for (contribution, wallet) in bounty.contributors.iter().zip(ctx.remaining_accounts) {
+        if wallet.key() != contribution.contributor {
+            return Err(error!(CustomError::InvalidBatchAccounts));
+        }
+
+        let refund_amount = contribution
+            .amount
+            .checked_add(contribution.client_fee)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        transfer_lamports(&bounty_info, wallet, refund_amount)?;
+    }
+
+    ctx.accounts.stats.load_mut()?.record_closed(escrow_amount);
+
+    let bounty = &mut ctx.accounts.bounty;
+    bounty.state = DealStatus::Refunded as u8;
+
+    emit!(BountyRefunded {
+        bounty: bounty.key(),
+        refund_amount: escrow_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::DealRefunded,
+    states::{Config, Deal, DealIndex, DealStatus, Gig, Reputation, Stats},
+    utils::refund_escrow,
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct RefundExpiredDeal<'info> {
+    /// Delivered deals are settled through `finalize_delivery` once the review window passes.
+    #[account(
+        mut,
+        constraint = deal.state != DealStatus::Delivered as u8 @ CustomError::DealDelivered,
+        constraint = matches!(
+            deal.status()?,
+            DealStatus::Created | DealStatus::CounterOffered | DealStatus::Accepted
+        ),
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key(),
+    )]
+    /// CHECK: should be the owner of the deal
+    pub client: UncheckedAccount<'info>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key(),
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Required if the deal was accepted, the freelancer's reputation was created then.
+    #[account(
+        mut,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Option<Box<Account<'info, Reputation>>>,
+
+    /// Updated with the deal's new state if passed, see `DealIndex`.
+    #[account(
+        mut,
+        seeds = [b"deal_index", deal.freelancer.as_ref()],
+        bump = deal_index.bump,
+    )]
+    pub deal_index: Option<Box<Account<'info, DealIndex>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn refund_expired_deal_handler(ctx: Context<RefundExpiredDeal>) -> Result<()> {
+    let deal = &mut ctx.accounts.deal;
+    let gig = &mut ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let clock = clock::Clock::get()?;
+
+    let refundable_after = deal
+        .deadline
+        .checked_add(config.refund_grace_period)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // A late delivery still beats the refund as long as it lands within the grace period.
+    // Deadlines counted from acceptance aren't set before it, so those offers don't expire.
+    if deal.deadline == 0 || clock.unix_timestamp <= refundable_after {
+        return Err(error!(CustomError::DealNotExpired));
+    }
+
+    if deal.state == DealStatus::Accepted as u8 {
+        gig.pending_deals = gig.pending_deals.saturating_sub(1);
+
+        let reputation = ctx
+            .accounts
+            .reputation
+            .as_mut()
+            .ok_or(error!(CustomError::MissingReputation))?;
+
+        reputation.cancelled_deals = reputation
+            .cancelled_deals
+            .checked_add(1)
+            .ok_or(error!(CustomError::MathOverflow))?;
+    }
+
+    // The whole escrow goes back to the client, so the freelancer's stake is forfeited to them.
+    let refund_amount = refund_escrow(
+        deal,
+        &ctx.accounts.client,
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.owner_wallet.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+        deal.mint.is_none(),
+    )?;
+
+    deal.state = DealStatus::Refunded as u8;
+
+    if let Some(deal_index) = ctx.accounts.deal_index.as_mut() {
+        deal_index.record(deal.key(), deal.state);
+    }
+    gig.active_deals = gig.active_deals.saturating_sub(1);
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_closed(deal.total_escrow);
+
+    emit!(DealRefunded {
+        deal: deal.key(),
+        refund_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
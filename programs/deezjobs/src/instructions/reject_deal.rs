@@ -0,0 +1,100 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::DealRejected,
+    states::{Deal, DealStatus, Gig, Stats},
+    utils::refund_escrow,
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct RejectDeal<'info> {
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Created as u8,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key(),
+    )]
+    /// CHECK: should be the owner of the deal
+    pub client: UncheckedAccount<'info>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key(),
+        constraint = deal.mint == Some(owner_wallet.mint) @ CustomError::MintMismatch,
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn reject_deal_handler(ctx: Context<RejectDeal>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+
+    let refund_amount = refund_escrow(
+        deal,
+        &ctx.accounts.client,
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.owner_wallet.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+        deal.mint.is_none(),
+    )?;
+
+    deal.state = DealStatus::Cancelled as u8;
+    ctx.accounts.gig.active_deals = ctx.accounts.gig.active_deals.saturating_sub(1);
+    ctx.accounts
+        .stats
+        .load_mut()?
+        .record_closed(deal.total_escrow);
+
+    emit!(DealRejected {
+        deal: deal.key(),
+        refund_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,323 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    events::MilestoneReleased,
+    states::{Config, Deal, DealStatus, FreelancerVault, Gig, Reputation, Stats},
+    utils::{freelancer_net, percentage_of, Escrow},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct ReleaseMilestoneParams {
+    pub index: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(params: ReleaseMilestoneParams)]
+pub struct ReleaseMilestone<'info> {
+    #[account(
+        mut,
+        constraint = matches!(deal.status()?, DealStatus::Accepted | DealStatus::Delivered),
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key(),
+    )]
+    /// CHECK: should be the freelancer of the deal
+    pub freelancer: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = deal.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    /// Not needed for native SOL gigs, the lamports are escrowed by the deal account itself.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    #[account(mut)]
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = client,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer,
+    )]
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Receives the freelancer's pay instead of their wallet while
+    /// `config.defer_freelancer_payouts` is set.
+    #[account(
+        init_if_needed,
+        payer = client,
+        seeds = [b"freelancer_vault", deal.freelancer.as_ref()],
+        bump,
+        space = FreelancerVault::len(),
+    )]
+    pub freelancer_vault: Option<Box<Account<'info, FreelancerVault>>>,
+
+    #[account(
+        init_if_needed,
+        payer = client,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer_vault,
+    )]
+    pub freelancer_vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Receives the late penalty of SPL deals, only needed if the milestone is released after its
+    /// due date.
+    #[account(
+        mut,
+        constraint = client_token_account.owner == client.key(),
+        constraint = deal.mint == Some(client_token_account.mint) @ CustomError::MintMismatch,
+    )]
+    pub client_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury.key(),
+    )]
+    /// CHECK:
+    pub treasury: UncheckedAccount<'info>,
+
+    /// The treasury's associated token account for the deal's mint, it holds one for each mint.
+    #[account(
+        mut,
+        constraint = deal.mint == Some(treasury_token_account.mint) @ CustomError::TreasuryMintMismatch,
+        constraint = treasury_token_account.owner == config.key() @ CustomError::InvalidTreasury,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.key(), &treasury_token_account.mint)
+            @ CustomError::InvalidTreasury,
+    )]
+    pub treasury_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Created when the freelancer accepted the deal.
+    #[account(
+        mut,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn release_milestone_handler(
+    ctx: Context<ReleaseMilestone>,
+    params: ReleaseMilestoneParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &ctx.accounts.deal;
+    let config = &ctx.accounts.config;
+
+    let milestone = deal
+        .milestones
+        .get(usize::from(params.index))
+        .ok_or(error!(CustomError::InvalidMilestone))?;
+
+    if milestone.released {
+        return Err(error!(CustomError::MilestoneAlreadyReleased));
+    }
+
+    let escrow = Escrow::new(
+        deal,
+        ctx.accounts.deal.mint.is_none(),
+        ctx.accounts.escrow.as_deref(),
+        ctx.accounts.fee_escrow.as_deref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+
+    let escrow_amount = escrow.amount()?;
+
+    let freelancer_net = freelancer_net(config, milestone.amount)?;
+    let freelancer_fee = milestone
+        .amount
+        .checked_sub(freelancer_net)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    // Releasing after the due date refunds the milestone's late penalty to the client, taken out
+    // of the freelancer's pay. Deals created before due dates have no `milestone_dues`.
+    let penalty = match deal.milestone_dues.get(usize::from(params.index)) {
+        Some(due) if due.due_ts > 0 && clock.unix_timestamp > due.due_ts => {
+            percentage_of(milestone.amount, due.late_penalty_bps)?.min(freelancer_net)
+        }
+        _ => 0,
+    };
+
+    let freelancer_pay = freelancer_net
+        .checked_sub(penalty)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    let is_last = deal
+        .milestones
+        .iter()
+        .enumerate()
+        .all(|(index, milestone)| milestone.released || index == usize::from(params.index));
+
+    // The last release also sweeps the client's fee to the treasury and returns the stake.
+    let (treasury_pay, stake_return) = if is_last {
+        let treasury_pay = escrow_amount
+            .checked_sub(freelancer_pay)
+            .and_then(|amount| amount.checked_sub(penalty))
+            .and_then(|amount| amount.checked_sub(deal.stake_amount))
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        (treasury_pay, deal.stake_amount)
+    } else {
+        (freelancer_fee, 0)
+    };
+
+    let freelancer_amount = freelancer_pay
+        .checked_add(stake_return)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if config.defer_freelancer_payouts {
+        let vault = ctx
+            .accounts
+            .freelancer_vault
+            .as_ref()
+            .ok_or(error!(CustomError::MissingFreelancerVault))?;
+
+        escrow.release(
+            ctx.accounts.freelancer_vault_token_account.as_deref(),
+            &vault.to_account_info(),
+            freelancer_amount,
+        )?;
+    } else {
+        escrow.release(
+            ctx.accounts.freelancer_token_account.as_deref(),
+            &ctx.accounts.freelancer,
+            freelancer_amount,
+        )?;
+    }
+
+    if penalty > 0 {
+        escrow.release(
+            ctx.accounts.client_token_account.as_deref(),
+            &ctx.accounts.client,
+            penalty,
+        )?;
+    }
+
+    // Earlier releases only pay the freelancer fee out of the offer, the client fee waits for the
+    // last one.
+    if is_last {
+        escrow.release_fee(
+            ctx.accounts.treasury_token_account.as_deref(),
+            &ctx.accounts.treasury,
+            treasury_pay,
+        )?;
+    } else {
+        escrow.release(
+            ctx.accounts.treasury_token_account.as_deref(),
+            &ctx.accounts.treasury,
+            treasury_pay,
+        )?;
+    }
+
+    let treasury_pay = if is_last {
+        let dust = escrow.sweep(
+            ctx.accounts.treasury_token_account.as_deref(),
+            &ctx.accounts.treasury,
+        )?;
+        escrow.close(&ctx.accounts.client)?;
+
+        treasury_pay
+            .checked_add(dust)
+            .ok_or(error!(CustomError::MathOverflow))?
+    } else {
+        treasury_pay
+    };
+
+    if config.defer_freelancer_payouts {
+        if let Some(vault) = ctx.accounts.freelancer_vault.as_mut() {
+            vault.record_deposit(
+                *ctx.bumps.get("freelancer_vault").unwrap(),
+                deal.freelancer,
+                freelancer_amount,
+            )?;
+        }
+    }
+
+    let deal = &mut ctx.accounts.deal;
+    deal.milestones[usize::from(params.index)].released = true;
+
+    if let Some(due) = deal.milestone_dues.get_mut(usize::from(params.index)) {
+        due.penalty_applied = penalty > 0;
+    }
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.total_earned = reputation
+        .total_earned
+        .checked_add(freelancer_pay)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_fees(treasury_pay)?;
+
+    if is_last {
+        deal.state = DealStatus::Completed as u8;
+        deal.time_completed = clock.unix_timestamp;
+
+        let gig = &mut ctx.accounts.gig;
+        gig.pending_deals = gig.pending_deals.saturating_sub(1);
+        gig.active_deals = gig.active_deals.saturating_sub(1);
+
+        stats.record_closed(deal.total_escrow);
+        reputation.completed_deals = reputation
+            .completed_deals
+            .checked_add(1)
+            .ok_or(error!(CustomError::MathOverflow))?;
+    }
+
+    emit!(MilestoneReleased {
+        deal: deal.key(),
+        index: params.index,
+        freelancer_amount: freelancer_pay,
+        penalty_amount: penalty,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
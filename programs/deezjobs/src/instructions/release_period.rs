@@ -0,0 +1,123 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::PeriodReleased,
+    states::{Config, DealStatus, Retainer, Stats},
+    utils::{freelancer_net, lamports_above_rent, transfer_lamports},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct ReleasePeriod<'info> {
+    #[account(
+        mut,
+        constraint = retainer.state == DealStatus::Created as u8 @ CustomError::InvalidState,
+    )]
+    pub retainer: Box<Account<'info, Retainer>>,
+
+    /// The client or the freelancer, or anyone if the client allowed cranking the retainer.
+    #[account(
+        constraint = retainer.allow_crank
+            || signer.key() == retainer.client
+            || signer.key() == retainer.freelancer @ CustomError::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+
+    /// Gets the rent back once the last period is released.
+    #[account(
+        mut,
+        constraint = client.key() == retainer.client.key(),
+    )]
+    /// CHECK: should be the client of the retainer
+    pub client: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == retainer.freelancer.key(),
+    )]
+    /// CHECK: should be the freelancer of the retainer
+    pub freelancer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury.key() @ CustomError::InvalidTreasury,
+    )]
+    /// CHECK: constraint to the config's treasury
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+}
+
+/// Pays the freelancer for the next period once it's due and moves the schedule on. The account
+/// is closed with the last period.
+pub fn release_period_handler(ctx: Context<ReleasePeriod>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let retainer = &ctx.accounts.retainer;
+    let retainer_info = retainer.to_account_info();
+
+    if clock.unix_timestamp < retainer.next_release_ts {
+        return Err(error!(CustomError::PeriodNotDue));
+    }
+
+    let expected_escrow = retainer
+        .expected_escrow()
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if lamports_above_rent(&retainer_info)? != expected_escrow {
+        return Err(error!(CustomError::EscrowBalanceMismatch));
+    }
+
+    let period_amount = retainer
+        .per_period_amount
+        .checked_add(retainer.per_period_client_fee)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    let freelancer_pay = freelancer_net(&ctx.accounts.config, retainer.per_period_amount)?;
+    let treasury_pay = period_amount
+        .checked_sub(freelancer_pay)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    transfer_lamports(&retainer_info, &ctx.accounts.freelancer, freelancer_pay)?;
+    transfer_lamports(&retainer_info, &ctx.accounts.treasury, treasury_pay)?;
+
+    let retainer = &mut ctx.accounts.retainer;
+    retainer.periods_released += 1;
+
+    let mut stats = ctx.accounts.stats.load_mut()?;
+    stats.record_fees(treasury_pay)?;
+
+    if retainer.periods_released == retainer.periods_total {
+        stats.record_closed(period_amount);
+    } else {
+        stats.record_released(period_amount);
+    }
+    retainer.next_release_ts = retainer
+        .next_release_ts
+        .checked_add(retainer.period_seconds)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    emit!(PeriodReleased {
+        retainer: retainer.key(),
+        period: retainer.periods_released,
+        freelancer_amount: freelancer_pay,
+        treasury_amount: treasury_pay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if retainer.periods_released == retainer.periods_total {
+        retainer.state = DealStatus::Completed as u8;
+        retainer.close(ctx.accounts.client.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::RevisionRequested,
+    states::{Config, Deal, DealStatus},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct RequestRevision<'info> {
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Delivered as u8,
+    )]
+    pub deal: Account<'info, Deal>,
+
+    #[account(
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn request_revision_handler(ctx: Context<RequestRevision>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+
+    if deal.revision_count >= ctx.accounts.config.max_revisions {
+        return Err(error!(CustomError::TooManyRevisions));
+    }
+
+    deal.revision_count += 1;
+    deal.state = DealStatus::Accepted as u8;
+
+    emit!(RevisionRequested {
+        deal: deal.key(),
+        revision_count: deal.revision_count,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use super::{settle_dispute, ResolveDispute};
+use crate::{events::DisputeTimedOut, CustomError};
+
+/// Settles a dispute with `config.dispute_default_client_bps` once it stayed unresolved for
+/// `config.dispute_timeout`, so funds can't be locked by an unresponsive arbiter. Anyone can call it.
+pub fn resolve_timeout_handler(ctx: Context<ResolveDispute>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &ctx.accounts.config;
+    let deal = &ctx.accounts.deal;
+
+    let timed_out_at = deal
+        .time_disputed
+        .checked_add(config.dispute_timeout)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if config.dispute_timeout == 0 || clock.unix_timestamp < timed_out_at {
+        return Err(error!(CustomError::DisputeTimeoutNotReached));
+    }
+
+    let deal_key = deal.key();
+    let resolved_by = ctx.accounts.arbiter.key();
+    let client_bps = config.dispute_default_client_bps;
+
+    settle_dispute(ctx, client_bps)?;
+
+    emit!(DisputeTimedOut {
+        deal: deal_key,
+        resolved_by,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::{BidSelected, DealPendingApproval},
+    states::{Bid, Config, Deal, DealStatus, Gig, GigStatus, Reputation, Stats, DEAL_VERSION},
+    utils::{freelancer_net, lamports_above_rent, transfer_lamports},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SelectBidParams {
+    /// Nonce of the new deal, unused by the bidder's other deals for the gig.
+    pub nonce: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: SelectBidParams)]
+pub struct SelectBid<'info> {
+    /// Its rent goes to the freelancer towards that of the deal, the escrow moves to the deal.
+    #[account(
+        mut,
+        close = freelancer,
+        seeds = [b"bid", gig.key().as_ref(), bid.bidder.as_ref()],
+        bump = bid.bump,
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    /// Same address as a deal the bidder would create with the nonce.
+    #[account(
+        init,
+        payer = freelancer,
+        seeds = [
+            b"deal",
+            bid.bidder.as_ref(),
+            gig.key().as_ref(),
+            Deal::nonce_seed(&params.nonce.to_le_bytes()),
+        ],
+        bump,
+        space = Deal::len()
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        constraint = gig.state == GigStatus::Open as u8,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == gig.owner.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Only exists if the bidder is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", bid.bidder.as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub client_blacklist: UncheckedAccount<'info>,
+
+    /// Only exists if the freelancer is blacklisted, see `Blacklist`.
+    #[account(
+        seeds = [b"blacklist", gig.owner.as_ref()],
+        bump,
+    )]
+    /// CHECK: may not be initialized
+    pub freelancer_blacklist: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        seeds = [b"reputation", freelancer.key().as_ref()],
+        bump,
+        space = Reputation::len(),
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.load()?.bump,
+    )]
+    pub stats: AccountLoader<'info, Stats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Turns the bid into an accepted deal, the freelancer picked it. The other bidders get their
+/// bids back with `reclaim_bid`.
+pub fn select_bid_handler(ctx: Context<SelectBid>, params: SelectBidParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let bid = &ctx.accounts.bid;
+    let gig = &mut ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+
+    if config.paused {
+        return Err(error!(CustomError::ProgramPaused));
+    }
+
+    if !ctx.accounts.client_blacklist.data_is_empty()
+        || !ctx.accounts.freelancer_blacklist.data_is_empty()
+    {
+        return Err(error!(CustomError::UserBlacklisted));
+    }
+
+    if gig.max_concurrent_deals > 0 && gig.active_deals >= gig.max_concurrent_deals {
+        return Err(error!(CustomError::GigAtCapacity));
+    }
+
+    // The deal starts out accepted, so the stake would be skipped.
+    if gig.required_stake > 0 {
+        return Err(error!(CustomError::StakeRequired));
+    }
+
+    if bid.deadline <= clock.unix_timestamp {
+        return Err(error!(CustomError::DeadlineInPast));
+    }
+
+    // The horizon may have been lowered since the bid was placed.
+    config.check_deadline_horizon(clock.unix_timestamp, bid.deadline)?;
+
+    // Past the approval threshold the deal waits for `approve_deal` with the bid escrowed,
+    // the freelancer accepts it once `fund_deal` cleared it.
+    let needs_approval = config.needs_approval(bid.offer);
+
+    let bid_info = bid.to_account_info();
+    let total_escrow_amount = bid
+        .expected_escrow()
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    if lamports_above_rent(&bid_info)? != total_escrow_amount {
+        return Err(error!(CustomError::EscrowBalanceMismatch));
+    }
+
+    transfer_lamports(
+        &bid_info,
+        &ctx.accounts.deal.to_account_info(),
+        total_escrow_amount,
+    )?;
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.bump = *ctx.bumps.get("reputation").unwrap();
+    reputation.freelancer = gig.owner;
+
+    let deal = &mut ctx.accounts.deal;
+    deal.bump = *ctx.bumps.get("deal").unwrap();
+    deal.version = DEAL_VERSION;
+    deal.offer = bid.offer;
+    deal.freelancer_net = freelancer_net(config, bid.offer)?;
+    deal.client_fee = bid.client_fee;
+    deal.total_escrow = total_escrow_amount;
+    deal.state = if needs_approval {
+        DealStatus::PendingApproval as u8
+    } else {
+        DealStatus::Accepted as u8
+    };
+    deal.gig = gig.key();
+    deal.mint = gig.mint;
+    deal.freelancer = gig.owner;
+    deal.client = bid.bidder;
+    deal.time_created = clock.unix_timestamp;
+    deal.deadline = bid.deadline;
+    deal.nonce = params.nonce;
+
+    gig.active_deals = gig
+        .active_deals
+        .checked_add(1)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    if !needs_approval {
+        deal.time_accepted = clock.unix_timestamp;
+        gig.pending_deals = gig
+            .pending_deals
+            .checked_add(1)
+            .ok_or(error!(CustomError::MathOverflow))?;
+    }
+    // The escrow was counted when the bid was placed, it only moves to the deal.
+    ctx.accounts.stats.load_mut()?.record_created(deal.offer)?;
+
+    emit!(BidSelected {
+        bid: bid.key(),
+        deal: deal.key(),
+        gig: deal.gig,
+        bidder: deal.client,
+        offer: deal.offer,
+        client_fee: deal.client_fee,
+        deadline: deal.deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if needs_approval {
+        emit!(DealPendingApproval {
+            deal: deal.key(),
+            client: deal.client,
+            offer: deal.offer,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{events::AttestorSet, states::Config, CustomError};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SetAttestorParams {
+    /// Leave empty to disable attested completion.
+    pub attestor: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttestor<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+}
+
+pub fn set_attestor_handler(ctx: Context<SetAttestor>, params: SetAttestorParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+
+    ctx.accounts.config.attestor = params.attestor;
+
+    emit!(AttestorSet {
+        attestor: params.attestor,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::ExemptionSet,
+    states::{Config, Exemption, MAX_TOTAL_FEE_PERCENTAGE},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SetExemptionParams {
+    pub client: Pubkey,
+    /// None to charge the client the usual fee again.
+    pub override_fee_bps: Option<u16>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetExemptionParams)]
+pub struct SetExemption<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"exemption", params.client.as_ref()],
+        bump,
+        space = Exemption::len(),
+    )]
+    pub exemption: Account<'info, Exemption>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_exemption_handler(ctx: Context<SetExemption>, params: SetExemptionParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &ctx.accounts.config;
+
+    if let Some(override_fee_bps) = params.override_fee_bps {
+        let total_fee_percentage = override_fee_bps
+            .checked_add(config.freelancer_fee_percentage)
+            .ok_or(error!(CustomError::FeeTooHigh))?;
+
+        if total_fee_percentage > MAX_TOTAL_FEE_PERCENTAGE {
+            return Err(error!(CustomError::FeeTooHigh));
+        }
+    }
+
+    let exemption = &mut ctx.accounts.exemption;
+    exemption.bump = *ctx.bumps.get("exemption").unwrap();
+    exemption.client = params.client;
+    exemption.override_fee_bps = params.override_fee_bps;
+    exemption.time_set = clock.unix_timestamp;
+
+    emit!(ExemptionSet {
+        client: params.client,
+        override_fee_bps: params.override_fee_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{events::PauseToggled, states::Config, CustomError};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SetPausedParams {
+    pub paused: bool,
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetPausedParams)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+}
+
+pub fn set_paused_handler(ctx: Context<SetPaused>, params: SetPausedParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &mut ctx.accounts.config;
+
+    config.paused = params.paused;
+
+    emit!(PauseToggled {
+        paused: params.paused,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
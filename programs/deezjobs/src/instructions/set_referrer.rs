@@ -0,0 +1,101 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::ReferrerUpdated,
+    states::{Config, Deal, DealStatus, Gig, ReferrerActivity},
+    utils::check_referrer,
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SetReferrerParams {
+    /// None to clear the referrer.
+    pub referrer: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetReferrerParams)]
+pub struct SetReferrer<'info> {
+    /// Referral terms are fixed once the freelancer accepts.
+    #[account(
+        mut,
+        constraint = deal.state == DealStatus::Created as u8 @ CustomError::InvalidState,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        constraint = gig.key() == deal.gig.key() @ CustomError::DealGigMismatch,
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Required with a referrer while `config.referrer_cooldown` is set, like in `create_deal`.
+    #[account(
+        init_if_needed,
+        payer = client,
+        seeds = [
+            b"referrer_activity",
+            params.referrer.unwrap_or_default().as_ref(),
+            client.key().as_ref(),
+        ],
+        bump,
+        space = ReferrerActivity::len(),
+    )]
+    pub referrer_activity: Option<Box<Account<'info, ReferrerActivity>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Changes or clears the referrer of a deal the freelancer hasn't accepted yet, checked the same
+/// way as at creation.
+pub fn set_referrer_handler(ctx: Context<SetReferrer>, params: SetReferrerParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+    let gig = &ctx.accounts.gig;
+    let config = &ctx.accounts.config;
+    let old_referrer = deal.referrer;
+
+    deal.referrer = params.referrer;
+
+    if let Some(referrer) = params.referrer {
+        check_referrer(&referrer, &deal.client, &gig.owner, &gig.allowed_referrers)?;
+
+        // Keeping the current referrer doesn't count as a new referral.
+        if config.referrer_cooldown > 0 && old_referrer != Some(referrer) {
+            let referrer_activity = ctx
+                .accounts
+                .referrer_activity
+                .as_mut()
+                .ok_or(error!(CustomError::MissingReferrerActivity))?;
+
+            if referrer_activity.in_cooldown(config.referrer_cooldown, clock.unix_timestamp) {
+                deal.referrer = None;
+            } else {
+                referrer_activity.bump = *ctx.bumps.get("referrer_activity").unwrap();
+                referrer_activity.referrer = referrer;
+                referrer_activity.client = deal.client;
+                referrer_activity.last_referral_ts = clock.unix_timestamp;
+            }
+        }
+    }
+
+    emit!(ReferrerUpdated {
+        deal: deal.key(),
+        old_referrer,
+        new_referrer: deal.referrer,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
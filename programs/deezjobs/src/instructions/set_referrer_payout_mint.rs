@@ -0,0 +1,67 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{events::ReferrerPayoutMintSet, states::Config, CustomError};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SetReferrerPayoutMintParams {
+    /// Leave empty to pay referrers from the deal escrow in the deal mint again.
+    pub mint: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetReferrerPayoutMintParams)]
+pub struct SetReferrerPayoutMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = params.mint == Some(mint.key()),
+    )]
+    pub mint: Option<Account<'info, Mint>>,
+
+    /// Funded by the platform with plain token transfers, only the config PDA can pay out of it.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"referrer_rewards", params.mint.unwrap_or_default().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = config,
+    )]
+    pub referrer_rewards_vault: Option<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn set_referrer_payout_mint_handler(
+    ctx: Context<SetReferrerPayoutMint>,
+    params: SetReferrerPayoutMintParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+
+    if params.mint.is_some() && ctx.accounts.referrer_rewards_vault.is_none() {
+        return Err(error!(CustomError::MissingEscrowAccounts));
+    }
+
+    ctx.accounts.config.referrer_payout_mint = params.mint;
+
+    emit!(ReferrerPayoutMintSet {
+        mint: params.mint,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{events::YieldStrategySet, states::Config, CustomError};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SetYieldStrategyParams {
+    /// Leave empty to stop parking escrows. Deals keep withdrawing from the strategy they were
+    /// deposited in, see `Deal::strategy`.
+    pub strategy: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct SetYieldStrategy<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+}
+
+pub fn set_yield_strategy_handler(
+    ctx: Context<SetYieldStrategy>,
+    params: SetYieldStrategyParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+
+    ctx.accounts.config.yield_strategy = params.strategy;
+
+    emit!(YieldStrategySet {
+        strategy: params.strategy,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,2 +0,0 @@
-// deal.state & 4 == 4
-// config.authority (community multisig) to resolve the dispute
@@ -0,0 +1,52 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::BountyDelivered,
+    states::{Bounty, DealStatus},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SubmitBountyParams {
+    pub delivery_hash: [u8; 32],
+}
+
+#[derive(Accounts)]
+#[instruction(params: SubmitBountyParams)]
+pub struct SubmitBounty<'info> {
+    /// Work can be resubmitted while delivered, replacing the previous hash.
+    #[account(
+        mut,
+        constraint = bounty.state == DealStatus::Created as u8
+            || bounty.state == DealStatus::Delivered as u8 @ CustomError::InvalidState,
+    )]
+    pub bounty: Box<Account<'info, Bounty>>,
+
+    #[account(
+        constraint = freelancer.key() == bounty.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+}
+
+/// Hands the work in for the creator to approve with `complete_bounty`. It has to be in before
+/// the deadline, the contributors can be refunded after it.
+pub fn submit_bounty_handler(ctx: Context<SubmitBounty>, params: SubmitBountyParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let bounty = &mut ctx.accounts.bounty;
+
+    if bounty.deadline <= clock.unix_timestamp {
+        return Err(error!(CustomError::DeadlineInPast));
+    }
+
+    bounty.delivery_hash = params.delivery_hash;
+    bounty.time_delivered = clock.unix_timestamp;
+    bounty.state = DealStatus::Delivered as u8;
+
+    emit!(BountyDelivered {
+        bounty: bounty.key(),
+        delivery_hash: params.delivery_hash,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
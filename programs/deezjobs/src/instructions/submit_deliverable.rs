@@ -0,0 +1,65 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::DeliverableSubmitted,
+    states::{Config, Deal, DealStatus},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct SubmitDeliverableParams {
+    pub delivery_hash: [u8; 32],
+}
+
+#[derive(Accounts)]
+#[instruction(params: SubmitDeliverableParams)]
+pub struct SubmitDeliverable<'info> {
+    /// Work can be resubmitted while delivered, replacing the previous hash.
+    #[account(
+        mut,
+        constraint = matches!(deal.status()?, DealStatus::Accepted | DealStatus::Delivered),
+    )]
+    pub deal: Account<'info, Deal>,
+
+    #[account(
+        constraint = freelancer.key() == deal.freelancer.key() @ CustomError::Unauthorized,
+    )]
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn submit_deliverable_handler(
+    ctx: Context<SubmitDeliverable>,
+    params: SubmitDeliverableParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let deal = &mut ctx.accounts.deal;
+    let review_window = ctx.accounts.config.review_window;
+
+    deal.delivery_hash = params.delivery_hash;
+    deal.time_delivered = clock.unix_timestamp;
+    deal.state = DealStatus::Delivered as u8;
+
+    // Every submission restarts the review window.
+    deal.review_deadline = if review_window > 0 {
+        clock
+            .unix_timestamp
+            .checked_add(review_window)
+            .ok_or(error!(CustomError::MathOverflow))?
+    } else {
+        0
+    };
+
+    emit!(DeliverableSubmitted {
+        deal: deal.key(),
+        delivery_hash: params.delivery_hash,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use anchor_lang::{prelude::*, solana_program::clock, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    events::TipSent,
+    states::{Deal, DealStatus, Gig, Reputation},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct TipFreelancerParams {
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: TipFreelancerParams)]
+pub struct TipFreelancer<'info> {
+    #[account(
+        constraint = deal.state == DealStatus::Completed as u8,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        constraint = gig.key() == deal.gig.key(),
+    )]
+    pub gig: Box<Account<'info, Gig>>,
+
+    #[account(
+        mut,
+        constraint = client.key() == deal.client.key() @ CustomError::Unauthorized,
+    )]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = freelancer.key() == deal.freelancer.key(),
+    )]
+    /// CHECK: should be the freelancer of the deal
+    pub freelancer: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = gig.mint.is_some() @ CustomError::GigHasNoMint,
+        constraint = gig.mint == Some(mint.key()),
+    )]
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == client.key(),
+        constraint = gig.mint == Some(owner_wallet.mint),
+    )]
+    pub owner_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = client,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer,
+    )]
+    pub freelancer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", deal.freelancer.as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Tips go straight from the client to the freelancer, the escrow is already settled so no fee is taken.
+pub fn tip_freelancer_handler(
+    ctx: Context<TipFreelancer>,
+    params: TipFreelancerParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+
+    match ctx.accounts.gig.mint {
+        Some(_) => {
+            let (owner_wallet, freelancer_token_account, token_program) = match (
+                &ctx.accounts.owner_wallet,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.token_program,
+            ) {
+                (Some(owner_wallet), Some(freelancer_token_account), Some(token_program)) => {
+                    (owner_wallet, freelancer_token_account, token_program)
+                }
+                _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+            };
+
+            let transfer_ix = Transfer {
+                from: owner_wallet.to_account_info(),
+                to: freelancer_token_account.to_account_info(),
+                authority: ctx.accounts.client.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(token_program.to_account_info(), transfer_ix);
+
+            anchor_spl::token::transfer(cpi_ctx, params.amount)?;
+        }
+        None => {
+            let transfer_ix = system_program::Transfer {
+                from: ctx.accounts.client.to_account_info(),
+                to: ctx.accounts.freelancer.to_account_info(),
+            };
+
+            let cpi_ctx =
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+
+            system_program::transfer(cpi_ctx, params.amount)?;
+        }
+    }
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.tips_received = reputation
+        .tips_received
+        .checked_add(params.amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    emit!(TipSent {
+        deal: ctx.accounts.deal.key(),
+        amount: params.amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
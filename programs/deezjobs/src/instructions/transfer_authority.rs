@@ -0,0 +1,44 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{events::AuthorityTransferInitiated, states::Config, CustomError};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct TransferAuthorityParams {
+    pub new_authority: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(params: TransferAuthorityParams)]
+pub struct TransferAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+}
+
+/// Only proposes the new authority, it takes over once it signs `accept_authority`.
+/// Proposing again replaces the pending authority.
+pub fn transfer_authority_handler(
+    ctx: Context<TransferAuthority>,
+    params: TransferAuthorityParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &mut ctx.accounts.config;
+
+    config.pending_authority = Some(params.new_authority);
+
+    emit!(AuthorityTransferInitiated {
+        authority: config.authority,
+        pending_authority: params.new_authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
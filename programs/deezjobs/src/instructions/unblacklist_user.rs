@@ -0,0 +1,43 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::BlacklistUpdated,
+    states::{Blacklist, Config},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct UnblacklistUser<'info> {
+    /// Closing the PDA is what lifts the blacklist.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"blacklist", blacklist.user.as_ref()],
+        bump = blacklist.bump,
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+}
+
+pub fn unblacklist_user_handler(ctx: Context<UnblacklistUser>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+
+    emit!(BlacklistUpdated {
+        user: ctx.accounts.blacklist.user,
+        blacklisted: false,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::{
+    states::{Config, MAX_ALLOWED_MINTS},
+    CustomError,
+};
+use anchor_lang::prelude::*;
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct UpdateAllowedMintsParams {
+    pub allowed_mints: Vec<Pubkey>,
+    /// Pyth price feed of each mint, `Pubkey::default()` for none.
+    pub price_feeds: Vec<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: UpdateAllowedMintsParams)]
+pub struct UpdateAllowedMints<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key(),
+    )]
+    pub authority: Signer<'info>,
+}
+
+pub fn update_allowed_mints_handler(
+    ctx: Context<UpdateAllowedMints>,
+    params: UpdateAllowedMintsParams,
+) -> Result<()> {
+    if params.allowed_mints.len() > MAX_ALLOWED_MINTS {
+        return Err(error!(CustomError::TooManyAllowedMints));
+    }
+
+    if params.price_feeds.len() != params.allowed_mints.len() {
+        return Err(error!(CustomError::InvalidPriceFeed));
+    }
+
+    let config = &mut ctx.accounts.config;
+
+    // Deals in the fee mint are the only ones charged `client_fee_min`, it can't be delisted.
+    if !params.allowed_mints.contains(&config.fee_mint) {
+        return Err(error!(CustomError::FeeMintMismatch));
+    }
+
+    config.allowed_mints = params.allowed_mints;
+    config.price_feeds = params.price_feeds;
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::ConfigUpdated,
+    states::{Config, FeeTier, MAX_FEE_TIERS},
+    CustomError,
+};
+
+/// Only the fields that are set are updated.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct UpdateConfigParams {
+    pub client_fee_percentage: Option<u16>,
+    pub freelancer_fee_percentage: Option<u16>,
+    pub client_fee_min: Option<u64>,
+    pub arbiter: Option<Pubkey>,
+    /// Zero to lift the cap.
+    pub max_deal_offer: Option<u64>,
+    /// Replaces all the fee tiers, pass an empty list to remove them.
+    pub fee_tiers: Option<Vec<FeeTier>>,
+    pub refund_grace_period: Option<i64>,
+    pub round_fees_up: Option<bool>,
+    /// Zero to credit referrers on every deal.
+    pub referrer_cooldown: Option<i64>,
+    pub defer_freelancer_payouts: Option<bool>,
+    /// Zero to allow any deadline.
+    pub max_deadline_horizon: Option<i64>,
+    pub freelancer_cancel_penalty_bps: Option<u16>,
+    /// Zero to never require approval.
+    pub require_approval_above: Option<u64>,
+    /// Zero to pay out completed deals right away.
+    pub settlement_delay: Option<i64>,
+    /// Zero to disable the cap.
+    pub max_tvl: Option<u64>,
+    /// Zero to charge the cancel fee right after acceptance.
+    pub client_cancel_window: Option<i64>,
+    /// Zero to leave disputes to the arbiter alone.
+    pub dispute_timeout: Option<i64>,
+    /// Only applies to deals created afterwards, see `Deal.fee_escrow`.
+    pub split_escrow: Option<bool>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: UpdateConfigParams)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+}
+
+pub fn update_config_handler(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &mut ctx.accounts.config;
+
+    if let Some(client_fee_percentage) = params.client_fee_percentage {
+        config.client_fee_percentage = client_fee_percentage;
+    }
+
+    if let Some(freelancer_fee_percentage) = params.freelancer_fee_percentage {
+        config.freelancer_fee_percentage = freelancer_fee_percentage;
+    }
+
+    if let Some(fee_tiers) = params.fee_tiers {
+        if fee_tiers.len() > MAX_FEE_TIERS {
+            return Err(error!(CustomError::TooManyFeeTiers));
+        }
+
+        config.fee_tiers = fee_tiers;
+    }
+
+    config.check_fees()?;
+
+    if let Some(client_fee_min) = params.client_fee_min {
+        config.client_fee_min = client_fee_min;
+    }
+
+    if let Some(arbiter) = params.arbiter {
+        config.arbiter = arbiter;
+    }
+
+    if let Some(max_deal_offer) = params.max_deal_offer {
+        config.max_deal_offer = max_deal_offer;
+    }
+
+    if let Some(refund_grace_period) = params.refund_grace_period {
+        if refund_grace_period < 0 {
+            return Err(error!(CustomError::InvalidGracePeriod));
+        }
+
+        config.refund_grace_period = refund_grace_period;
+    }
+
+    if let Some(round_fees_up) = params.round_fees_up {
+        config.round_fees_up = round_fees_up;
+    }
+
+    if let Some(referrer_cooldown) = params.referrer_cooldown {
+        if referrer_cooldown < 0 {
+            return Err(error!(CustomError::InvalidReferrerCooldown));
+        }
+
+        config.referrer_cooldown = referrer_cooldown;
+    }
+
+    if let Some(defer_freelancer_payouts) = params.defer_freelancer_payouts {
+        config.defer_freelancer_payouts = defer_freelancer_payouts;
+    }
+
+    if let Some(max_deadline_horizon) = params.max_deadline_horizon {
+        if max_deadline_horizon < 0 {
+            return Err(error!(CustomError::InvalidDeadlineHorizon));
+        }
+
+        config.max_deadline_horizon = max_deadline_horizon;
+    }
+
+    if let Some(freelancer_cancel_penalty_bps) = params.freelancer_cancel_penalty_bps {
+        if freelancer_cancel_penalty_bps > 100_00 {
+            return Err(error!(CustomError::InvalidCancelPenalty));
+        }
+
+        config.freelancer_cancel_penalty_bps = freelancer_cancel_penalty_bps;
+    }
+
+    if let Some(require_approval_above) = params.require_approval_above {
+        config.require_approval_above = require_approval_above;
+    }
+
+    if let Some(settlement_delay) = params.settlement_delay {
+        if settlement_delay < 0 {
+            return Err(error!(CustomError::InvalidSettlementDelay));
+        }
+
+        config.settlement_delay = settlement_delay;
+    }
+
+    if let Some(max_tvl) = params.max_tvl {
+        config.max_tvl = max_tvl;
+    }
+
+    if let Some(client_cancel_window) = params.client_cancel_window {
+        if client_cancel_window < 0 {
+            return Err(error!(CustomError::InvalidCancelWindow));
+        }
+
+        config.client_cancel_window = client_cancel_window;
+    }
+
+    if let Some(dispute_timeout) = params.dispute_timeout {
+        if dispute_timeout < 0 {
+            return Err(error!(CustomError::InvalidDisputeTimeout));
+        }
+
+        config.dispute_timeout = dispute_timeout;
+    }
+
+    if let Some(split_escrow) = params.split_escrow {
+        config.split_escrow = split_escrow;
+    }
+
+    emit!(ConfigUpdated {
+        client_fee_percentage: config.client_fee_percentage,
+        freelancer_fee_percentage: config.freelancer_fee_percentage,
+        client_fee_min: config.client_fee_min,
+        arbiter: config.arbiter,
+        max_deal_offer: config.max_deal_offer,
+        refund_grace_period: config.refund_grace_period,
+        round_fees_up: config.round_fees_up,
+        referrer_cooldown: config.referrer_cooldown,
+        defer_freelancer_payouts: config.defer_freelancer_payouts,
+        max_deadline_horizon: config.max_deadline_horizon,
+        freelancer_cancel_penalty_bps: config.freelancer_cancel_penalty_bps,
+        require_approval_above: config.require_approval_above,
+        settlement_delay: config.settlement_delay,
+        max_tvl: config.max_tvl,
+        client_cancel_window: config.client_cancel_window,
+        dispute_timeout: config.dispute_timeout,
+        split_escrow: config.split_escrow,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
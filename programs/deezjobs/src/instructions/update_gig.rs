@@ -0,0 +1,87 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use crate::{
+    events::GigUpdated,
+    states::{Config, Gig, GigStatus, MAX_ALLOWED_REFERRERS},
+    CustomError,
+};
+
+/// Only the fields that are set are updated. Deals already created keep their offer and mint.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct UpdateGigParams {
+    pub asking: Option<u64>,
+    /// Paused gigs don't accept new deals.
+    pub paused: Option<bool>,
+    /// Replaces the referrer allowlist, pass an empty list to let anyone refer the gig.
+    pub allowed_referrers: Option<Vec<Pubkey>>,
+    /// Switches new deals to another of `config.allowed_mints`, deals already created are settled
+    /// in the mint they were escrowed in, see `Deal.mint`.
+    pub mint: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: UpdateGigParams)]
+pub struct UpdateGig<'info> {
+    #[account(
+        mut,
+        constraint = gig.state != GigStatus::Closed as u8 @ CustomError::InvalidState,
+    )]
+    pub gig: Account<'info, Gig>,
+
+    #[account(
+        constraint = owner.key() == gig.owner.key() @ CustomError::Unauthorized,
+    )]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn update_gig_handler(ctx: Context<UpdateGig>, params: UpdateGigParams) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let gig = &mut ctx.accounts.gig;
+
+    if let Some(asking) = params.asking {
+        if asking == 0 || matches!(gig.max_asking, Some(max_asking) if max_asking < asking) {
+            return Err(error!(CustomError::InvalidAsking));
+        }
+
+        gig.asking = asking;
+    }
+
+    if let Some(paused) = params.paused {
+        gig.state = if paused {
+            GigStatus::Paused as u8
+        } else {
+            GigStatus::Open as u8
+        };
+    }
+
+    if let Some(allowed_referrers) = params.allowed_referrers {
+        if allowed_referrers.len() > MAX_ALLOWED_REFERRERS {
+            return Err(error!(CustomError::TooManyReferrers));
+        }
+
+        gig.allowed_referrers = allowed_referrers;
+    }
+
+    if let Some(mint) = params.mint {
+        if !ctx.accounts.config.allowed_mints.contains(&mint) {
+            return Err(error!(CustomError::MintNotAllowed));
+        }
+
+        gig.mint = Some(mint);
+    }
+
+    emit!(GigUpdated {
+        gig: gig.key(),
+        asking: gig.asking,
+        state: gig.state,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
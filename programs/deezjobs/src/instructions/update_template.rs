@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use super::check_gig_terms;
+use crate::{
+    states::{Config, GigTemplate},
+    CustomError,
+};
+
+/// Replaces every field of the template, which is created on first use.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct UpdateTemplateParams {
+    pub template_id: u64,
+    pub category: u8,
+    pub asking: u64,
+    pub min_completion_time: i64,
+    /// Percentage of the offer refunded to the client for late completion, 2 decimal places.
+    pub late_penalty_bps: u16,
+    /// Percentage of the offer the client pays to cancel an accepted deal, 2 decimal places.
+    pub cancel_fee_bps: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(params: UpdateTemplateParams)]
+pub struct UpdateTemplate<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [
+            b"gig_template",
+            owner.key().as_ref(),
+            &params.template_id.to_le_bytes(),
+        ],
+        bump,
+        space = GigTemplate::len(),
+    )]
+    pub template: Account<'info, GigTemplate>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Sets the mint of the gigs, native SOL without it.
+    #[account(
+        constraint = config.allowed_mints.contains(&mint.key()) @ CustomError::MintNotAllowed,
+    )]
+    pub mint: Option<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_template_handler(
+    ctx: Context<UpdateTemplate>,
+    params: UpdateTemplateParams,
+) -> Result<()> {
+    check_gig_terms(
+        params.asking,
+        None,
+        params.min_completion_time,
+        params.late_penalty_bps,
+        params.cancel_fee_bps,
+    )?;
+
+    let template = &mut ctx.accounts.template;
+    template.bump = *ctx.bumps.get("template").unwrap();
+    template.owner = ctx.accounts.owner.key();
+    template.template_id = params.template_id;
+    template.mint = ctx.accounts.mint.as_ref().map(|mint| mint.key());
+    template.category = params.category;
+    template.asking = params.asking;
+    template.min_completion_time = params.min_completion_time;
+    template.late_penalty_bps = params.late_penalty_bps;
+    template.cancel_fee_bps = params.cancel_fee_bps;
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    events::EarningsWithdrawn,
+    states::FreelancerVault,
+    utils::{lamports_above_rent, transfer_lamports},
+    CustomError,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawEarnings<'info> {
+    #[account(
+        mut,
+        seeds = [b"freelancer_vault", freelancer.key().as_ref()],
+        bump = freelancer_vault.bump,
+    )]
+    pub freelancer_vault: Box<Account<'info, FreelancerVault>>,
+
+    /// Receives native SOL withdrawals.
+    #[account(mut)]
+    pub freelancer: Signer<'info>,
+
+    /// Leave empty to withdraw the native SOL held by the vault account.
+    pub mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = freelancer_vault,
+    )]
+    pub vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Receives SPL withdrawals, any token account of the mint chosen by the freelancer.
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub destination_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Withdraws everything the vault holds in the mint, or in native SOL without one.
+pub fn withdraw_earnings_handler(ctx: Context<WithdrawEarnings>) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let vault = &ctx.accounts.freelancer_vault;
+
+    let amount = match &ctx.accounts.mint {
+        Some(_) => {
+            let (vault_token_account, destination_token_account, token_program) = match (
+                &ctx.accounts.vault_token_account,
+                &ctx.accounts.destination_token_account,
+                &ctx.accounts.token_program,
+            ) {
+                (Some(vault_token_account), Some(destination), Some(token_program)) => {
+                    (vault_token_account, destination, token_program)
+                }
+                _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+            };
+
+            let amount = vault_token_account.amount;
+
+            let bump = [vault.bump];
+            let vault_seeds = [
+                b"freelancer_vault".as_ref(),
+                vault.freelancer.as_ref(),
+                bump.as_ref(),
+            ];
+            let vault_sig = [vault_seeds.as_slice()];
+
+            let transfer_ix = Transfer {
+                from: vault_token_account.to_account_info(),
+                to: destination_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                transfer_ix,
+                &vault_sig,
+            );
+
+            anchor_spl::token::transfer(cpi_ctx, amount)?;
+
+            amount
+        }
+        None => {
+            // The vault account has to stay rent exempt.
+            let amount = lamports_above_rent(&vault.to_account_info())?;
+
+            transfer_lamports(&vault.to_account_info(), &ctx.accounts.freelancer, amount)?;
+
+            amount
+        }
+    };
+
+    if amount == 0 {
+        return Err(error!(CustomError::NothingToWithdraw));
+    }
+
+    let vault = &mut ctx.accounts.freelancer_vault;
+    vault.total_withdrawn = vault
+        .total_withdrawn
+        .checked_add(amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    emit!(EarningsWithdrawn {
+        freelancer: vault.freelancer,
+        mint: ctx.accounts.mint.as_ref().map(|mint| mint.key()),
+        amount,
+        total_withdrawn: vault.total_withdrawn,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    events::EscrowWithdrawnFromStrategy,
+    states::{Config, Deal},
+    utils::{invoke_strategy, Escrow},
+    CustomError,
+};
+
+/// The strategy's own accounts follow as remaining accounts, passed on as they are.
+#[derive(Accounts)]
+pub struct WithdrawEscrowFromStrategy<'info> {
+    #[account(
+        mut,
+        constraint = deal.strategy_deposited > 0 @ CustomError::NothingToWithdraw,
+    )]
+    pub deal: Box<Account<'info, Deal>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = deal,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Holds the client fee of SPL deals with a split escrow, see `Deal.fee_escrow`.
+    pub fee_escrow: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = deal.mint == Some(mint.key()) @ CustomError::MintMismatch,
+    )]
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The authority, or either party so their funds never depend on the authority.
+    #[account(
+        constraint = signer.key() == config.authority
+            || signer.key() == deal.client
+            || signer.key() == deal.freelancer @ CustomError::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+
+    /// The strategy the deal deposited in, even if the config has moved on since.
+    #[account(
+        executable,
+        constraint = deal.strategy == Some(strategy.key()) @ CustomError::InvalidStrategy,
+    )]
+    /// CHECK: the strategy recorded by the deal
+    pub strategy: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury.key() @ CustomError::InvalidTreasury,
+    )]
+    /// CHECK: constraint to the config's treasury
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Receives the yield.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Takes the escrow back out of the yield strategy. The principal returns to the escrow, which
+/// must then hold exactly what the deal expects, and the yield goes to the treasury.
+pub fn withdraw_escrow_from_strategy_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawEscrowFromStrategy<'info>>,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let escrow_amount = ctx.accounts.escrow.amount;
+    let principal = ctx.accounts.deal.strategy_deposited;
+
+    invoke_strategy(
+        &ctx.accounts.strategy,
+        &ctx.accounts.deal,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+        "withdraw",
+        &[],
+    )?;
+
+    ctx.accounts.escrow.reload()?;
+
+    let recovered_amount = ctx
+        .accounts
+        .escrow
+        .amount
+        .checked_sub(escrow_amount)
+        .ok_or(error!(CustomError::StrategyPrincipalShort))?;
+
+    let yield_amount = recovered_amount
+        .checked_sub(principal)
+        .ok_or(error!(CustomError::StrategyPrincipalShort))?;
+
+    let deal = &mut ctx.accounts.deal;
+    deal.strategy = None;
+    deal.strategy_deposited = 0;
+
+    if yield_amount > 0 {
+        Escrow::new(
+            &ctx.accounts.deal,
+            false,
+            Some(&ctx.accounts.escrow),
+            ctx.accounts.fee_escrow.as_deref(),
+            Some(&ctx.accounts.token_program),
+        )?
+        .release(
+            Some(&ctx.accounts.treasury_token_account),
+            &ctx.accounts.treasury,
+            yield_amount,
+        )?;
+
+        ctx.accounts.escrow.reload()?;
+    }
+
+    // The whole principal is back, settlement can go on as if it never left.
+    Escrow::new(
+        &ctx.accounts.deal,
+        false,
+        Some(&ctx.accounts.escrow),
+        ctx.accounts.fee_escrow.as_deref(),
+        Some(&ctx.accounts.token_program),
+    )?
+    .checked_amount()?;
+
+    emit!(EscrowWithdrawnFromStrategy {
+        deal: ctx.accounts.deal.key(),
+        strategy: ctx.accounts.strategy.key(),
+        principal,
+        yield_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
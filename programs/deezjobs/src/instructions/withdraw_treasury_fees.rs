@@ -0,0 +1,121 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    events::TreasuryWithdrawal,
+    states::Config,
+    utils::{lamports_above_rent, transfer_lamports},
+    CustomError,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct WithdrawTreasuryFeesParams {
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: WithdrawTreasuryFeesParams)]
+pub struct WithdrawTreasuryFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        constraint = authority.key() == config.authority.key() @ CustomError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    /// Leave empty to withdraw the native SOL fees held by the config account.
+    pub mint: Option<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config,
+    )]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Receives native SOL withdrawals.
+    #[account(mut)]
+    /// CHECK: any wallet chosen by the authority
+    pub destination: UncheckedAccount<'info>,
+
+    /// Receives SPL withdrawals, must be of the same mint as the treasury token account.
+    #[account(mut)]
+    pub destination_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn withdraw_treasury_fees_handler(
+    ctx: Context<WithdrawTreasuryFees>,
+    params: WithdrawTreasuryFeesParams,
+) -> Result<()> {
+    let clock = clock::Clock::get()?;
+    let config = &ctx.accounts.config;
+
+    let destination = match &ctx.accounts.mint {
+        Some(_) => {
+            let (treasury_token_account, destination_token_account, token_program) = match (
+                &ctx.accounts.treasury_token_account,
+                &ctx.accounts.destination_token_account,
+                &ctx.accounts.token_program,
+            ) {
+                (Some(treasury), Some(destination), Some(token_program)) => {
+                    (treasury, destination, token_program)
+                }
+                _ => return Err(error!(CustomError::MissingEscrowAccounts)),
+            };
+
+            if params.amount > treasury_token_account.amount {
+                return Err(error!(CustomError::InsufficientTreasuryBalance));
+            }
+
+            let bump = [config.bump];
+            let config_seeds = [b"config".as_ref(), bump.as_ref()];
+            let config_sig = [config_seeds.as_slice()];
+
+            let transfer_ix = Transfer {
+                from: treasury_token_account.to_account_info(),
+                to: destination_token_account.to_account_info(),
+                authority: config.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                transfer_ix,
+                &config_sig,
+            );
+
+            anchor_spl::token::transfer(cpi_ctx, params.amount)?;
+
+            destination_token_account.key()
+        }
+        None => {
+            // The config account has to stay rent exempt.
+            if params.amount > lamports_above_rent(&config.to_account_info())? {
+                return Err(error!(CustomError::InsufficientTreasuryBalance));
+            }
+
+            transfer_lamports(
+                &config.to_account_info(),
+                &ctx.accounts.destination,
+                params.amount,
+            )?;
+
+            ctx.accounts.destination.key()
+        }
+    };
+
+    emit!(TreasuryWithdrawal {
+        mint: ctx.accounts.mint.as_ref().map(|mint| mint.key()),
+        destination,
+        amount: params.amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
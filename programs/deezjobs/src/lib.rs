@@ -1,7 +1,12 @@
+// Anchor's `Result` carries a large error type, and fee percentages are written as `100_00` for 100.00%.
+#![allow(clippy::result_large_err, clippy::inconsistent_digit_grouping)]
+
 use anchor_lang::prelude::*;
 
+pub mod events;
 pub mod instructions;
 pub mod states;
+pub mod utils;
 
 pub use instructions::*;
 
@@ -19,11 +24,11 @@ pub mod deezjobs {
         create_gig_handler(ctx, params)
     }
 
-    pub fn close_gig(ctx: Context<CloseGig>) -> Result<()> {
-        close_gig_handler(ctx)
+    pub fn close_gig(ctx: Context<CloseGig>, params: CloseGigParams) -> Result<()> {
+        close_gig_handler(ctx, params)
     }
 
-    pub fn create_deal(ctx: Context<CreateDeal>, params: CreateDealParams) -> Result<()> {
+    pub fn create_deal(ctx: Context<CreateDeal>, params: CreateDealParams) -> Result<Pubkey> {
         create_deal_handler(ctx, params)
     }
 
@@ -31,13 +36,313 @@ pub mod deezjobs {
         close_deal_handler(ctx)
     }
 
-    pub fn accept_deal(ctx: Context<AcceptDeal>) -> Result<()> {
-        accept_deal_handler(ctx)
+    pub fn accept_deal(ctx: Context<AcceptDeal>, params: AcceptDealParams) -> Result<()> {
+        accept_deal_handler(ctx, params)
     }
 
     pub fn complete_deal(ctx: Context<CompleteDeal>) -> Result<()> {
         complete_deal_handler(ctx)
     }
+
+    pub fn reject_deal(ctx: Context<RejectDeal>) -> Result<()> {
+        reject_deal_handler(ctx)
+    }
+
+    pub fn refund_expired_deal(ctx: Context<RefundExpiredDeal>) -> Result<()> {
+        refund_expired_deal_handler(ctx)
+    }
+
+    pub fn dispute_deal(ctx: Context<DisputeDeal>) -> Result<()> {
+        dispute_deal_handler(ctx)
+    }
+
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        params: ResolveDisputeParams,
+    ) -> Result<()> {
+        resolve_dispute_handler(ctx, params)
+    }
+
+    pub fn extend_deadline(
+        ctx: Context<ExtendDeadline>,
+        params: ExtendDeadlineParams,
+    ) -> Result<()> {
+        extend_deadline_handler(ctx, params)
+    }
+
+    pub fn release_milestone(
+        ctx: Context<ReleaseMilestone>,
+        params: ReleaseMilestoneParams,
+    ) -> Result<()> {
+        release_milestone_handler(ctx, params)
+    }
+
+    pub fn update_allowed_mints(
+        ctx: Context<UpdateAllowedMints>,
+        params: UpdateAllowedMintsParams,
+    ) -> Result<()> {
+        update_allowed_mints_handler(ctx, params)
+    }
+
+    pub fn withdraw_treasury_fees(
+        ctx: Context<WithdrawTreasuryFees>,
+        params: WithdrawTreasuryFeesParams,
+    ) -> Result<()> {
+        withdraw_treasury_fees_handler(ctx, params)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+        update_config_handler(ctx, params)
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, params: SetPausedParams) -> Result<()> {
+        set_paused_handler(ctx, params)
+    }
+
+    pub fn cancel_deal(ctx: Context<CancelDeal>) -> Result<()> {
+        cancel_deal_handler(ctx)
+    }
+
+    pub fn submit_deliverable(
+        ctx: Context<SubmitDeliverable>,
+        params: SubmitDeliverableParams,
+    ) -> Result<()> {
+        submit_deliverable_handler(ctx, params)
+    }
+
+    pub fn request_revision(ctx: Context<RequestRevision>) -> Result<()> {
+        request_revision_handler(ctx)
+    }
+
+    pub fn leave_review(ctx: Context<LeaveReview>, params: LeaveReviewParams) -> Result<()> {
+        leave_review_handler(ctx, params)
+    }
+
+    pub fn update_gig(ctx: Context<UpdateGig>, params: UpdateGigParams) -> Result<()> {
+        update_gig_handler(ctx, params)
+    }
+
+    pub fn batch_refund_expired<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchRefundExpired<'info>>,
+    ) -> Result<u32> {
+        batch_refund_expired_handler(ctx)
+    }
+
+    pub fn counter_offer(ctx: Context<CounterOffer>, params: CounterOfferParams) -> Result<()> {
+        counter_offer_handler(ctx, params)
+    }
+
+    pub fn accept_counter(ctx: Context<AcceptCounter>, params: AcceptCounterParams) -> Result<()> {
+        accept_counter_handler(ctx, params)
+    }
+
+    pub fn tip_freelancer(ctx: Context<TipFreelancer>, params: TipFreelancerParams) -> Result<()> {
+        tip_freelancer_handler(ctx, params)
+    }
+
+    pub fn transfer_authority(
+        ctx: Context<TransferAuthority>,
+        params: TransferAuthorityParams,
+    ) -> Result<()> {
+        transfer_authority_handler(ctx, params)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        accept_authority_handler(ctx)
+    }
+
+    pub fn blacklist_user(ctx: Context<BlacklistUser>, params: BlacklistUserParams) -> Result<()> {
+        blacklist_user_handler(ctx, params)
+    }
+
+    pub fn unblacklist_user(ctx: Context<UnblacklistUser>) -> Result<()> {
+        unblacklist_user_handler(ctx)
+    }
+
+    pub fn finalize_delivery(ctx: Context<CompleteDeal>) -> Result<()> {
+        finalize_delivery_handler(ctx)
+    }
+
+    pub fn set_referrer_payout_mint(
+        ctx: Context<SetReferrerPayoutMint>,
+        params: SetReferrerPayoutMintParams,
+    ) -> Result<()> {
+        set_referrer_payout_mint_handler(ctx, params)
+    }
+
+    pub fn increase_offer(ctx: Context<IncreaseOffer>, params: IncreaseOfferParams) -> Result<()> {
+        increase_offer_handler(ctx, params)
+    }
+
+    pub fn propose_deal(ctx: Context<ProposeDeal>, params: ProposeDealParams) -> Result<()> {
+        propose_deal_handler(ctx, params)
+    }
+
+    pub fn fund_proposal(ctx: Context<FundProposal>) -> Result<()> {
+        fund_proposal_handler(ctx)
+    }
+
+    pub fn resolve_timeout(ctx: Context<ResolveDispute>) -> Result<()> {
+        resolve_timeout_handler(ctx)
+    }
+
+    pub fn migrate_deal(ctx: Context<MigrateDeal>) -> Result<()> {
+        migrate_deal_handler(ctx)
+    }
+
+    pub fn quote_deal(ctx: Context<QuoteDeal>, params: QuoteDealParams) -> Result<DealQuote> {
+        quote_deal_handler(ctx, params)
+    }
+
+    pub fn quote_deals(
+        ctx: Context<QuoteDeal>,
+        params: QuoteDealsParams,
+    ) -> Result<Vec<DealQuote>> {
+        quote_deals_handler(ctx, params)
+    }
+
+    pub fn create_bounty(ctx: Context<CreateBounty>, params: CreateBountyParams) -> Result<()> {
+        create_bounty_handler(ctx, params)
+    }
+
+    pub fn contribute_bounty(
+        ctx: Context<ContributeBounty>,
+        params: ContributeBountyParams,
+    ) -> Result<()> {
+        contribute_bounty_handler(ctx, params)
+    }
+
+    pub fn submit_bounty(ctx: Context<SubmitBounty>, params: SubmitBountyParams) -> Result<()> {
+        submit_bounty_handler(ctx, params)
+    }
+
+    pub fn complete_bounty(ctx: Context<CompleteBounty>) -> Result<()> {
+        complete_bounty_handler(ctx)
+    }
+
+    pub fn refund_bounty<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundBounty<'info>>,
+    ) -> Result<()> {
+        refund_bounty_handler(ctx)
+    }
+
+    pub fn reassign_freelancer(ctx: Context<ReassignFreelancer>) -> Result<()> {
+        reassign_freelancer_handler(ctx)
+    }
+
+    pub fn place_bid(ctx: Context<PlaceBid>, params: PlaceBidParams) -> Result<()> {
+        place_bid_handler(ctx, params)
+    }
+
+    pub fn select_bid(ctx: Context<SelectBid>, params: SelectBidParams) -> Result<()> {
+        select_bid_handler(ctx, params)
+    }
+
+    pub fn reclaim_bid(ctx: Context<ReclaimBid>) -> Result<()> {
+        reclaim_bid_handler(ctx)
+    }
+
+    pub fn set_exemption(ctx: Context<SetExemption>, params: SetExemptionParams) -> Result<()> {
+        set_exemption_handler(ctx, params)
+    }
+
+    pub fn withdraw_earnings(ctx: Context<WithdrawEarnings>) -> Result<()> {
+        withdraw_earnings_handler(ctx)
+    }
+
+    pub fn create_gig_and_deal(
+        ctx: Context<CreateGigAndDeal>,
+        params: CreateGigAndDealParams,
+    ) -> Result<Pubkey> {
+        create_gig_and_deal_handler(ctx, params)
+    }
+
+    pub fn freelancer_cancel(ctx: Context<FreelancerCancel>) -> Result<()> {
+        freelancer_cancel_handler(ctx)
+    }
+
+    pub fn approve_deal(ctx: Context<ApproveDeal>) -> Result<()> {
+        approve_deal_handler(ctx)
+    }
+
+    pub fn fund_deal(ctx: Context<FundDeal>) -> Result<()> {
+        fund_deal_handler(ctx)
+    }
+
+    pub fn close_gig_account(ctx: Context<CloseGigAccount>) -> Result<()> {
+        close_gig_account_handler(ctx)
+    }
+
+    pub fn finalize_settlement(ctx: Context<CompleteDeal>) -> Result<()> {
+        finalize_settlement_handler(ctx)
+    }
+
+    pub fn update_template(
+        ctx: Context<UpdateTemplate>,
+        params: UpdateTemplateParams,
+    ) -> Result<()> {
+        update_template_handler(ctx, params)
+    }
+
+    pub fn create_gig_from_template(
+        ctx: Context<CreateGigFromTemplate>,
+        params: CreateGigFromTemplateParams,
+    ) -> Result<()> {
+        create_gig_from_template_handler(ctx, params)
+    }
+
+    pub fn mutual_settle(ctx: Context<MutualSettle>, params: MutualSettleParams) -> Result<()> {
+        mutual_settle_handler(ctx, params)
+    }
+
+    pub fn set_referrer(ctx: Context<SetReferrer>, params: SetReferrerParams) -> Result<()> {
+        set_referrer_handler(ctx, params)
+    }
+
+    pub fn set_yield_strategy(
+        ctx: Context<SetYieldStrategy>,
+        params: SetYieldStrategyParams,
+    ) -> Result<()> {
+        set_yield_strategy_handler(ctx, params)
+    }
+
+    pub fn deposit_escrow_to_strategy<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositEscrowToStrategy<'info>>,
+        params: DepositEscrowToStrategyParams,
+    ) -> Result<()> {
+        deposit_escrow_to_strategy_handler(ctx, params)
+    }
+
+    pub fn withdraw_escrow_from_strategy<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawEscrowFromStrategy<'info>>,
+    ) -> Result<()> {
+        withdraw_escrow_from_strategy_handler(ctx)
+    }
+
+    pub fn create_retainer(
+        ctx: Context<CreateRetainer>,
+        params: CreateRetainerParams,
+    ) -> Result<()> {
+        create_retainer_handler(ctx, params)
+    }
+
+    pub fn release_period(ctx: Context<ReleasePeriod>) -> Result<()> {
+        release_period_handler(ctx)
+    }
+
+    pub fn cancel_retainer(ctx: Context<CancelRetainer>) -> Result<()> {
+        cancel_retainer_handler(ctx)
+    }
+
+    pub fn set_attestor(ctx: Context<SetAttestor>, params: SetAttestorParams) -> Result<()> {
+        set_attestor_handler(ctx, params)
+    }
+
+    pub fn attested_complete<'info>(
+        ctx: Context<'_, '_, '_, 'info, AttestedComplete<'info>>,
+    ) -> Result<()> {
+        attested_complete_handler(ctx)
+    }
 }
 
 #[error_code]
@@ -47,4 +352,295 @@ pub enum CustomError {
 
     #[msg("Deadline is not long enough")]
     DeadlineTooShort,
+
+    #[msg("Escrow accounts for the gig's payment are missing")]
+    MissingEscrowAccounts,
+
+    #[msg("Token is not accepted as payment")]
+    MintNotAllowed,
+
+    #[msg("Too many tokens in the allowed list")]
+    TooManyAllowedMints,
+
+    #[msg("Fee mint must remain an allowed mint")]
+    FeeMintMismatch,
+
+    #[msg("Price feed of the gig's token is required")]
+    MissingPriceFeed,
+
+    #[msg("Price feed is invalid")]
+    InvalidPriceFeed,
+
+    #[msg("Price feed is stale")]
+    StalePriceFeed,
+
+    #[msg("Account is in an unknown state")]
+    InvalidState,
+
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+
+    #[msg("Deal deadline and grace period have not passed yet")]
+    DealNotExpired,
+
+    #[msg("Split must be between 0 and 10000")]
+    InvalidSplit,
+
+    #[msg("New deadline must be later than the current one")]
+    DeadlineNotExtended,
+
+    #[msg("Milestone amounts must add up to the offer")]
+    MilestoneSumMismatch,
+
+    #[msg("Too many milestones")]
+    TooManyMilestones,
+
+    #[msg("Milestone does not exist")]
+    InvalidMilestone,
+
+    #[msg("Milestone was already released")]
+    MilestoneAlreadyReleased,
+
+    #[msg("Deals with milestones are completed by releasing each milestone")]
+    DealHasMilestones,
+
+    #[msg("Client and freelancer can't be the referrer")]
+    InvalidReferrer,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("Offer must be greater than zero")]
+    InvalidOffer,
+
+    #[msg("Deadline is already in the past")]
+    DeadlineInPast,
+
+    #[msg("Not enough fees in the treasury")]
+    InsufficientTreasuryBalance,
+
+    #[msg("Client and freelancer fees together exceed the cap")]
+    FeeTooHigh,
+
+    #[msg("Program is paused")]
+    ProgramPaused,
+
+    #[msg("Deal has reached the maximum number of revisions")]
+    TooManyRevisions,
+
+    #[msg("Freelancer reputation account is missing")]
+    MissingReputation,
+
+    #[msg("Rating must be from 1 to 5 stars")]
+    InvalidRating,
+
+    #[msg("Asking price must be greater than zero and not above the maximum")]
+    InvalidAsking,
+
+    #[msg("Minimum completion time must be greater than zero")]
+    InvalidCompletionTime,
+
+    #[msg("Gig has reached its maximum number of concurrent deals")]
+    GigAtCapacity,
+
+    #[msg("Accounts of the batch don't match their deals")]
+    InvalidBatchAccounts,
+
+    #[msg("Counter offer must be higher than the current offer")]
+    InvalidCounterOffer,
+
+    #[msg("The client or the freelancer is blacklisted")]
+    UserBlacklisted,
+
+    #[msg("Offer is above the gig's maximum asking price")]
+    OfferTooHigh,
+
+    #[msg("Gig still has active deals")]
+    GigHasActiveDeals,
+
+    #[msg("Late penalty can't be more than 100%")]
+    InvalidLatePenalty,
+
+    #[msg("The client can still review the delivered work")]
+    ReviewWindowOpen,
+
+    #[msg("Referrer rewards vault can't cover the referral payout")]
+    InsufficientRewardsVault,
+
+    #[msg("Metadata URI is too long or not zero padded")]
+    InvalidMetadataUri,
+
+    #[msg("Escrow still holds funds")]
+    EscrowNotEmpty,
+
+    #[msg("Offer is above the maximum allowed per deal")]
+    OfferExceedsCap,
+
+    #[msg("Client fee is above the max set by the client")]
+    FeeExceedsMax,
+
+    #[msg("Escrow balance doesn't match the deal")]
+    EscrowBalanceMismatch,
+
+    #[msg("Too many fee tiers")]
+    TooManyFeeTiers,
+
+    #[msg("The arbiter can still resolve the dispute")]
+    DisputeTimeoutNotReached,
+
+    #[msg("Terms hash doesn't match the deal")]
+    TermsMismatch,
+
+    #[msg("Gig requires a stake, the freelancer has to accept the deal")]
+    StakeRequired,
+
+    #[msg("Refund grace period can't be negative")]
+    InvalidGracePeriod,
+
+    #[msg("Deal was delivered, it has to be finalized instead of refunded")]
+    DealDelivered,
+
+    #[msg("Gig is paid in native SOL, it has no mint")]
+    GigHasNoMint,
+
+    #[msg("Gig can't have more allowed referrers")]
+    TooManyReferrers,
+
+    #[msg("Referrer is not allowed by the gig")]
+    ReferrerNotAllowed,
+
+    #[msg("Cancel fee can't be more than 100%")]
+    InvalidCancelFee,
+
+    #[msg("Bounties can only be funded in native SOL")]
+    BountyNotNative,
+
+    #[msg("Bounty can't have more contributors")]
+    TooManyContributors,
+
+    #[msg("Gigs must be paid in the same mint")]
+    GigMintMismatch,
+
+    #[msg("Referrer cooldown can't be negative")]
+    InvalidReferrerCooldown,
+
+    #[msg("Referrer activity account is required while the referrer cooldown is set")]
+    MissingReferrerActivity,
+
+    #[msg("Mints with extensions like transfer fees aren't supported")]
+    UnsupportedMintExtension,
+
+    #[msg("Only native SOL gigs take bids")]
+    BidNotNative,
+
+    #[msg("Freelancer vault is required while payouts are deferred")]
+    MissingFreelancerVault,
+
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+
+    #[msg("Token accounts must be of the gig's mint")]
+    MintMismatch,
+
+    #[msg("Client hasn't completed enough deals for this gig")]
+    ClientReputationTooLow,
+
+    #[msg("Deadline is too far in the future")]
+    DeadlineTooFar,
+
+    #[msg("Deadline horizon can't be negative")]
+    InvalidDeadlineHorizon,
+
+    #[msg("Cancel penalty can't be more than 100%")]
+    InvalidCancelPenalty,
+
+    #[msg("Too many offers to quote at once")]
+    TooManyOffers,
+
+    #[msg("Config bump isn't the canonical bump")]
+    ConfigBumpMismatch,
+
+    #[msg("Freelancers can't make deals on their own gigs")]
+    SelfDeal,
+
+    #[msg("Only closed gigs without active deals can be closed")]
+    GigNotClosable,
+
+    #[msg("Settlement delay can't be negative")]
+    InvalidSettlementDelay,
+
+    #[msg("The settlement delay hasn't passed yet")]
+    SettlementDelayNotElapsed,
+
+    #[msg("Gig isn't the gig of the deal")]
+    DealGigMismatch,
+
+    #[msg("Deal would exceed the program's escrow cap")]
+    TvlCapExceeded,
+
+    #[msg("Gig isn't open for new deals")]
+    GigNotOpen,
+
+    #[msg("Token account isn't owned by the expected wallet")]
+    WalletOwnerMismatch,
+
+    #[msg("Wallet can't cover the offer and fees")]
+    InsufficientFunds,
+
+    #[msg("Treasury isn't the one set in the config")]
+    InvalidTreasury,
+
+    #[msg("Rent can only go back to the gig's payer")]
+    PayerMismatch,
+
+    #[msg("Part of the escrow is still in the yield strategy")]
+    EscrowInStrategy,
+
+    #[msg("Strategy isn't the one set in the config")]
+    InvalidStrategy,
+
+    #[msg("Deposit must be greater than zero and within the escrow")]
+    InvalidStrategyDeposit,
+
+    #[msg("Strategy didn't take the deposited amount")]
+    StrategyTransferMismatch,
+
+    #[msg("Strategy returned less than was deposited")]
+    StrategyPrincipalShort,
+
+    #[msg("Retainers can only be paid in native SOL")]
+    RetainerNotNative,
+
+    #[msg("Period length, amount and count must be greater than zero")]
+    InvalidRetainerSchedule,
+
+    #[msg("The next period isn't due yet")]
+    PeriodNotDue,
+
+    #[msg("A due period has to be released first")]
+    RetainerPeriodDue,
+
+    #[msg("Treasury token account isn't of the deal's mint")]
+    TreasuryMintMismatch,
+
+    #[msg("Client cancel window can't be negative")]
+    InvalidCancelWindow,
+
+    #[msg("No attestor is set in the config")]
+    AttestorNotSet,
+
+    #[msg("Previous instruction isn't an ed25519 signature by the attestor")]
+    InvalidAttestation,
+
+    #[msg("Attestation isn't for this deal and its delivery")]
+    AttestationMismatch,
+
+    #[msg("Dispute timeout can't be negative")]
+    InvalidDisputeTimeout,
+
+    #[msg("Offer is above the approval threshold, open it as a deal instead")]
+    OfferNeedsApproval,
+
+    #[msg("Fee escrow isn't the one the deal holds its client fee in")]
+    FeeEscrowMismatch,
 }
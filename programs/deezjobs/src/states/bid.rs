@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, I64_LEN, PUBKEY_LEN, U64_LEN, U8_LEN};
+
+/// A client's bid for a gig, the freelancer picks the one they want with `select_bid`.
+/// The lamports are escrowed by the bid account itself, so only native SOL gigs take bids.
+#[account]
+pub struct Bid {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// The Gig account. (32)
+    pub gig: Pubkey,
+
+    /// Client who placed the bid, the client of the deal if it's selected. (32)
+    pub bidder: Pubkey,
+
+    /// Offer of the deal if the bid is selected. (8)
+    pub offer: u64,
+
+    /// Client fee escrowed on top of the offer. (8)
+    pub client_fee: u64,
+
+    /// Deadline of the deal if the bid is selected, anyone can reclaim the bid after it.
+    /// Unix timestamp. (8)
+    pub deadline: i64,
+
+    /// Time this bid was placed. Unix timestamp. (8)
+    pub time_created: i64,
+}
+
+impl Bid {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // gig
+            + PUBKEY_LEN // bidder
+            + U64_LEN // offer
+            + U64_LEN // client_fee
+            + I64_LEN // deadline
+            + I64_LEN // time_created
+    }
+
+    /// Offer plus client fee, what the bid account should hold on top of its rent.
+    pub fn expected_escrow(&self) -> Option<u64> {
+        self.offer.checked_add(self.client_fee)
+    }
+}
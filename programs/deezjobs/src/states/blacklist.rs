@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, I64_LEN, PUBKEY_LEN, U8_LEN};
+
+/// Marks a user as blacklisted while it exists, closing it removes them from the blacklist.
+/// One PDA per user instead of a list in the Config so it isn't bounded by the account size.
+#[account]
+pub struct Blacklist {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// The blacklisted client or freelancer. (32)
+    pub user: Pubkey,
+
+    /// Time the user was blacklisted. Unix timestamp. (8)
+    pub time_added: i64,
+}
+
+impl Blacklist {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // user
+            + I64_LEN // time_added
+    }
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{
+    DISCRIMINATOR_LEN, HASH_LEN, I64_LEN, PUBKEY_LEN, U64_LEN, U8_LEN, VEC_PREFIX_LEN,
+};
+
+/// Maximum number of clients funding a Bounty.
+pub const MAX_BOUNTY_CONTRIBUTORS: usize = 8;
+
+/// What one client put into a Bounty.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Contribution {
+    pub contributor: Pubkey,
+
+    /// Added to the bounty offer. (8)
+    pub amount: u64,
+
+    /// Client fee paid on top of `amount`, refunded with it if the bounty expires. (8)
+    pub client_fee: u64,
+}
+
+impl Contribution {
+    pub fn len() -> usize {
+        PUBKEY_LEN // contributor
+            + U64_LEN // amount
+            + U64_LEN // client_fee
+    }
+}
+
+/// A deal funded by several clients, eg. an open-source bounty. The lamports are escrowed by the
+/// bounty account itself, so only native SOL gigs can have bounties.
+#[account]
+pub struct Bounty {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Client who created the bounty, the one approving the work. (32)
+    pub creator: Pubkey,
+
+    /// State of this Bounty, see `DealStatus`. Created until the freelancer submits the work,
+    /// then Delivered until it's completed or refunded. (1)
+    pub state: u8,
+
+    /// The owner of the Gig. (32)
+    pub freelancer: Pubkey,
+
+    /// The Gig account. (32)
+    pub gig: Pubkey,
+
+    /// Sum of all the contributions. (8)
+    pub offer: u64,
+
+    /// Contributors are refunded after this. Unix timestamp. (8)
+    pub deadline: i64,
+
+    /// Time this bounty was created. (8)
+    pub time_created: i64,
+
+    /// Time when the creator approved the work. Unix timestamp. (8)
+    pub time_completed: i64,
+
+    /// Hash of the work submitted by the freelancer, see `submit_bounty`. (32)
+    pub delivery_hash: [u8; 32],
+
+    /// Time of the last submission. Unix timestamp. (8)
+    pub time_delivered: i64,
+
+    /// Each client funding the bounty, the creator first. (4 + 48 * 8)
+    pub contributors: Vec<Contribution>,
+}
+
+impl Bounty {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // creator
+            + U8_LEN // state
+            + PUBKEY_LEN // freelancer
+            + PUBKEY_LEN // gig
+            + U64_LEN // offer
+            + I64_LEN // deadline
+            + I64_LEN // time_created
+            + I64_LEN // time_completed
+            + HASH_LEN // delivery_hash
+            + I64_LEN // time_delivered
+            + (VEC_PREFIX_LEN + Contribution::len() * MAX_BOUNTY_CONTRIBUTORS) // contributors
+    }
+
+    /// Offer plus client fees, what the bounty account should hold on top of its rent.
+    pub fn expected_escrow(&self) -> Option<u64> {
+        self.contributors
+            .iter()
+            .try_fold(0u64, |escrow, contribution| {
+                escrow
+                    .checked_add(contribution.amount)?
+                    .checked_add(contribution.client_fee)
+            })
+    }
+}
@@ -1,5 +1,37 @@
 use anchor_lang::prelude::*;
 
+use super::sizes::{
+    BOOL_LEN, DISCRIMINATOR_LEN, I64_LEN, OPTION_PUBKEY_LEN, PUBKEY_LEN, U16_LEN, U64_LEN, U8_LEN,
+    VEC_PREFIX_LEN,
+};
+use crate::CustomError;
+
+/// Maximum number of SPL tokens that can be accepted as gig payment.
+pub const MAX_ALLOWED_MINTS: usize = 16;
+
+/// Cap on the client and freelancer fee percentages combined, 20.00%.
+pub const MAX_TOTAL_FEE_PERCENTAGE: u16 = 20_00;
+
+/// Maximum number of `Config::fee_tiers`.
+pub const MAX_FEE_TIERS: usize = 8;
+
+/// Discounted client fee for deals with freelancers who completed enough deals.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeTier {
+    /// Applies once the freelancer's `Reputation::completed_deals` reaches this. (8)
+    pub min_completed_deals: u64,
+
+    /// Replaces `Config::client_fee_percentage`. Percentage with 2 decimal places (0 to 10000). (2)
+    pub client_fee_percentage: u16,
+}
+
+impl FeeTier {
+    pub fn len() -> usize {
+        U64_LEN // min_completed_deals
+            + U16_LEN // client_fee_percentage
+    }
+}
+
 /// Config account serves as the global settings of the protocol.
 #[account]
 pub struct Config {
@@ -9,24 +41,231 @@ pub struct Config {
     /// Super authority. (32)
     pub authority: Pubkey,
 
-    /// Account which will hold all collected fee. (32)
+    /// Resolves disputed deals, eg. the community multisig. (32)
+    pub arbiter: Pubkey,
+
+    /// Account which will hold all collected fee, the config PDA itself. Fees of SPL gigs go to its
+    /// associated token account for the mint, native SOL fees are held on top of its rent. (32)
     pub treasury: Pubkey,
 
     /// Fee to collect from the client for every accepted deal. Percentage with 2 decimal places (0 to 10000). (2)
     pub client_fee_percentage: u16,
 
-    /// If client fee is less than this value, this value will serve as the fee (in USDC). 6 decimal places. (8)
+    /// If client fee is less than this value, this value will serve as the fee. Denominated in `fee_mint`. (8)
     pub client_fee_min: u64,
 
+    /// Token which `client_fee_min` is denominated in, eg. USDC. (32)
+    pub fee_mint: Pubkey,
+
     /// Fee to collect from the freelancer for each completed deal. Percentage with 2 decimal places (0 to 10000). (2)
     pub freelancer_fee_percentage: u16,
 
-    /// Bounty of the referral when the deal is completed. Percentage with 2 decimal places (0 to 10000). (2)
+    /// Share of the client fee paid to the referrer when the deal is completed. Percentage with 2 decimal places (0 to 10000). (2)
     pub referral_fee_percentage: u16,
+
+    /// SPL tokens that gigs and deals can be denominated in. (4 + 32 * 16)
+    pub allowed_mints: Vec<Pubkey>,
+
+    /// Pyth price feed of each of the `allowed_mints`, in the same order. (4 + 32 * 16)
+    /// Set to `Pubkey::default()` if the mint has no feed.
+    pub price_feeds: Vec<Pubkey>,
+
+    /// Minimum client fee in USD for deals not paid in `fee_mint`. 6 decimal places. (8)
+    pub fee_min_usd: u64,
+
+    /// Price feeds older than this are rejected. In seconds. (8)
+    pub max_price_age: u64,
+
+    /// How long after the deadline an unfinished deal can be refunded to the client. In seconds. (8)
+    pub refund_grace_period: i64,
+
+    /// Blocks new deals while set, existing deals can still be settled. (1)
+    pub paused: bool,
+
+    /// How many times a client can request changes to delivered work. (1)
+    pub max_revisions: u8,
+
+    /// Authority proposed by `transfer_authority`, takes over once it signs `accept_authority`. (33)
+    pub pending_authority: Option<Pubkey>,
+
+    /// How long the client has to approve delivered work before anyone can finalize it. In seconds.
+    /// Zero disables auto-completion. (8)
+    pub review_window: i64,
+
+    /// Pays referrers in this token out of the referrer rewards vault instead of the deal escrow,
+    /// eg. USDC. If None, the referral fee is paid in the deal mint. (33)
+    pub referrer_payout_mint: Option<Pubkey>,
+
+    /// Deals offering more than this are rejected, zero for unlimited. (8)
+    /// Note: compared as is against the offer, regardless of the mint decimals.
+    pub max_deal_offer: u64,
+
+    /// Client fee discounts by freelancer reputation, the highest tier reached applies. (4 + 10 * 8)
+    pub fee_tiers: Vec<FeeTier>,
+
+    /// How long a deal can stay disputed before anyone can settle it with the default split.
+    /// In seconds, zero to leave disputes to the arbiter only. (8)
+    pub dispute_timeout: i64,
+
+    /// Share of the offer refunded to the client when a dispute times out.
+    /// Percentage with 2 decimal places (0 to 10000). (2)
+    pub dispute_default_client_bps: u16,
+
+    /// Rounds the client and freelancer fees up instead of down, never past the amount charged on.
+    /// Applies to the percentage fees only, the fee minimums are unaffected. (1)
+    pub round_fees_up: bool,
+
+    /// How long a referrer isn't credited again for deals of the same client, see `ReferrerActivity`.
+    /// In seconds, zero to disable. (8)
+    pub referrer_cooldown: i64,
+
+    /// Pays the freelancer's share of completed deals, milestone releases, mutual settlements and
+    /// dispute splits into their `FreelancerVault` instead of their wallet. (1)
+    pub defer_freelancer_payouts: bool,
+
+    /// How far past its creation the deadline of a deal can be, so escrows aren't locked for years.
+    /// In seconds, zero for no limit. (8)
+    pub max_deadline_horizon: i64,
+
+    /// Share of the stake forfeited to the client when the freelancer cancels an accepted deal,
+    /// see `freelancer_cancel`. Percentage with 2 decimal places (0 to 10000). (2)
+    pub freelancer_cancel_penalty_bps: u16,
+
+    /// Deals with a higher offer wait for the authority in `approve_deal` before they can be
+    /// funded, zero to never require it. (8)
+    pub require_approval_above: u64,
+
+    /// How long completed deals wait before they're paid out, so a dispute can still be raised
+    /// should the client's funds be reversed. In seconds, zero to pay out right away. (8)
+    pub settlement_delay: i64,
+
+    /// Ceiling on `Stats.active_escrow_total`, deals that would exceed it can't be funded. In
+    /// the smallest unit of the mints, zero to disable the cap. (8)
+    pub max_tvl: u64,
+
+    /// Lending program idle SPL escrows can be parked in by the authority, see
+    /// `deposit_escrow_to_strategy`. Yield goes to the treasury. (33)
+    pub yield_strategy: Option<Pubkey>,
+
+    /// How long after acceptance the client can still cancel a deal with a full refund, the
+    /// gig's cancel fee applies after that. In seconds, zero for no free window. (8)
+    pub client_cancel_window: i64,
+
+    /// Trusted oracle whose signature on a delivery completes the deal without the client, see
+    /// `attested_complete`. None to disable attested completion. (33)
+    pub attestor: Option<Pubkey>,
+
+    /// Escrows the client fee of new SPL deals in a token account of its own, apart from the
+    /// offer, see `Deal.fee_escrow`. Native SOL deals keep both in the deal account. (1)
+    pub split_escrow: bool,
 }
 
 impl Config {
     pub fn len() -> usize {
-        8 + 1 + 32 + 32 + 2 + 8 + 2 + 2
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // authority
+            + PUBKEY_LEN // arbiter
+            + PUBKEY_LEN // treasury
+            + U16_LEN // client_fee_percentage
+            + U64_LEN // client_fee_min
+            + PUBKEY_LEN // fee_mint
+            + U16_LEN // freelancer_fee_percentage
+            + U16_LEN // referral_fee_percentage
+            + (VEC_PREFIX_LEN + PUBKEY_LEN * MAX_ALLOWED_MINTS) // allowed_mints
+            + (VEC_PREFIX_LEN + PUBKEY_LEN * MAX_ALLOWED_MINTS) // price_feeds
+            + U64_LEN // fee_min_usd
+            + U64_LEN // max_price_age
+            + I64_LEN // refund_grace_period
+            + BOOL_LEN // paused
+            + U8_LEN // max_revisions
+            + OPTION_PUBKEY_LEN // pending_authority
+            + I64_LEN // review_window
+            + OPTION_PUBKEY_LEN // referrer_payout_mint
+            + U64_LEN // max_deal_offer
+            + (VEC_PREFIX_LEN + FeeTier::len() * MAX_FEE_TIERS) // fee_tiers
+            + I64_LEN // dispute_timeout
+            + U16_LEN // dispute_default_client_bps
+            + BOOL_LEN // round_fees_up
+            + I64_LEN // referrer_cooldown
+            + BOOL_LEN // defer_freelancer_payouts
+            + I64_LEN // max_deadline_horizon
+            + U16_LEN // freelancer_cancel_penalty_bps
+            + U64_LEN // require_approval_above
+            + I64_LEN // settlement_delay
+            + U64_LEN // max_tvl
+            + OPTION_PUBKEY_LEN // yield_strategy
+            + I64_LEN // client_cancel_window
+            + OPTION_PUBKEY_LEN // attestor
+            + BOOL_LEN // split_escrow
+    }
+
+    /// Pyth price feed registered for `mint`, if any.
+    pub fn price_feed(&self, mint: &Pubkey) -> Option<Pubkey> {
+        self.allowed_mints
+            .iter()
+            .position(|allowed_mint| allowed_mint == mint)
+            .and_then(|index| self.price_feeds.get(index).copied())
+            .filter(|price_feed| *price_feed != Pubkey::default())
+    }
+
+    /// Index of the fee tier for a freelancer with `completed_deals`, None for the base fee.
+    pub fn fee_tier(&self, completed_deals: u64) -> Option<usize> {
+        self.fee_tiers
+            .iter()
+            .enumerate()
+            .filter(|(_, tier)| tier.min_completed_deals <= completed_deals)
+            .max_by_key(|(_, tier)| tier.min_completed_deals)
+            .map(|(index, _)| index)
+    }
+
+    /// Client fee percentage of the tier at `index`, or the base percentage if None.
+    pub fn tier_client_fee_percentage(&self, index: Option<usize>) -> u16 {
+        index
+            .and_then(|index| self.fee_tiers.get(index))
+            .map_or(self.client_fee_percentage, |tier| {
+                tier.client_fee_percentage
+            })
+    }
+
+    /// Fails if `deadline` is further than `max_deadline_horizon` past `from`.
+    pub fn check_deadline_horizon(&self, from: i64, deadline: i64) -> Result<()> {
+        if self.max_deadline_horizon == 0 {
+            return Ok(());
+        }
+
+        let max_deadline = from
+            .checked_add(self.max_deadline_horizon)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        if deadline > max_deadline {
+            return Err(error!(CustomError::DeadlineTooFar));
+        }
+
+        Ok(())
+    }
+
+    /// Whether an offer this large has to wait for `approve_deal` before it's escrowed.
+    pub fn needs_approval(&self, offer: u64) -> bool {
+        self.require_approval_above > 0 && offer > self.require_approval_above
+    }
+
+    /// Fails if the client and freelancer fees together exceed `MAX_TOTAL_FEE_PERCENTAGE`,
+    /// for the base client fee and every fee tier.
+    pub fn check_fees(&self) -> Result<()> {
+        let client_fee_percentages = std::iter::once(self.client_fee_percentage)
+            .chain(self.fee_tiers.iter().map(|tier| tier.client_fee_percentage));
+
+        for client_fee_percentage in client_fee_percentages {
+            let total_fee_percentage = client_fee_percentage
+                .checked_add(self.freelancer_fee_percentage)
+                .ok_or(error!(CustomError::FeeTooHigh))?;
+
+            if total_fee_percentage > MAX_TOTAL_FEE_PERCENTAGE {
+                return Err(error!(CustomError::FeeTooHigh));
+            }
+        }
+
+        Ok(())
     }
 }
@@ -1,5 +1,123 @@
 use anchor_lang::prelude::*;
 
+use super::sizes::{
+    BOOL_LEN, DISCRIMINATOR_LEN, HASH_LEN, I64_LEN, OPTION_PUBKEY_LEN, PUBKEY_LEN, U16_LEN,
+    U64_LEN, U8_LEN, VEC_PREFIX_LEN,
+};
+use super::Config;
+use crate::CustomError;
+
+/// Maximum number of milestones of a Deal.
+pub const MAX_MILESTONES: usize = 8;
+
+/// Layout version of new deals, older accounts are brought up to it with `migrate_deal`.
+pub const DEAL_VERSION: u8 = 10;
+
+/// Part of the offer that the client can release on its own.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Milestone {
+    /// Amount released to the freelancer for this milestone. (8)
+    pub amount: u64,
+
+    /// Whether the client already released this milestone. (1)
+    pub released: bool,
+
+    /// Hash of the off-chain description of this milestone. (32)
+    pub description_hash: [u8; 32],
+}
+
+impl Milestone {
+    pub fn len() -> usize {
+        U64_LEN // amount
+            + BOOL_LEN // released
+            + HASH_LEN // description_hash
+    }
+}
+
+/// Due date and late penalty of the milestone at the same index, see `Deal::milestone_dues`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MilestoneDue {
+    /// The penalty applies if the milestone is released after this, zero for no due date.
+    /// Unix timestamp. (8)
+    pub due_ts: i64,
+
+    /// Share of the milestone refunded to the client if it's released late.
+    /// Percentage with 2 decimal places (0 to 10000). (2)
+    pub late_penalty_bps: u16,
+
+    /// Whether the penalty was taken out of the release. (1)
+    pub penalty_applied: bool,
+}
+
+impl MilestoneDue {
+    pub fn len() -> usize {
+        I64_LEN // due_ts
+            + U16_LEN // late_penalty_bps
+            + BOOL_LEN // penalty_applied
+    }
+}
+
+/// Lifecycle of a Deal, stored as `u8` in `Deal::state`.
+/// Created, Accepted and Completed keep the values of the bit flags used by earlier versions.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DealStatus {
+    Created = 1,
+    Accepted = 3,
+    Delivered = 4,
+    Cancelled = 5,
+    Refunded = 6,
+    Disputed = 7,
+    Resolved = 8,
+    CounterOffered = 9,
+    Completed = 11,
+    /// Offered by the freelancer through `propose_deal`, waiting for the client to fund it.
+    Proposed = 12,
+    /// Offer above `config.require_approval_above`, nothing is escrowed until `approve_deal`.
+    PendingApproval = 13,
+    /// Approved by the authority, waiting for the client to fund it with `fund_deal`.
+    Approved = 14,
+    /// Completed by the client, paid out by `finalize_settlement` once `config.settlement_delay`
+    /// has passed.
+    PendingSettlement = 15,
+}
+
+impl DealStatus {
+    /// Settled one way or another, the escrow was paid out and closed.
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            DealStatus::Cancelled
+                | DealStatus::Refunded
+                | DealStatus::Resolved
+                | DealStatus::Completed
+        )
+    }
+}
+
+impl TryFrom<u8> for DealStatus {
+    type Error = Error;
+
+    fn try_from(state: u8) -> Result<Self> {
+        match state {
+            1 => Ok(DealStatus::Created),
+            3 => Ok(DealStatus::Accepted),
+            4 => Ok(DealStatus::Delivered),
+            5 => Ok(DealStatus::Cancelled),
+            6 => Ok(DealStatus::Refunded),
+            7 => Ok(DealStatus::Disputed),
+            8 => Ok(DealStatus::Resolved),
+            9 => Ok(DealStatus::CounterOffered),
+            11 => Ok(DealStatus::Completed),
+            12 => Ok(DealStatus::Proposed),
+            13 => Ok(DealStatus::PendingApproval),
+            14 => Ok(DealStatus::Approved),
+            15 => Ok(DealStatus::PendingSettlement),
+            _ => Err(error!(CustomError::InvalidState)),
+        }
+    }
+}
+
 /// A Deal is an account created by the client, related to the Gig created by the freelancer.
 #[account]
 pub struct Deal {
@@ -9,11 +127,7 @@ pub struct Deal {
     /// Owner of this deal. (32)
     pub client: Pubkey,
 
-    /// State of this Deal. (1)
-    /// * 1  - Published
-    /// * 2  - Accepted by the freelancer
-    /// * 4  - Has dispute
-    /// * 8  - Completed / settled
+    /// State of this Deal, see `DealStatus`. (1)
     pub state: u8,
 
     /// The owner of the Gig. (32)
@@ -36,10 +150,311 @@ pub struct Deal {
 
     /// Address who referred the gig to the client. (33)
     pub referrer: Option<Pubkey>,
+
+    /// Time when the client approved the work and the escrow was released. Unix timestamp. (8)
+    pub time_completed: i64,
+
+    /// Time when the deal was disputed. Unix timestamp. (8)
+    pub time_disputed: i64,
+
+    /// Optional split of the offer, the deal is completed once all are released. (4 + 41 * 8)
+    pub milestones: Vec<Milestone>,
+
+    /// Hash of the latest submitted work, eg. an IPFS CID or file digest. (32)
+    pub delivery_hash: [u8; 32],
+
+    /// Time when the latest work was submitted. Unix timestamp. (8)
+    pub time_delivered: i64,
+
+    /// Number of times the client sent the work back, capped by `Config::max_revisions`. (1)
+    pub revision_count: u8,
+
+    /// Higher offer proposed by the freelancer, waiting for the client to top up the escrow. (8)
+    pub counter_amount: u64,
+
+    /// What the freelancer is paid on completion, the offer less the freelancer fee at the time the
+    /// offer was made. Milestones are charged the fee in force when each is released. (8)
+    pub freelancer_net: u64,
+
+    /// Client fee deposited on top of the offer, settlement splits this instead of recomputing it. (8)
+    pub client_fee: u64,
+
+    /// Offer plus client fee, what the client deposited into the escrow. (8)
+    pub total_escrow: u64,
+
+    /// Refunded to the client out of the freelancer's pay for completing after the deadline. (8)
+    pub penalty_applied: u64,
+
+    /// Delivered work can be finalized without the client after this, zero if there's no review
+    /// window. Unix timestamp. (8)
+    pub review_deadline: i64,
+
+    /// Hash of the off-chain terms both parties agreed to, echoed by the freelancer on accept. (32)
+    pub terms_hash: [u8; 32],
+
+    /// Collateral the freelancer put in the escrow on accept, see `Gig::required_stake`. (8)
+    /// Returned on completion, forfeited to the client if the deal is refunded after expiring.
+    pub stake_amount: u64,
+
+    /// Layout version of this account, see `DEAL_VERSION`. Zero for deals created before it. (1)
+    pub version: u8,
+
+    /// Paid to the freelancer out of the escrow when the client cancelled after acceptance. (8)
+    pub cancel_fee_paid: u64,
+
+    /// Seconds from acceptance the freelancer has to complete the deal, zero if `deadline` was
+    /// set on creation. `deadline` stays zero until the deal is accepted. (8)
+    pub deadline_duration: i64,
+
+    /// Gig the deal was created for, which its address is derived from. Only set once the deal
+    /// was reassigned to another freelancer's gig, `gig` is the gig otherwise. (32)
+    pub origin_gig: Pubkey,
+
+    /// Picked by the client to have several deals for the same gig, part of the address. Deals
+    /// created before nonces are all zero, see `Deal::nonce_seed`. (8)
+    pub nonce: u64,
+
+    /// Due date and late penalty of each milestone, in the same order. Kept apart from `milestones`
+    /// so those keep their layout, empty for deals created before it. (4 + 11 * 8)
+    pub milestone_dues: Vec<MilestoneDue>,
+
+    /// When a deal pending settlement can be paid out, see `DealStatus::PendingSettlement`.
+    /// Unix timestamp, zero if it was settled right away. (8)
+    pub settle_after: i64,
+
+    /// SPL token the deal is escrowed in, native SOL if None. Copied from the gig when the deal
+    /// is created so settlement doesn't depend on the gig, set by `migrate_deal` for older deals.
+    /// (33)
+    pub mint: Option<Pubkey>,
+
+    /// Yield strategy part of the escrow is parked in, kept in case the config moves on to
+    /// another one. (33)
+    pub strategy: Option<Pubkey>,
+
+    /// Part of the escrow parked in `strategy`, the deal can't be settled until it's withdrawn.
+    /// (8)
+    pub strategy_deposited: u64,
+
+    /// Token account holding the client fee apart from the offer, see `Config.split_escrow`.
+    /// None if the fee is escrowed along with the offer. (33)
+    pub fee_escrow: Option<Pubkey>,
 }
 
 impl Deal {
     pub fn len() -> usize {
-        8 + 1 + 32 + 1 + 32 + 32 + 8 + 8 + 8 + 8 + 33
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // client
+            + U8_LEN // state
+            + PUBKEY_LEN // freelancer
+            + PUBKEY_LEN // gig
+            + U64_LEN // offer
+            + I64_LEN // deadline
+            + I64_LEN // time_created
+            + I64_LEN // time_accepted
+            + OPTION_PUBKEY_LEN // referrer
+            + I64_LEN // time_completed
+            + I64_LEN // time_disputed
+            + (VEC_PREFIX_LEN + Milestone::len() * MAX_MILESTONES) // milestones
+            + HASH_LEN // delivery_hash
+            + I64_LEN // time_delivered
+            + U8_LEN // revision_count
+            + U64_LEN // counter_amount
+            + U64_LEN // freelancer_net
+            + U64_LEN // client_fee
+            + U64_LEN // total_escrow
+            + U64_LEN // penalty_applied
+            + I64_LEN // review_deadline
+            + HASH_LEN // terms_hash
+            + U64_LEN // stake_amount
+            + U8_LEN // version
+            + U64_LEN // cancel_fee_paid
+            + I64_LEN // deadline_duration
+            + PUBKEY_LEN // origin_gig
+            + U64_LEN // nonce
+            + (VEC_PREFIX_LEN + MilestoneDue::len() * MAX_MILESTONES) // milestone_dues
+            + I64_LEN // settle_after
+            + OPTION_PUBKEY_LEN // mint
+            + OPTION_PUBKEY_LEN // strategy
+            + U64_LEN // strategy_deposited
+            + OPTION_PUBKEY_LEN // fee_escrow
+    }
+
+    /// Part of the offer which has not been released through milestones yet.
+    pub fn unreleased_offer(&self) -> Result<u64> {
+        self.milestones
+            .iter()
+            .filter(|milestone| milestone.released)
+            .try_fold(0u64, |released, milestone| {
+                released.checked_add(milestone.amount)
+            })
+            .and_then(|released| self.offer.checked_sub(released))
+            .ok_or(error!(CustomError::MathOverflow))
+    }
+
+    /// What the escrow should be holding, the deposit and stake less the milestones released so far.
+    pub fn expected_escrow(&self) -> Result<u64> {
+        let released = self
+            .offer
+            .checked_sub(self.unreleased_offer()?)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        self.total_escrow
+            .checked_add(self.stake_amount)
+            .and_then(|amount| amount.checked_sub(released))
+            .ok_or(error!(CustomError::MathOverflow))
+    }
+
+    /// Sets `deadline` for a deal created with `deadline_duration`, counted from `time_accepted`.
+    /// The gig's minimum and the config's horizon are checked again, they may have changed since
+    /// the deal was created.
+    pub fn start_deadline(&mut self, min_completion_time: i64, config: &Config) -> Result<()> {
+        if self.deadline_duration == 0 {
+            return Ok(());
+        }
+
+        if self.deadline_duration < min_completion_time {
+            return Err(error!(CustomError::DeadlineTooShort));
+        }
+
+        let deadline = self
+            .time_accepted
+            .checked_add(self.deadline_duration)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        config.check_deadline_horizon(self.time_accepted, deadline)?;
+        self.deadline = deadline;
+
+        Ok(())
+    }
+
+    pub fn status(&self) -> Result<DealStatus> {
+        DealStatus::try_from(self.state)
+    }
+
+    /// Gig the address of this PDA is derived from, see `origin_gig`.
+    pub fn seed_gig(&self) -> &Pubkey {
+        if self.origin_gig == Pubkey::default() {
+            &self.gig
+        } else {
+            &self.origin_gig
+        }
+    }
+
+    /// Seed of the deal address for `nonce` in little endian. Nonce zero adds nothing to the
+    /// seeds, so it derives the same address as deals created before nonces.
+    pub fn nonce_seed(nonce: &[u8; 8]) -> &[u8] {
+        if nonce == &[0; 8] {
+            &[]
+        } else {
+            nonce
+        }
+    }
+
+    /// Seeds of this PDA, used to sign for the escrow it owns. `nonce` is `self.nonce` in
+    /// little endian.
+    pub fn signer_seeds<'a>(&'a self, nonce: &'a [u8; 8]) -> [&'a [u8]; 5] {
+        [
+            b"deal",
+            self.client.as_ref(),
+            self.seed_gig().as_ref(),
+            Deal::nonce_seed(nonce),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deal with every option set and every vec full, the largest it can serialize to.
+    fn full_deal() -> Deal {
+        let key = Pubkey::new_unique();
+
+        Deal {
+            bump: u8::MAX,
+            client: key,
+            state: DealStatus::PendingSettlement as u8,
+            freelancer: key,
+            gig: key,
+            offer: u64::MAX,
+            deadline: i64::MAX,
+            time_created: i64::MAX,
+            time_accepted: i64::MAX,
+            referrer: Some(key),
+            time_completed: i64::MAX,
+            time_disputed: i64::MAX,
+            milestones: vec![
+                Milestone {
+                    amount: u64::MAX,
+                    released: true,
+                    description_hash: [u8::MAX; 32],
+                };
+                MAX_MILESTONES
+            ],
+            delivery_hash: [u8::MAX; 32],
+            time_delivered: i64::MAX,
+            revision_count: u8::MAX,
+            counter_amount: u64::MAX,
+            freelancer_net: u64::MAX,
+            client_fee: u64::MAX,
+            total_escrow: u64::MAX,
+            penalty_applied: u64::MAX,
+            review_deadline: i64::MAX,
+            terms_hash: [u8::MAX; 32],
+            stake_amount: u64::MAX,
+            version: DEAL_VERSION,
+            cancel_fee_paid: u64::MAX,
+            deadline_duration: i64::MAX,
+            origin_gig: key,
+            nonce: u64::MAX,
+            milestone_dues: vec![
+                MilestoneDue {
+                    due_ts: i64::MAX,
+                    late_penalty_bps: u16::MAX,
+                    penalty_applied: true,
+                };
+                MAX_MILESTONES
+            ],
+            settle_after: i64::MAX,
+            mint: Some(key),
+            strategy: Some(key),
+            strategy_deposited: u64::MAX,
+            fee_escrow: Some(key),
+        }
+    }
+
+    #[test]
+    fn full_deal_fits_its_space() {
+        let data = full_deal().try_to_vec().unwrap();
+
+        assert_eq!(DISCRIMINATOR_LEN + data.len(), Deal::len());
+    }
+
+    #[test]
+    fn escrow_math_near_u64_max_fails_instead_of_panicking() {
+        let mut deal = full_deal();
+
+        // Every milestone at u64::MAX released sums past u64::MAX.
+        assert_eq!(
+            deal.unreleased_offer().unwrap_err(),
+            error!(CustomError::MathOverflow)
+        );
+
+        deal.milestones.clear();
+        deal.offer = 100;
+        deal.total_escrow = u64::MAX;
+        deal.stake_amount = 1;
+
+        assert_eq!(deal.unreleased_offer().unwrap(), 100);
+        assert_eq!(
+            deal.expected_escrow().unwrap_err(),
+            error!(CustomError::MathOverflow)
+        );
+
+        deal.stake_amount = 0;
+
+        assert_eq!(deal.expected_escrow().unwrap(), u64::MAX);
     }
 }
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, PUBKEY_LEN, U8_LEN, VEC_PREFIX_LEN};
+
+/// Maximum number of deals kept in a DealIndex, the oldest is overwritten past it.
+pub const MAX_DEAL_INDEX_ENTRIES: usize = 32;
+
+/// A deal listed in a DealIndex.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct DealIndexEntry {
+    pub deal: Pubkey,
+
+    /// State of the deal when it was last recorded, see `DealStatus`. (1)
+    pub state: u8,
+}
+
+impl DealIndexEntry {
+    pub fn len() -> usize {
+        PUBKEY_LEN // deal
+            + U8_LEN // state
+    }
+}
+
+/// Recent deals of a freelancer, so clients can list them without scanning every deal account.
+/// Only a convenience cache: it's updated when the account is passed to `create_deal` and the
+/// instructions settling a deal, so entries can be missing or stale. The deal accounts remain
+/// the source of truth.
+#[account]
+pub struct DealIndex {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Freelancer whose deals are listed. (32)
+    pub freelancer: Pubkey,
+
+    /// Entry overwritten by the next new deal once the index is full. (1)
+    pub head: u8,
+
+    /// Most recent deals, in the order they were added until the index wraps. (4 + 33 * 32)
+    pub entries: Vec<DealIndexEntry>,
+}
+
+impl DealIndex {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // freelancer
+            + U8_LEN // head
+            + (VEC_PREFIX_LEN + DealIndexEntry::len() * MAX_DEAL_INDEX_ENTRIES) // entries
+    }
+
+    /// Updates the state of the deal if it's listed, otherwise adds it in place of the oldest.
+    pub fn record(&mut self, deal: Pubkey, state: u8) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.deal == deal) {
+            entry.state = state;
+            return;
+        }
+
+        let entry = DealIndexEntry { deal, state };
+
+        if self.entries.len() < MAX_DEAL_INDEX_ENTRIES {
+            self.entries.push(entry);
+        } else {
+            self.entries[self.head as usize] = entry;
+            self.head = ((self.head as usize + 1) % MAX_DEAL_INDEX_ENTRIES) as u8;
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, I64_LEN, OPTION_U16_LEN, PUBKEY_LEN, U8_LEN};
+
+/// Client fee of a partner client, set by the authority with `set_exemption`.
+#[account]
+pub struct Exemption {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Client the exemption applies to. (32)
+    pub client: Pubkey,
+
+    /// Client fee percentage charged instead of the config and fee tier ones, zero also skips
+    /// the fee minimum. None to charge the usual fee. (1 + 2)
+    pub override_fee_bps: Option<u16>,
+
+    /// Time the exemption was last set. Unix timestamp. (8)
+    pub time_set: i64,
+}
+
+impl Exemption {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // client
+            + OPTION_U16_LEN // override_fee_bps
+            + I64_LEN // time_set
+    }
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, PUBKEY_LEN, U64_LEN, U8_LEN};
+use crate::CustomError;
+
+/// Collects the pay of a freelancer's completed deals while `Config.defer_freelancer_payouts`
+/// is set, so they can withdraw it at once with `withdraw_earnings`. Native SOL is held by the
+/// vault account itself, SPL tokens by an associated token account of the vault for each mint.
+#[account]
+pub struct FreelancerVault {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Freelancer this vault belongs to. (32)
+    pub freelancer: Pubkey,
+
+    /// Sum of everything paid into the vault, in the units of each mint. (8)
+    pub total_deposited: u64,
+
+    /// Sum of everything withdrawn from the vault, in the units of each mint. (8)
+    pub total_withdrawn: u64,
+}
+
+impl FreelancerVault {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // freelancer
+            + U64_LEN // total_deposited
+            + U64_LEN // total_withdrawn
+    }
+
+    /// Records `amount` paid into the vault by a settlement, the first one also initializes it.
+    pub fn record_deposit(&mut self, bump: u8, freelancer: Pubkey, amount: u64) -> Result<()> {
+        self.bump = bump;
+        self.freelancer = freelancer;
+        self.total_deposited = self
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,27 @@
 use anchor_lang::prelude::*;
 
+use super::sizes::{
+    DISCRIMINATOR_LEN, HASH_LEN, I64_LEN, OPTION_PUBKEY_LEN, OPTION_U64_LEN, PUBKEY_LEN, U16_LEN,
+    U64_LEN, U8_LEN, VEC_PREFIX_LEN,
+};
+use crate::CustomError;
+
+/// Maximum bytes of `Gig::metadata_uri`.
+pub const MAX_METADATA_URI_LEN: usize = 128;
+
+/// Maximum number of `Gig::allowed_referrers`.
+pub const MAX_ALLOWED_REFERRERS: usize = 8;
+
+/// Availability of a Gig, stored as `u8` in `Gig::state`.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GigStatus {
+    Draft = 0,
+    Open = 1,
+    Paused = 2,
+    Closed = 3,
+}
+
 /// Gig is an account created by the freelancer which tells the details of the offered service.
 #[account]
 pub struct Gig {
@@ -9,8 +31,7 @@ pub struct Gig {
     /// Freelancer who owns this Gig. (32)
     pub owner: Pubkey,
 
-    /// State of this Gig. (1)
-    /// * 1 - Published
+    /// State of this Gig, see `GigStatus`. (1)
     pub state: u8,
 
     /// How many deals are currently accepted and ongoing. (1)
@@ -41,10 +62,81 @@ pub struct Gig {
     /// SPL token for the gig payment. (33)
     /// If set to None, the freelancer is expecting SOL as payment.
     pub mint: Option<Pubkey>,
+
+    /// Hash of the title, description and other details stored off-chain. (32)
+    /// Lets frontends verify what they resolve from `metadata_uri`.
+    pub metadata_hash: [u8; 32],
+
+    /// How many deals are currently open, from creation until they are settled, cancelled or refunded. (2)
+    pub active_deals: u16,
+
+    /// New deals are rejected once `active_deals` reaches this. Zero for unlimited. (2)
+    pub max_concurrent_deals: u16,
+
+    /// The maximum pay allowed of this Gig, no upper bound if None. (9)
+    pub max_asking: Option<u64>,
+
+    /// Share of the offer refunded to the client if the deal is completed after its deadline.
+    /// Percentage with 2 decimal places (0 to 10000). (2)
+    pub late_penalty_bps: u16,
+
+    /// How many bytes of `metadata_uri` are used. (1)
+    pub metadata_uri_len: u8,
+
+    /// Arweave / IPFS pointer to the details, zero padded past `metadata_uri_len`. (128)
+    pub metadata_uri: [u8; MAX_METADATA_URI_LEN],
+
+    /// Collateral the freelancer has to escrow when accepting a deal, zero for none. (8)
+    /// Note: decimal places depends on the mint.
+    pub required_stake: u64,
+
+    /// Only these can be set as the referrer of a deal, anyone can if it's empty. (4 + 32 * 8)
+    pub allowed_referrers: Vec<Pubkey>,
+
+    /// Share of the offer paid to the freelancer if the client cancels an accepted deal, zero
+    /// if accepted deals can't be cancelled. Percentage with 2 decimal places (0 to 10000). (2)
+    pub cancel_fee_bps: u16,
+
+    /// Deals the client needs on their own `Reputation` to create a deal, zero for anyone. (2)
+    pub min_client_completed_deals: u16,
 }
 
 impl Gig {
     pub fn len() -> usize {
-        8 + 1 + 32 + 1 + 1 + 1 + 8 + 8 + 8 + 32 + 8 + 33
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // owner
+            + U8_LEN // state
+            + U8_LEN // pending_deals
+            + U8_LEN // category
+            + U64_LEN // skills
+            + U64_LEN // asking
+            + I64_LEN // min_completion_time
+            + PUBKEY_LEN // payer
+            + 8 * U8_LEN // nonce
+            + OPTION_PUBKEY_LEN // mint
+            + HASH_LEN // metadata_hash
+            + U16_LEN // active_deals
+            + U16_LEN // max_concurrent_deals
+            + OPTION_U64_LEN // max_asking
+            + U16_LEN // late_penalty_bps
+            + U8_LEN // metadata_uri_len
+            + MAX_METADATA_URI_LEN * U8_LEN // metadata_uri
+            + U64_LEN // required_stake
+            + (VEC_PREFIX_LEN + PUBKEY_LEN * MAX_ALLOWED_REFERRERS) // allowed_referrers
+            + U16_LEN // cancel_fee_bps
+            + U16_LEN // min_client_completed_deals
+    }
+
+    /// The URI has to fit in `metadata_uri` with the unused bytes zeroed,
+    /// so the same pointer always serializes the same way.
+    pub fn validate_metadata_uri(len: u8, uri: &[u8; MAX_METADATA_URI_LEN]) -> Result<()> {
+        let len = len as usize;
+
+        if len > MAX_METADATA_URI_LEN || uri[len..].iter().any(|byte| *byte != 0) {
+            return Err(error!(CustomError::InvalidMetadataUri));
+        }
+
+        Ok(())
     }
 }
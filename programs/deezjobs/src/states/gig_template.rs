@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{
+    DISCRIMINATOR_LEN, I64_LEN, OPTION_PUBKEY_LEN, PUBKEY_LEN, U16_LEN, U64_LEN, U8_LEN,
+};
+
+/// Defaults a freelancer reuses across similar gigs, see `create_gig_from_template`.
+/// Gigs copy the fields when they're created, updating the template doesn't change them.
+#[account]
+pub struct GigTemplate {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Freelancer who owns this template. (32)
+    pub owner: Pubkey,
+
+    /// Picked by the freelancer to have several templates, part of the address. (8)
+    pub template_id: u64,
+
+    /// SPL token for the payment of the gigs, native SOL if None. (33)
+    pub mint: Option<Pubkey>,
+
+    /// Service being offered, see `Gig::category`. (1)
+    pub category: u8,
+
+    /// The minimum pay allowed of the gigs. (8)
+    /// Note: decimal places depends on the mint.
+    pub asking: u64,
+
+    /// The minimum deadline that the freelancer can offer. In seconds. (8)
+    pub min_completion_time: i64,
+
+    /// See `Gig::late_penalty_bps`. Percentage with 2 decimal places (0 to 10000). (2)
+    pub late_penalty_bps: u16,
+
+    /// See `Gig::cancel_fee_bps`. Percentage with 2 decimal places (0 to 10000). (2)
+    pub cancel_fee_bps: u16,
+}
+
+impl GigTemplate {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // owner
+            + U64_LEN // template_id
+            + OPTION_PUBKEY_LEN // mint
+            + U8_LEN // category
+            + U64_LEN // asking
+            + I64_LEN // min_completion_time
+            + U16_LEN // late_penalty_bps
+            + U16_LEN // cancel_fee_bps
+    }
+}
@@ -1,7 +1,32 @@
+mod bid;
+mod blacklist;
+mod bounty;
 mod config;
 mod deal;
+mod deal_index;
+mod exemption;
+mod freelancer_vault;
 mod gig;
+mod gig_template;
+mod referrer_activity;
+mod reputation;
+mod retainer;
+mod review;
+mod sizes;
+mod stats;
 
+pub use bid::*;
+pub use blacklist::*;
+pub use bounty::*;
 pub use config::*;
 pub use deal::*;
+pub use deal_index::*;
+pub use exemption::*;
+pub use freelancer_vault::*;
 pub use gig::*;
+pub use gig_template::*;
+pub use referrer_activity::*;
+pub use reputation::*;
+pub use retainer::*;
+pub use review::*;
+pub use stats::*;
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, I64_LEN, PUBKEY_LEN, U8_LEN};
+
+/// Last time a referrer was credited for a deal of a client, one PDA per pair.
+/// Only used while `Config.referrer_cooldown` is set.
+#[account]
+pub struct ReferrerActivity {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Referrer credited for the deals. (32)
+    pub referrer: Pubkey,
+
+    /// Client whose deals were referred. (32)
+    pub client: Pubkey,
+
+    /// Time the referrer was last credited for a deal of the client. Unix timestamp. (8)
+    pub last_referral_ts: i64,
+}
+
+impl ReferrerActivity {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // referrer
+            + PUBKEY_LEN // client
+            + I64_LEN // last_referral_ts
+    }
+
+    /// Whether the referrer was credited for the client less than `cooldown` seconds before `now`.
+    pub fn in_cooldown(&self, cooldown: i64, now: i64) -> bool {
+        self.last_referral_ts > 0
+            && matches!(self.last_referral_ts.checked_add(cooldown), Some(until) if now < until)
+    }
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, PUBKEY_LEN, U64_LEN, U8_LEN};
+
+/// Track record of a freelancer across all their deals. Only this program can write to it,
+/// frontends compute a reputation score from the counts.
+#[account]
+pub struct Reputation {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Freelancer this reputation belongs to. (32)
+    pub freelancer: Pubkey,
+
+    /// Deals completed by the client releasing the escrow. (8)
+    pub completed_deals: u64,
+
+    /// Sum of everything paid out to the freelancer, after fees. (8)
+    pub total_earned: u64,
+
+    /// Disputes where the client was awarded more than half of the offer. (8)
+    pub disputes_lost: u64,
+
+    /// Accepted deals refunded to the client after the deadline passed. (8)
+    pub cancelled_deals: u64,
+
+    /// Number of reviews left by clients. (8)
+    pub review_count: u64,
+
+    /// Sum of the stars of all reviews, the average rating is `total_stars / review_count`. (8)
+    pub total_stars: u64,
+
+    /// Sum of the tips sent by clients after completion, not part of `total_earned`. (8)
+    pub tips_received: u64,
+}
+
+impl Reputation {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // freelancer
+            + U64_LEN // completed_deals
+            + U64_LEN // total_earned
+            + U64_LEN // disputes_lost
+            + U64_LEN // cancelled_deals
+            + U64_LEN // review_count
+            + U64_LEN // total_stars
+            + U64_LEN // tips_received
+    }
+}
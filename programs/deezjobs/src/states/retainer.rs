@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{BOOL_LEN, DISCRIMINATOR_LEN, I64_LEN, PUBKEY_LEN, U16_LEN, U64_LEN, U8_LEN};
+
+/// Ongoing work paid per period, eg. a monthly retainer. Every period is funded upfront and
+/// released one at a time with `release_period`. The lamports are escrowed by the retainer
+/// account itself like a Bounty, so only native SOL gigs can have retainers.
+#[account]
+pub struct Retainer {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Client paying the retainer. (32)
+    pub client: Pubkey,
+
+    /// The owner of the Gig. (32)
+    pub freelancer: Pubkey,
+
+    /// The Gig account. (32)
+    pub gig: Pubkey,
+
+    /// State of this Retainer, see `DealStatus`. Created until every period is released. (1)
+    pub state: u8,
+
+    /// Length of a period in seconds. (8)
+    pub period_seconds: i64,
+
+    /// Offer for each period, the freelancer fee is taken from it on release. (8)
+    pub per_period_amount: u64,
+
+    /// Client fee paid on top of each period, refunded with the periods still escrowed. (8)
+    pub per_period_client_fee: u64,
+
+    /// Number of periods funded. (2)
+    pub periods_total: u16,
+
+    /// Number of periods paid out so far. (2)
+    pub periods_released: u16,
+
+    /// The next period can be released after this. Unix timestamp. (8)
+    pub next_release_ts: i64,
+
+    /// Set by the client to let anyone release due periods, otherwise only the client can. (1)
+    pub allow_crank: bool,
+
+    /// Picked by the client to have several retainers with the same gig, part of the address. (8)
+    pub nonce: u64,
+
+    /// Time this retainer was created. (8)
+    pub time_created: i64,
+}
+
+impl Retainer {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // client
+            + PUBKEY_LEN // freelancer
+            + PUBKEY_LEN // gig
+            + U8_LEN // state
+            + I64_LEN // period_seconds
+            + U64_LEN // per_period_amount
+            + U64_LEN // per_period_client_fee
+            + U16_LEN // periods_total
+            + U16_LEN // periods_released
+            + I64_LEN // next_release_ts
+            + BOOL_LEN // allow_crank
+            + U64_LEN // nonce
+            + I64_LEN // time_created
+    }
+
+    /// What the retainer account should hold on top of its rent, each unreleased period and
+    /// its client fee.
+    pub fn expected_escrow(&self) -> Option<u64> {
+        let periods_left = u64::from(self.periods_total.checked_sub(self.periods_released)?);
+
+        self.per_period_amount
+            .checked_add(self.per_period_client_fee)?
+            .checked_mul(periods_left)
+    }
+}
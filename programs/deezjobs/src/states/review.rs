@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, HASH_LEN, PUBKEY_LEN, U8_LEN};
+
+/// Rating left by the client once a deal is completed, one per deal.
+#[account]
+pub struct Review {
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// The completed Deal. (32)
+    pub deal: Pubkey,
+
+    /// Client who left the review. (32)
+    pub reviewer: Pubkey,
+
+    /// Rating from 1 to 5. (1)
+    pub stars: u8,
+
+    /// Hash of the review text stored off-chain. (32)
+    pub comment_hash: [u8; 32],
+}
+
+impl Review {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U8_LEN // bump
+            + PUBKEY_LEN // deal
+            + PUBKEY_LEN // reviewer
+            + U8_LEN // stars
+            + HASH_LEN // comment_hash
+    }
+}
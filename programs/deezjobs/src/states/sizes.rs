@@ -0,0 +1,26 @@
+//! Borsh sizes of the field types, used to add up the space of each account in its `len()`.
+
+/// Anchor account discriminator, prepended to every account.
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+pub const BOOL_LEN: usize = 1;
+pub const U8_LEN: usize = 1;
+pub const U16_LEN: usize = 2;
+pub const U64_LEN: usize = 8;
+pub const I64_LEN: usize = 8;
+pub const PUBKEY_LEN: usize = 32;
+
+/// `[u8; 32]` hashes of off-chain content.
+pub const HASH_LEN: usize = 32;
+
+/// `Option<Pubkey>`, one byte for the tag even when `None`.
+pub const OPTION_PUBKEY_LEN: usize = 1 + PUBKEY_LEN;
+
+/// `Option<u16>`, one byte for the tag even when `None`.
+pub const OPTION_U16_LEN: usize = 1 + U16_LEN;
+
+/// `Option<u64>`, one byte for the tag even when `None`.
+pub const OPTION_U64_LEN: usize = 1 + U64_LEN;
+
+/// Length prefix of a `Vec`, the items are added separately for its maximum length.
+pub const VEC_PREFIX_LEN: usize = 4;
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+
+use super::sizes::{DISCRIMINATOR_LEN, U64_LEN, U8_LEN};
+use crate::CustomError;
+
+/// Program-wide counters for dashboards, zero-copy since nearly every deal instruction writes it.
+/// Amounts are summed across mints in their smallest unit.
+#[account(zero_copy)]
+pub struct Stats {
+    /// Number of deals ever created. (8)
+    pub total_deals_created: u64,
+
+    /// Sum of the offers of all created deals. (8)
+    pub total_volume: u64,
+
+    /// Sum of the fees paid to the treasury on settlement. (8)
+    pub total_fees_collected: u64,
+
+    /// Deals currently open, from creation until they are settled, cancelled or refunded. (8)
+    pub active_deals: u64,
+
+    /// Sum of `total_escrow` of the open deals, capped by `Config.max_tvl`. (8)
+    pub active_escrow_total: u64,
+
+    /// Bump nonce of the PDA. (1)
+    pub bump: u8,
+
+    /// Keeps the struct free of implicit padding. (7)
+    pub _padding: [u8; 7],
+}
+
+impl Stats {
+    pub fn len() -> usize {
+        DISCRIMINATOR_LEN
+            + U64_LEN // total_deals_created
+            + U64_LEN // total_volume
+            + U64_LEN // total_fees_collected
+            + U64_LEN // active_deals
+            + U64_LEN // active_escrow_total
+            + U8_LEN // bump
+            + 7 * U8_LEN // _padding
+    }
+
+    pub fn record_created(&mut self, offer: u64) -> Result<()> {
+        self.total_deals_created = self
+            .total_deals_created
+            .checked_add(1)
+            .ok_or(error!(CustomError::MathOverflow))?;
+        self.active_deals = self
+            .active_deals
+            .checked_add(1)
+            .ok_or(error!(CustomError::MathOverflow))?;
+        self.record_volume(offer)
+    }
+
+    /// Call when an open deal's offer grows without `record_created`, eg. a bounty contribution.
+    pub fn record_volume(&mut self, amount: u64) -> Result<()> {
+        self.total_volume = self
+            .total_volume
+            .checked_add(amount)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        Ok(())
+    }
+
+    /// Call whenever a deal escrows more, fails if that exceeds `max_tvl` unless it's zero.
+    pub fn record_escrowed(&mut self, amount: u64, max_tvl: u64) -> Result<()> {
+        let active_escrow_total = self
+            .active_escrow_total
+            .checked_add(amount)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        if max_tvl > 0 && active_escrow_total > max_tvl {
+            return Err(error!(CustomError::TvlCapExceeded));
+        }
+
+        self.active_escrow_total = active_escrow_total;
+
+        Ok(())
+    }
+
+    /// Call once per deal when it's settled, cancelled or refunded, with its `total_escrow`.
+    pub fn record_closed(&mut self, total_escrow: u64) {
+        self.active_deals = self.active_deals.saturating_sub(1);
+        self.record_released(total_escrow);
+    }
+
+    /// Call whenever escrow added by `record_escrowed` leaves the program outside of
+    /// `record_closed`, eg. a reclaimed bid or a released retainer period.
+    pub fn record_released(&mut self, amount: u64) {
+        // Escrows funded before the total was tracked were never added.
+        self.active_escrow_total = self.active_escrow_total.saturating_sub(amount);
+    }
+
+    pub fn record_fees(&mut self, fees: u64) -> Result<()> {
+        self.total_fees_collected = self
+            .total_fees_collected
+            .checked_add(fees)
+            .ok_or(error!(CustomError::MathOverflow))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,838 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{hash, instruction::Instruction, program::invoke_signed},
+};
+use anchor_spl::token::{
+    spl_token::native_mint, CloseAccount, Mint, Token, TokenAccount, Transfer,
+};
+use pyth_sdk_solana::{load_price_feed_from_account_info, Price};
+
+use crate::{
+    states::{Config, Deal, Exemption, Reputation},
+    CustomError,
+};
+
+/// Decimal places of USD amounts, same as USDC.
+pub const USD_DECIMALS: i32 = 6;
+
+/// Fails for mints with extensions (Token-2022), eg. a transfer fee would leave the escrow short
+/// of `total_escrow`. `Account<Mint>` only loads legacy mints, this keeps it explicit.
+pub fn check_mint_supported(mint: &AccountInfo) -> Result<()> {
+    if mint.owner != &anchor_spl::token::ID || mint.data_len() != Mint::LEN {
+        return Err(error!(CustomError::UnsupportedMintExtension));
+    }
+
+    Ok(())
+}
+
+/// Moves lamports out of an account owned by this program, eg. the native SOL escrow held by a Deal.
+pub fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    Ok(())
+}
+
+/// `percentage` of `amount`, with percentages written as `100_00` for 100.00%.
+pub fn percentage_of(amount: u64, percentage: u16) -> Result<u64> {
+    u64::from(percentage)
+        .checked_mul(amount)
+        .and_then(|amount| amount.checked_div(100_00))
+        .ok_or(error!(CustomError::MathOverflow))
+}
+
+/// Platform fee of `percentage` on `amount`, rounded up if `config.round_fees_up` is set.
+/// Never more than `amount`.
+pub fn fee_of(config: &Config, amount: u64, percentage: u16) -> Result<u64> {
+    if !config.round_fees_up {
+        return percentage_of(amount, percentage);
+    }
+
+    let fee = u64::from(percentage)
+        .checked_mul(amount)
+        .and_then(|amount| amount.checked_add(100_00 - 1))
+        .and_then(|amount| amount.checked_div(100_00))
+        .ok_or(error!(CustomError::MathOverflow))?;
+
+    Ok(fee.min(amount))
+}
+
+/// Lamports held by `account` on top of its rent-exempt balance.
+pub fn lamports_above_rent(account: &AccountInfo) -> Result<u64> {
+    let rent_exempt_balance = Rent::get()?.minimum_balance(account.data_len());
+
+    Ok(account.lamports().saturating_sub(rent_exempt_balance))
+}
+
+/// Converts a USD amount into the smallest unit of a token with `decimals`, using the token's Pyth `price`.
+pub fn usd_to_token_amount(usd_amount: u64, price: &Price, decimals: u8) -> Option<u64> {
+    if price.price <= 0 {
+        return None;
+    }
+
+    let token_price = u128::try_from(price.price).ok()?;
+    let exponent = i32::from(decimals) - USD_DECIMALS - price.expo;
+    let scale = 10u128.checked_pow(exponent.unsigned_abs())?;
+
+    let amount = if exponent >= 0 {
+        u128::from(usd_amount)
+            .checked_mul(scale)?
+            .checked_div(token_price)?
+    } else {
+        u128::from(usd_amount).checked_div(token_price.checked_mul(scale)?)?
+    };
+
+    u64::try_from(amount).ok()
+}
+
+/// Converts `amount` in the smallest unit of a token with `decimals` into USD, using the token's Pyth `price`.
+pub fn token_amount_to_usd(amount: u64, price: &Price, decimals: u8) -> Option<u64> {
+    if price.price <= 0 {
+        return None;
+    }
+
+    let token_price = u128::try_from(price.price).ok()?;
+    let exponent = USD_DECIMALS + price.expo - i32::from(decimals);
+    let scale = 10u128.checked_pow(exponent.unsigned_abs())?;
+
+    let usd_amount = if exponent >= 0 {
+        u128::from(amount)
+            .checked_mul(token_price)?
+            .checked_mul(scale)?
+    } else {
+        u128::from(amount)
+            .checked_mul(token_price)?
+            .checked_div(scale)?
+    };
+
+    u64::try_from(usd_amount).ok()
+}
+
+/// Moves `amount` from `from_decimals` to `to_decimals` places, rounding down.
+pub fn rescale_amount(amount: u64, from_decimals: i32, to_decimals: i32) -> Option<u64> {
+    let exponent = to_decimals - from_decimals;
+    let scale = 10u64.checked_pow(exponent.unsigned_abs())?;
+
+    if exponent >= 0 {
+        amount.checked_mul(scale)
+    } else {
+        amount.checked_div(scale)
+    }
+}
+
+/// Pyth price of `mint` if the config has a feed registered for it, `price_feed` must be that feed.
+fn registered_price(
+    config: &Config,
+    mint: &Pubkey,
+    price_feed: Option<&AccountInfo>,
+    now: i64,
+) -> Result<Option<Price>> {
+    let price_feed_key = match config.price_feed(mint) {
+        Some(price_feed_key) => price_feed_key,
+        None => return Ok(None),
+    };
+
+    let price_feed = price_feed.ok_or(error!(CustomError::MissingPriceFeed))?;
+
+    if price_feed.key() != price_feed_key {
+        return Err(error!(CustomError::InvalidPriceFeed));
+    }
+
+    let price = load_price_feed_from_account_info(price_feed)
+        .map_err(|_| error!(CustomError::InvalidPriceFeed))?
+        .get_price_no_older_than(now, config.max_price_age)
+        .ok_or(error!(CustomError::StalePriceFeed))?;
+
+    Ok(Some(price))
+}
+
+/// Fee charged to the client on top of `offer` for a gig paid in `gig_mint`, `None` for native SOL.
+/// `price_feed` is only needed if the mint has a Pyth feed registered in the config.
+/// `client_fee_percentage` comes from the `client_fee_percentage` helper: the client's exemption,
+/// else the freelancer's fee tier, else the config's base percentage. None means the client is
+/// exempted altogether, no fee is charged and the minimum fee doesn't apply either.
+pub fn compute_client_fee(
+    config: &Config,
+    gig_mint: Option<Pubkey>,
+    mint_decimals: Option<u8>,
+    price_feed: Option<&AccountInfo>,
+    client_fee_percentage: Option<u16>,
+    offer: u64,
+    now: i64,
+) -> Result<u64> {
+    let client_fee_percentage = match client_fee_percentage {
+        Some(client_fee_percentage) => client_fee_percentage,
+        None => return Ok(0),
+    };
+
+    let client_fee = fee_of(config, offer, client_fee_percentage)?;
+
+    // The flat minimum is denominated in `config.fee_mint`. Deals paid in any other token (or native SOL)
+    // are floored at `config.fee_min_usd`, converted with the Pyth price of the mint if it has a feed.
+    let client_fee_min = match gig_mint {
+        Some(mint) if mint == config.fee_mint => config.client_fee_min,
+        _ => match registered_price(
+            config,
+            &gig_mint.unwrap_or(native_mint::ID),
+            price_feed,
+            now,
+        )? {
+            Some(price) => {
+                let decimals = mint_decimals.unwrap_or(native_mint::DECIMALS);
+
+                usd_to_token_amount(config.fee_min_usd, &price, decimals)
+                    .ok_or(error!(CustomError::InvalidPriceFeed))?
+            }
+            None => 0,
+        },
+    };
+
+    Ok(client_fee.max(client_fee_min))
+}
+
+/// Client fee percentage of a deal between the client behind `client_exemption` and the freelancer
+/// behind `freelancer_reputation`. The exemption overrides the fee tier the freelancer reached,
+/// which overrides the base percentage of the config. None if the client is exempted from the fee
+/// altogether, see `Exemption::override_fee_bps`.
+pub fn client_fee_percentage(
+    config: &Config,
+    freelancer_reputation: Option<&Account<Reputation>>,
+    client_exemption: Option<&Account<Exemption>>,
+) -> Option<u16> {
+    let fee_tier =
+        freelancer_reputation.and_then(|reputation| config.fee_tier(reputation.completed_deals));
+
+    match client_exemption.and_then(|exemption| exemption.override_fee_bps) {
+        Some(0) => None,
+        Some(override_fee_bps) => Some(override_fee_bps),
+        None => Some(config.tier_client_fee_percentage(fee_tier)),
+    }
+}
+
+/// Converts a referral fee of `amount` in the deal mint into `config.referrer_payout_mint`, which is
+/// expected to be a USD stablecoin. Deals in `config.fee_mint` are taken at face value, other mints
+/// need their Pyth feed registered in the config.
+pub fn referral_payout_amount(
+    config: &Config,
+    gig_mint: Option<Pubkey>,
+    mint_decimals: Option<u8>,
+    price_feed: Option<&AccountInfo>,
+    payout_decimals: u8,
+    amount: u64,
+    now: i64,
+) -> Result<u64> {
+    let decimals = mint_decimals.unwrap_or(native_mint::DECIMALS);
+
+    if gig_mint.is_some() && gig_mint == config.referrer_payout_mint {
+        return Ok(amount);
+    }
+
+    let (usd_amount, usd_decimals) = if gig_mint == Some(config.fee_mint) {
+        (amount, i32::from(decimals))
+    } else {
+        let price = registered_price(
+            config,
+            &gig_mint.unwrap_or(native_mint::ID),
+            price_feed,
+            now,
+        )?
+        .ok_or(error!(CustomError::MissingPriceFeed))?;
+
+        let usd_amount = token_amount_to_usd(amount, &price, decimals)
+            .ok_or(error!(CustomError::InvalidPriceFeed))?;
+
+        (usd_amount, USD_DECIMALS)
+    };
+
+    rescale_amount(usd_amount, usd_decimals, i32::from(payout_decimals))
+        .ok_or(error!(CustomError::MathOverflow))
+}
+
+/// Fails if `referrer` can't be credited for a deal between `client` and `freelancer`. Neither
+/// party can refer the deal to themselves to skim the referral pay, and gigs with
+/// `allowed_referrers` only take those.
+pub fn check_referrer(
+    referrer: &Pubkey,
+    client: &Pubkey,
+    freelancer: &Pubkey,
+    allowed_referrers: &[Pubkey],
+) -> Result<()> {
+    if referrer == client || referrer == freelancer {
+        return Err(error!(CustomError::InvalidReferrer));
+    }
+
+    if !allowed_referrers.is_empty() && !allowed_referrers.contains(referrer) {
+        return Err(error!(CustomError::ReferrerNotAllowed));
+    }
+
+    Ok(())
+}
+
+/// What the freelancer is paid out of `amount` once the freelancer fee is taken.
+pub fn freelancer_net(config: &Config, amount: u64) -> Result<u64> {
+    let freelancer_fee = fee_of(config, amount, config.freelancer_fee_percentage)?;
+
+    amount
+        .checked_sub(freelancer_fee)
+        .ok_or(error!(CustomError::MathOverflow))
+}
+
+/// Escrow of a deal, either its associated token account or, for native SOL gigs,
+/// the lamports held by the deal account itself on top of its rent. SPL deals created with
+/// `config.split_escrow` hold the client fee in a fee escrow next to it, see `Deal.fee_escrow`.
+/// The two are paid out as one: `release` draws on the escrow first, `release_fee` on the fee
+/// escrow first, and both take what's missing from the other.
+pub struct Escrow<'a, 'info> {
+    deal: &'a Account<'info, Deal>,
+    token: Option<(&'a Account<'info, TokenAccount>, &'a Program<'info, Token>)>,
+    fee_escrow: Option<&'a Account<'info, TokenAccount>>,
+}
+
+impl<'a, 'info> Escrow<'a, 'info> {
+    /// `fee_escrow` is ignored for deals without one.
+    pub fn new(
+        deal: &'a Account<'info, Deal>,
+        is_native: bool,
+        escrow: Option<&'a Account<'info, TokenAccount>>,
+        fee_escrow: Option<&'a Account<'info, TokenAccount>>,
+        token_program: Option<&'a Program<'info, Token>>,
+    ) -> Result<Self> {
+        // Settling with part of the escrow in a yield strategy would leave the rest short.
+        if deal.strategy_deposited > 0 {
+            return Err(error!(CustomError::EscrowInStrategy));
+        }
+
+        if is_native {
+            return Ok(Self {
+                deal,
+                token: None,
+                fee_escrow: None,
+            });
+        }
+
+        let fee_escrow = fee_escrow_of(deal, fee_escrow)?;
+
+        match (escrow, token_program) {
+            (Some(escrow), Some(token_program)) => Ok(Self {
+                deal,
+                token: Some((escrow, token_program)),
+                fee_escrow,
+            }),
+            _ => Err(error!(CustomError::MissingEscrowAccounts)),
+        }
+    }
+
+    /// Amount held in escrow, read it before releasing any funds.
+    pub fn amount(&self) -> Result<u64> {
+        match self.token {
+            Some((escrow, _)) => escrow
+                .amount
+                .checked_add(self.fee_escrow.map_or(0, |fee_escrow| fee_escrow.amount))
+                .ok_or(error!(CustomError::MathOverflow)),
+            None => lamports_above_rent(&self.deal.to_account_info()),
+        }
+    }
+
+    /// Amount held in escrow, failing if it drifted from what the deal deposited.
+    /// Settlements split the balance, so anything sent to the escrow on top would be paid out too.
+    pub fn checked_amount(&self) -> Result<u64> {
+        let amount = self.amount()?;
+
+        if amount != self.deal.expected_escrow()? {
+            return Err(error!(CustomError::EscrowBalanceMismatch));
+        }
+
+        Ok(amount)
+    }
+
+    /// Releases `amount` to `wallet`, or to `destination` (a token account of `wallet`) for SPL gigs.
+    pub fn release(
+        &self,
+        destination: Option<&Account<'info, TokenAccount>>,
+        wallet: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        match self.token {
+            Some((escrow, _)) => self.release_from(escrow, self.fee_escrow, destination, amount),
+            None => transfer_lamports(&self.deal.to_account_info(), wallet, amount),
+        }
+    }
+
+    /// Releases `amount` like `release`, out of the fee escrow first. For payouts of the client
+    /// fee, eg. to the treasury or back to the client.
+    pub fn release_fee(
+        &self,
+        destination: Option<&Account<'info, TokenAccount>>,
+        wallet: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        match (self.token, self.fee_escrow) {
+            (Some((escrow, _)), Some(fee_escrow)) => {
+                self.release_from(fee_escrow, Some(escrow), destination, amount)
+            }
+            _ => self.release(destination, wallet, amount),
+        }
+    }
+
+    /// Releases whatever is still in escrow to `wallet`, so it's empty when closed. The
+    /// settlements pay out the remainder of their split already, this catches any rounding dust
+    /// they missed. Returns the amount swept.
+    pub fn sweep(
+        &self,
+        destination: Option<&Account<'info, TokenAccount>>,
+        wallet: &AccountInfo<'info>,
+    ) -> Result<u64> {
+        let escrow = match self.token {
+            Some((escrow, _)) => escrow,
+            None => {
+                let amount = lamports_above_rent(&self.deal.to_account_info())?;
+
+                if amount > 0 {
+                    self.release(destination, wallet, amount)?;
+                }
+
+                return Ok(amount);
+            }
+        };
+
+        let mut swept = 0u64;
+
+        for source in [Some(escrow), self.fee_escrow].into_iter().flatten() {
+            let amount = token_amount(source)?;
+
+            if amount > 0 {
+                let destination = destination.ok_or(error!(CustomError::MissingEscrowAccounts))?;
+
+                self.transfer(source, destination, amount)?;
+                swept = swept
+                    .checked_add(amount)
+                    .ok_or(error!(CustomError::MathOverflow))?;
+            }
+        }
+
+        Ok(swept)
+    }
+
+    /// Closes the escrow token account, and the fee escrow if there is one, and gives back the
+    /// rent to `rent_destination`. Nothing to close for native SOL gigs.
+    pub fn close(&self, rent_destination: &AccountInfo<'info>) -> Result<()> {
+        let (escrow, token_program) = match self.token {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+
+        let nonce = self.deal.nonce.to_le_bytes();
+        let seeds = self.deal.signer_seeds(&nonce);
+        let deal_sig = [seeds.as_slice()];
+
+        for account in [Some(escrow), self.fee_escrow].into_iter().flatten() {
+            let cpi_close = CloseAccount {
+                account: account.to_account_info(),
+                destination: rent_destination.to_account_info(),
+                authority: self.deal.to_account_info(),
+            };
+
+            let cpi_ctx =
+                CpiContext::new_with_signer(token_program.to_account_info(), cpi_close, &deal_sig);
+
+            anchor_spl::token::close_account(cpi_ctx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Transfers `amount` to `destination` out of `first`, and out of `second` for whatever
+    /// `first` doesn't hold.
+    fn release_from(
+        &self,
+        first: &Account<'info, TokenAccount>,
+        second: Option<&Account<'info, TokenAccount>>,
+        destination: Option<&Account<'info, TokenAccount>>,
+        amount: u64,
+    ) -> Result<()> {
+        let destination = destination.ok_or(error!(CustomError::MissingEscrowAccounts))?;
+
+        let second = match second {
+            Some(second) => second,
+            None => return self.transfer(first, destination, amount),
+        };
+
+        let from_first = amount.min(token_amount(first)?);
+        let from_second = amount - from_first;
+
+        if from_first > 0 {
+            self.transfer(first, destination, from_first)?;
+        }
+
+        if from_second > 0 {
+            self.transfer(second, destination, from_second)?;
+        }
+
+        Ok(())
+    }
+
+    fn transfer(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        destination: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        let token_program = match self.token {
+            Some((_, token_program)) => token_program,
+            None => return Err(error!(CustomError::MissingEscrowAccounts)),
+        };
+
+        let nonce = self.deal.nonce.to_le_bytes();
+        let seeds = self.deal.signer_seeds(&nonce);
+        let deal_sig = [seeds.as_slice()];
+
+        let transfer_ix = Transfer {
+            from: from.to_account_info(),
+            to: destination.to_account_info(),
+            authority: self.deal.to_account_info(),
+        };
+
+        let cpi_ctx =
+            CpiContext::new_with_signer(token_program.to_account_info(), transfer_ix, &deal_sig);
+
+        anchor_spl::token::transfer(cpi_ctx, amount)
+    }
+}
+
+/// Balance of `account` read from its data, `Account::amount` isn't reloaded after transfers.
+fn token_amount(account: &Account<TokenAccount>) -> Result<u64> {
+    let account_info = account.to_account_info();
+    let data = account_info.try_borrow_data()?;
+
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+/// The fee escrow of `deal` out of `fee_escrow`, None for deals escrowing the fee with the offer.
+/// Fails if the deal has one and it wasn't passed.
+pub fn fee_escrow_of<'a, 'info>(
+    deal: &Deal,
+    fee_escrow: Option<&'a Account<'info, TokenAccount>>,
+) -> Result<Option<&'a Account<'info, TokenAccount>>> {
+    match (deal.fee_escrow, fee_escrow) {
+        (None, _) => Ok(None),
+        (Some(key), Some(fee_escrow)) if fee_escrow.key() == key => Ok(Some(fee_escrow)),
+        _ => Err(error!(CustomError::FeeEscrowMismatch)),
+    }
+}
+
+/// Deposits `amount` from the client's `owner_wallet` into the escrow of an SPL deal. With a
+/// `fee_escrow`, that's topped up to the deal's `client_fee` first and the escrow gets the rest.
+pub fn deposit_tokens<'info>(
+    owner_wallet: &Account<'info, TokenAccount>,
+    client: &AccountInfo<'info>,
+    escrow: &Account<'info, TokenAccount>,
+    fee_escrow: Option<&Account<'info, TokenAccount>>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+    client_fee: u64,
+) -> Result<()> {
+    let transfer = |to: &Account<'info, TokenAccount>, amount: u64| {
+        let transfer_ix = Transfer {
+            from: owner_wallet.to_account_info(),
+            to: to.to_account_info(),
+            authority: client.clone(),
+        };
+
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), transfer_ix);
+
+        anchor_spl::token::transfer(cpi_ctx, amount)
+    };
+
+    let fee_amount = match fee_escrow {
+        Some(fee_escrow) => {
+            let fee_amount = client_fee.saturating_sub(fee_escrow.amount).min(amount);
+
+            if fee_amount > 0 {
+                transfer(fee_escrow, fee_amount)?;
+            }
+
+            fee_amount
+        }
+        None => 0,
+    };
+
+    transfer(escrow, amount - fee_amount)
+}
+
+/// Returns everything held in the escrow of `deal` to the client, closing its token accounts.
+pub fn refund_escrow<'info>(
+    deal: &Account<'info, Deal>,
+    client: &AccountInfo<'info>,
+    escrow: Option<&Account<'info, TokenAccount>>,
+    fee_escrow: Option<&Account<'info, TokenAccount>>,
+    owner_wallet: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+    is_native: bool,
+) -> Result<u64> {
+    let escrow = Escrow::new(deal, is_native, escrow, fee_escrow, token_program)?;
+    let refund_amount = escrow.checked_amount()?;
+
+    escrow.release_fee(owner_wallet, client, refund_amount)?;
+    let dust = escrow.sweep(owner_wallet, client)?;
+    escrow.close(client)?;
+
+    refund_amount
+        .checked_add(dust)
+        .ok_or(error!(CustomError::MathOverflow))
+}
+
+/// Calls `instruction` of a yield strategy with `args`, signed by `deal`. The strategy gets the
+/// deal, its escrow and the token program, then `remaining_accounts` as they were passed.
+pub fn invoke_strategy<'info>(
+    strategy: &AccountInfo<'info>,
+    deal: &Account<'info, Deal>,
+    escrow: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    remaining_accounts: &[AccountInfo<'info>],
+    instruction: &str,
+    args: &[u8],
+) -> Result<()> {
+    // Anchor's instruction discriminator, the strategy is expected to be an Anchor program.
+    let preimage = format!("global:{}", instruction);
+    let mut data = hash::hash(preimage.as_bytes()).to_bytes()[..8].to_vec();
+    data.extend_from_slice(args);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(deal.key(), true),
+        AccountMeta::new(escrow.key(), false),
+        AccountMeta::new_readonly(token_program.key(), false),
+    ];
+    let mut account_infos = vec![
+        deal.to_account_info(),
+        escrow.to_account_info(),
+        token_program.to_account_info(),
+    ];
+
+    for account in remaining_accounts {
+        accounts.push(match account.is_writable {
+            true => AccountMeta::new(account.key(), account.is_signer),
+            false => AccountMeta::new_readonly(account.key(), account.is_signer),
+        });
+        account_infos.push(account.clone());
+    }
+
+    account_infos.push(strategy.clone());
+
+    let nonce = deal.nonce.to_le_bytes();
+    let seeds = deal.signer_seeds(&nonce);
+
+    let strategy_ix = Instruction {
+        program_id: strategy.key(),
+        accounts,
+        data,
+    };
+
+    invoke_signed(&strategy_ix, &account_infos, &[seeds.as_slice()])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config with a 6 decimal fee mint, no price feeds or minimum fees.
+    fn config() -> Config {
+        Config {
+            bump: 0,
+            authority: Pubkey::new_unique(),
+            arbiter: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            client_fee_percentage: 2_00,
+            client_fee_min: 0,
+            fee_mint: Pubkey::new_unique(),
+            freelancer_fee_percentage: 5_00,
+            referral_fee_percentage: 1_00,
+            allowed_mints: vec![],
+            price_feeds: vec![],
+            fee_min_usd: 0,
+            max_price_age: 60,
+            refund_grace_period: 0,
+            paused: false,
+            max_revisions: 0,
+            pending_authority: None,
+            review_window: 0,
+            referrer_payout_mint: None,
+            max_deal_offer: 0,
+            fee_tiers: vec![],
+            dispute_timeout: 0,
+            dispute_default_client_bps: 0,
+            round_fees_up: false,
+            referrer_cooldown: 0,
+            defer_freelancer_payouts: false,
+            max_deadline_horizon: 0,
+            freelancer_cancel_penalty_bps: 0,
+            require_approval_above: 0,
+            settlement_delay: 0,
+            max_tvl: 0,
+            yield_strategy: None,
+            client_cancel_window: 0,
+            attestor: None,
+            split_escrow: false,
+        }
+    }
+
+    #[test]
+    fn referral_in_the_payout_mint_is_paid_as_is() {
+        let mut config = config();
+        let payout_mint = Pubkey::new_unique();
+        config.referrer_payout_mint = Some(payout_mint);
+
+        let amount =
+            referral_payout_amount(&config, Some(payout_mint), Some(9), None, 9, 1_234, 0).unwrap();
+
+        assert_eq!(amount, 1_234);
+    }
+
+    #[test]
+    fn referral_in_the_fee_mint_is_rescaled_to_the_payout_decimals() {
+        let mut config = config();
+        config.referrer_payout_mint = Some(Pubkey::new_unique());
+        let fee_mint = Some(config.fee_mint);
+
+        let up = referral_payout_amount(&config, fee_mint, Some(6), None, 9, 1_234, 0).unwrap();
+        let down = referral_payout_amount(&config, fee_mint, Some(6), None, 2, 1_234, 0).unwrap();
+
+        assert_eq!(up, 1_234_000);
+        assert_eq!(down, 0);
+    }
+
+    #[test]
+    fn referral_in_a_mint_without_a_feed_fails() {
+        let mut config = config();
+        config.referrer_payout_mint = Some(Pubkey::new_unique());
+
+        let result = referral_payout_amount(&config, None, None, None, 6, 1_000_000_000, 0);
+
+        assert_eq!(result.unwrap_err(), error!(CustomError::MissingPriceFeed));
+    }
+
+    #[test]
+    fn client_cant_refer_their_own_deal() {
+        let client = Pubkey::new_unique();
+        let freelancer = Pubkey::new_unique();
+
+        let result = check_referrer(&client, &client, &freelancer, &[]);
+
+        assert_eq!(result.unwrap_err(), error!(CustomError::InvalidReferrer));
+    }
+
+    #[test]
+    fn freelancer_cant_refer_their_own_deal() {
+        let client = Pubkey::new_unique();
+        let freelancer = Pubkey::new_unique();
+
+        let result = check_referrer(&freelancer, &client, &freelancer, &[]);
+
+        assert_eq!(result.unwrap_err(), error!(CustomError::InvalidReferrer));
+    }
+
+    #[test]
+    fn third_party_can_refer_a_deal() {
+        let referrer = Pubkey::new_unique();
+        let client = Pubkey::new_unique();
+        let freelancer = Pubkey::new_unique();
+
+        assert!(check_referrer(&referrer, &client, &freelancer, &[]).is_ok());
+        assert!(check_referrer(&referrer, &client, &freelancer, &[referrer]).is_ok());
+    }
+
+    #[test]
+    fn referrer_outside_the_allowed_ones_is_rejected() {
+        let referrer = Pubkey::new_unique();
+        let allowed = [Pubkey::new_unique()];
+
+        let result = check_referrer(
+            &referrer,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &allowed,
+        );
+
+        assert_eq!(result.unwrap_err(), error!(CustomError::ReferrerNotAllowed));
+    }
+
+    /// Largest amount a fee percentage can be taken of without overflowing.
+    const MAX_FEE_AMOUNT: u64 = u64::MAX / 100_00;
+
+    #[test]
+    fn percentage_of_near_u64_max_fails_instead_of_panicking() {
+        assert_eq!(
+            percentage_of(MAX_FEE_AMOUNT, 100_00).unwrap(),
+            MAX_FEE_AMOUNT
+        );
+        assert_eq!(percentage_of(u64::MAX, 0).unwrap(), 0);
+        assert_eq!(
+            percentage_of(u64::MAX, 2).unwrap_err(),
+            error!(CustomError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn fee_of_near_u64_max_fails_instead_of_panicking() {
+        let mut config = config();
+
+        assert_eq!(
+            fee_of(&config, MAX_FEE_AMOUNT, 50_00).unwrap(),
+            MAX_FEE_AMOUNT / 2
+        );
+        assert_eq!(
+            fee_of(&config, u64::MAX, 50_00).unwrap_err(),
+            error!(CustomError::MathOverflow)
+        );
+
+        config.round_fees_up = true;
+
+        assert_eq!(
+            fee_of(&config, MAX_FEE_AMOUNT - 1, 100_00).unwrap(),
+            MAX_FEE_AMOUNT - 1
+        );
+        assert_eq!(
+            fee_of(&config, u64::MAX, 1).unwrap_err(),
+            error!(CustomError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn compute_client_fee_near_u64_max_fails_instead_of_panicking() {
+        let config = config();
+        let client_fee = |percentage: Option<u16>, offer: u64| {
+            compute_client_fee(&config, None, None, None, percentage, offer, 0)
+        };
+
+        assert_eq!(
+            client_fee(Some(2_00), MAX_FEE_AMOUNT).unwrap(),
+            MAX_FEE_AMOUNT / 50
+        );
+        assert_eq!(client_fee(None, u64::MAX).unwrap(), 0);
+        assert_eq!(
+            client_fee(Some(2_00), u64::MAX).unwrap_err(),
+            error!(CustomError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn freelancer_net_near_u64_max_fails_instead_of_panicking() {
+        let config = config();
+
+        assert_eq!(
+            freelancer_net(&config, MAX_FEE_AMOUNT).unwrap(),
+            MAX_FEE_AMOUNT - MAX_FEE_AMOUNT / 20
+        );
+        assert_eq!(
+            freelancer_net(&config, u64::MAX).unwrap_err(),
+            error!(CustomError::MathOverflow)
+        );
+    }
+}
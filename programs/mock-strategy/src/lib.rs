@@ -0,0 +1,99 @@
+// Anchor's `Result` carries a large error type.
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+declare_id!("Epa1vXtGXjVy8yVLUeDm1aCCte4PeNDuAFo21i2SBNYs");
+
+/// Stand-in for a lending protocol in the tests of `deposit_escrow_to_strategy`. Each owner has
+/// a vault of its own, the tests accrue yield by minting straight into it.
+#[program]
+pub mod mock_strategy {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        let transfer_ix = Transfer {
+            from: ctx.accounts.source.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_ix);
+
+        anchor_spl::token::transfer(cpi_ctx, amount)
+    }
+
+    /// Returns the whole vault, deposits and yield.
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let seeds = [
+            b"vault".as_ref(),
+            owner.as_ref(),
+            &[*ctx.bumps.get("vault_authority").unwrap()],
+        ];
+        let vault_sig = [seeds.as_slice()];
+
+        let transfer_ix = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_ix,
+            &vault_sig,
+        );
+
+        anchor_spl::token::transfer(cpi_ctx, ctx.accounts.vault.amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        seeds = [b"vault", owner.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: only signs for the vault
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = source.mint,
+        token::authority = vault_authority,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        seeds = [b"vault", owner.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: only signs for the vault
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = destination.mint,
+        token::authority = vault_authority,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+}
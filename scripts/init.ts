@@ -11,7 +11,6 @@ import { getUsdc, KeypairWallet } from './utils'
 import idl from '../target/idl/deezjobs.json'
 import programKp from '../target/deploy/deezjobs-keypair.json'
 import authorityKp from '../keys/authority.json'
-import treasuryKp from '../keys/treasury.json'
 import {
   ASSOCIATED_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
@@ -22,7 +21,6 @@ const { publicKey: programId } = Keypair.fromSecretKey(
   new Uint8Array(programKp),
 )
 const authority = Keypair.fromSecretKey(new Uint8Array(authorityKp))
-const treasury = Keypair.fromSecretKey(new Uint8Array(treasuryKp))
 
 const program = new Program<Deezjobs>(
   idl as unknown as Deezjobs,
@@ -40,6 +38,7 @@ const [programDataPda] = findProgramAddressSync(
 )
 
 const [configPda] = findProgramAddressSync([Buffer.from('config')], programId)
+const [statsPda] = findProgramAddressSync([Buffer.from('stats')], programId)
 
 const init = async () => {
   const usdcPubkey = await getUsdc(program.provider.connection, authority)
@@ -47,14 +46,16 @@ const init = async () => {
 
   if (!existingConfig) {
     try {
+      // Fees are held by the config PDA
       const treasuryUsdcAta = await getAssociatedTokenAddress(
         usdcPubkey,
-        treasury.publicKey,
+        configPda,
+        true,
       )
 
       const accounts = {
         config: configPda,
-        treasury: treasury.publicKey,
+        stats: statsPda,
         program: program.programId,
         programData: programDataPda,
         mint: usdcPubkey,
@@ -76,6 +77,16 @@ const init = async () => {
           clientFeePercentage: 3_00,
           freelancerFeePercentage: 5_00,
           referralFeePercentage: 5_00,
+          // minimum fee for deals not paid in USDC, 6 decimal places
+          feeMinUsd: new BN(2_000_000),
+          maxPriceAge: new BN(60),
+          // 3 days
+          refundGracePeriod: new BN(60 * 60 * 24 * 3),
+          maxRevisions: 3,
+          reviewWindow: new BN(60 * 60 * 24 * 3),
+          // 14 days, then split 50/50
+          disputeTimeout: new BN(60 * 60 * 24 * 14),
+          disputeDefaultClientBps: 50_00,
         })
         .accounts(accounts)
         .rpc()
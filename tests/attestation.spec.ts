@@ -0,0 +1,272 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import {
+  Ed25519Program,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import {
+  authority,
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let client = Keypair.generate()
+let attestor = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+let [dealPda] = findProgramAddressSync(
+  [Buffer.from('deal'), client.publicKey.toBytes(), gigPda.toBytes()],
+  program.programId,
+)
+
+const setAttestor = (attestor: PublicKey | null) =>
+  program.methods
+    .setAttestor({ attestor })
+    .accounts({
+      config: configPda,
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc()
+
+describe('Attested completion', () => {
+  const offer = new BN(LAMPORTS_PER_SOL / 10)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+  const deliveryHash = Buffer.alloc(32, 9)
+
+  // Completes the deal with `signer`'s signature over the deal, `hash` and the
+  // time the work was delivered, the latest submission unless given.
+  const attestedComplete = async (
+    signer: Keypair,
+    hash: Buffer,
+    timeDelivered?: BN,
+  ) => {
+    const deal = await program.account.deal.fetch(dealPda)
+    const deliveredAt = timeDelivered ?? deal.timeDelivered
+
+    return program.methods
+      .attestedComplete()
+      .accounts({
+        complete: {
+          client: client.publicKey,
+          deal: dealPda,
+          escrow: null,
+          feeEscrow: null,
+          gig: gigPda,
+          mint: null,
+          signer: freelancer.publicKey,
+          referrer: null,
+          clientTokenAccount: null,
+          referrerTokenAccount: null,
+          referrerRewardsVault: null,
+          referrerPayoutMint: null,
+          priceFeed: null,
+          config: configPda,
+          stats: statsPda,
+          freelancer: freelancer.publicKey,
+          freelancerTokenAccount: null,
+          freelancerVault: null,
+          freelancerVaultTokenAccount: null,
+          treasury: configPda,
+          treasuryTokenAccount: null,
+          reputation: findReputationPda(freelancer.publicKey),
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: null,
+          tokenProgram: null,
+          rent: SYSVAR_RENT_PUBKEY,
+          dealIndex: null,
+        },
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions([
+        Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: signer.secretKey,
+          message: Buffer.concat([
+            dealPda.toBuffer(),
+            hash,
+            deliveredAt.toArrayLike(Buffer, 'le', 8),
+          ]),
+        }),
+      ])
+      .signers([freelancer])
+      .rpc()
+  }
+
+  const submitDeliverable = () =>
+    program.methods
+      .submitDeliverable({ deliveryHash: Array.from(deliveryHash) })
+      .accounts({
+        deal: dealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+      })
+      .signers([freelancer])
+      .rpc()
+
+  before(async () => {
+    for (const wallet of [freelancer, client]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+
+    await program.methods
+      .createDeal({
+        offer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: null,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: null,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: dealPda,
+        gig: gigPda,
+        mint: null,
+        owner: client.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+    await program.methods
+      .acceptDeal({ termsHash: Array.from(Buffer.alloc(32)) })
+      .accounts({
+        gig: gigPda,
+        deal: dealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    await submitDeliverable()
+
+    await setAttestor(attestor.publicKey)
+  })
+
+  after(async () => {
+    await setAttestor(null)
+  })
+
+  it('should not complete a deal attested by someone else', async () => {
+    try {
+      await attestedComplete(freelancer, deliveryHash)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidAttestation')
+    }
+  })
+
+  it('should not complete a deal attested for another delivery', async () => {
+    try {
+      await attestedComplete(attestor, Buffer.alloc(32, 8))
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'AttestationMismatch')
+    }
+  })
+
+  it('should not complete a deal attested for an earlier submission', async () => {
+    const { timeDelivered } = await program.account.deal.fetch(dealPda)
+
+    await wait(1500)
+    await submitDeliverable()
+
+    try {
+      await attestedComplete(attestor, deliveryHash, timeDelivered)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'AttestationMismatch')
+    }
+  })
+
+  it('should complete the deal once the attestor signed the delivery', async () => {
+    await attestedComplete(attestor, deliveryHash)
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.state === 11)
+  })
+})
@@ -0,0 +1,212 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+} from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import {
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let winner = Keypair.generate()
+let loser = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+const findBidPda = (bidder: PublicKey) =>
+  findProgramAddressSync(
+    [Buffer.from('bid'), gigPda.toBytes(), bidder.toBytes()],
+    program.programId,
+  )[0]
+let [dealPda] = findProgramAddressSync(
+  [Buffer.from('deal'), winner.publicKey.toBytes(), gigPda.toBytes()],
+  program.programId,
+)
+
+describe('Gig auctions', () => {
+  const asking = new BN(LAMPORTS_PER_SOL / 10)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+
+  const placeBid = (bidder: Keypair, offer: BN) =>
+    program.methods
+      .placeBid({ offer, deadline })
+      .accounts({
+        bid: findBidPda(bidder.publicKey),
+        gig: gigPda,
+        bidder: bidder.publicKey,
+        config: configPda,
+        clientBlacklist: findBlacklistPda(bidder.publicKey),
+        freelancerReputation: null,
+        clientExemption: null,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([bidder])
+      .rpc()
+
+  const reclaimBid = (bidder: PublicKey, signer: Keypair) =>
+    program.methods
+      .reclaimBid()
+      .accounts({
+        bid: findBidPda(bidder),
+        bidder,
+        signer: signer.publicKey,
+        stats: statsPda,
+      })
+      .signers([signer])
+      .rpc()
+
+  before(async () => {
+    for (const wallet of [freelancer, winner, loser]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    await program.methods
+      .createGig({
+        asking,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+  })
+
+  it('should not allow bids below the asking price', async () => {
+    try {
+      await placeBid(loser, asking.subn(1))
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InsufficientOffer')
+    }
+  })
+
+  it('should escrow the bids of several clients', async () => {
+    await placeBid(loser, asking)
+    await placeBid(winner, asking.muln(2))
+
+    const bid = await program.account.bid.fetch(findBidPda(winner.publicKey))
+    const bidInfo = await program.provider.connection.getAccountInfo(
+      findBidPda(winner.publicKey),
+    )
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        bidInfo.data.length,
+      )
+
+    assert.ok(bid.offer.eq(asking.muln(2)))
+    assert.ok(new BN(bidInfo.lamports - rent).eq(bid.offer.add(bid.clientFee)))
+  })
+
+  it('should turn the selected bid into an accepted deal', async () => {
+    const bid = await program.account.bid.fetch(findBidPda(winner.publicKey))
+
+    await program.methods
+      .selectBid({ nonce: new BN(0) })
+      .accounts({
+        bid: findBidPda(winner.publicKey),
+        deal: dealPda,
+        gig: gigPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        clientBlacklist: findBlacklistPda(winner.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        reputation: findReputationPda(freelancer.publicKey),
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+    const gig = await program.account.gig.fetch(gigPda)
+    const dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(deal.state === 3)
+    assert.ok(deal.client.equals(winner.publicKey))
+    assert.ok(deal.offer.eq(bid.offer))
+    assert.ok(deal.deadline.eq(bid.deadline))
+    assert.ok(gig.activeDeals === 1)
+    assert.ok(new BN(dealInfo.lamports - rent).eq(deal.totalEscrow))
+    assert.ok(
+      (await program.account.bid.fetchNullable(
+        findBidPda(winner.publicKey),
+      )) === null,
+    )
+  })
+
+  it('should not allow anyone else to reclaim a bid before its deadline', async () => {
+    try {
+      await reclaimBid(loser.publicKey, winner)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'Unauthorized')
+    }
+  })
+
+  it('should give the other bidders their bids back', async () => {
+    const bidInfo = await program.provider.connection.getAccountInfo(
+      findBidPda(loser.publicKey),
+    )
+    const before = await program.provider.connection.getBalance(
+      loser.publicKey,
+    )
+
+    await reclaimBid(loser.publicKey, loser)
+
+    const after = await program.provider.connection.getBalance(loser.publicKey)
+
+    assert.ok(
+      (await program.account.bid.fetchNullable(findBidPda(loser.publicKey))) ===
+        null,
+    )
+    assert.ok(after > before)
+    assert.ok(after - before <= bidInfo.lamports)
+  })
+})
@@ -0,0 +1,202 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import { Keypair, LAMPORTS_PER_SOL, SystemProgram } from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import {
+  configPda,
+  findBlacklistPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let creator = Keypair.generate()
+let contributor = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+let [bountyPda] = findProgramAddressSync(
+  [Buffer.from('bounty'), creator.publicKey.toBytes(), gigPda.toBytes()],
+  program.programId,
+)
+
+describe('Bounties', () => {
+  const amount = new BN(LAMPORTS_PER_SOL / 10)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+
+  before(async () => {
+    for (const wallet of [freelancer, creator, contributor]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    await program.methods
+      .createGig({
+        asking: amount,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+  })
+
+  const contribute = (wallet: Keypair) =>
+    program.methods
+      .contributeBounty({ amount })
+      .accounts({
+        bounty: bountyPda,
+        contributor: wallet.publicKey,
+        config: configPda,
+        contributorBlacklist: findBlacklistPda(wallet.publicKey),
+        freelancerReputation: null,
+        clientExemption: null,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([wallet])
+      .rpc()
+
+  const completeBounty = async () => {
+    const config = await program.account.config.fetch(configPda)
+
+    return program.methods
+      .completeBounty()
+      .accounts({
+        bounty: bountyPda,
+        creator: creator.publicKey,
+        freelancer: freelancer.publicKey,
+        treasury: config.treasury,
+        config: configPda,
+        reputation: null,
+        stats: statsPda,
+      })
+      .signers([creator])
+      .rpc()
+  }
+
+  it('should allow a client to create a bounty', async () => {
+    const before = await program.account.stats.fetch(statsPda)
+
+    await program.methods
+      .createBounty({ amount, deadline })
+      .accounts({
+        bounty: bountyPda,
+        gig: gigPda,
+        creator: creator.publicKey,
+        config: configPda,
+        clientBlacklist: findBlacklistPda(creator.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        freelancerReputation: null,
+        clientExemption: null,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc()
+
+    const bounty = await program.account.bounty.fetch(bountyPda)
+    const stats = await program.account.stats.fetch(statsPda)
+    const escrowed = amount.add(bounty.contributors[0].clientFee)
+
+    assert.ok(bounty.offer.eq(amount))
+    assert.ok(bounty.contributors.length === 1)
+    assert.ok(
+      stats.activeEscrowTotal.eq(before.activeEscrowTotal.add(escrowed)),
+    )
+    assert.ok(stats.activeDeals.eq(before.activeDeals.addn(1)))
+  })
+
+  it('should not allow the freelancer to fund their own bounty', async () => {
+    try {
+      await contribute(freelancer)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'Unauthorized')
+    }
+  })
+
+  it('should pool the contributions of other clients', async () => {
+    await contribute(contributor)
+    await contribute(contributor)
+
+    const bounty = await program.account.bounty.fetch(bountyPda)
+
+    assert.ok(bounty.offer.eq(amount.muln(3)))
+    assert.ok(bounty.contributors.length === 2)
+    assert.ok(bounty.contributors[1].amount.eq(amount.muln(2)))
+  })
+
+  it('should not complete a bounty before the work is submitted', async () => {
+    try {
+      await completeBounty()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidState')
+    }
+  })
+
+  it('should pay the pooled offer to the freelancer on completion', async () => {
+    const deliveryHash = Array.from(Buffer.alloc(32, 3))
+
+    await program.methods
+      .submitBounty({ deliveryHash })
+      .accounts({
+        bounty: bountyPda,
+        freelancer: freelancer.publicKey,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const before = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const config = await program.account.config.fetch(configPda)
+
+    await completeBounty()
+
+    const after = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const freelancerFee = amount
+      .muln(3)
+      .muln(config.freelancerFeePercentage)
+      .divn(100_00)
+
+    assert.ok(new BN(after - before).eq(amount.muln(3).sub(freelancerFee)))
+    assert.ok((await program.account.bounty.fetchNullable(bountyPda)) === null)
+  })
+})
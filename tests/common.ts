@@ -4,22 +4,39 @@ import {
   setProvider,
   workspace,
 } from '@coral-xyz/anchor'
-import { Keypair } from '@solana/web3.js'
+import { Keypair, PublicKey } from '@solana/web3.js'
 import { Deezjobs } from '../target/types/deezjobs'
+import { MockStrategy } from '../target/types/mock_strategy'
 import authorityKp from '../keys/authority.json'
-import treasuryKp from '../keys/treasury.json'
 import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
 
 setProvider(AnchorProvider.env())
 
 export const program = workspace.Deezjobs as Program<Deezjobs>
+export const mockStrategy = workspace.MockStrategy as Program<MockStrategy>
 
 export const wait = (ms: number) =>
   new Promise((resolve) => setTimeout(resolve, ms))
 
 export const authority = Keypair.fromSecretKey(new Uint8Array(authorityKp))
-export const treasury = Keypair.fromSecretKey(new Uint8Array(treasuryKp))
 export const [configPda] = findProgramAddressSync(
   [Buffer.from('config')],
   program.programId,
 )
+
+export const [statsPda] = findProgramAddressSync(
+  [Buffer.from('stats')],
+  program.programId,
+)
+
+export const findBlacklistPda = (user: PublicKey) =>
+  findProgramAddressSync(
+    [Buffer.from('blacklist'), user.toBytes()],
+    program.programId,
+  )[0]
+
+export const findReputationPda = (freelancer: PublicKey) =>
+  findProgramAddressSync(
+    [Buffer.from('reputation'), freelancer.toBytes()],
+    program.programId,
+  )[0]
@@ -0,0 +1,353 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import { authority, configPda, program, wait } from './common'
+
+describe('Config', () => {
+  it('should not allow a client fee above 100%', async () => {
+    try {
+      await program.methods
+        .updateConfig({
+          clientFeePercentage: 100_01,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'FeeTooHigh')
+    }
+  })
+
+  it('should not allow the combined fees above the cap', async () => {
+    try {
+      await program.methods
+        .updateConfig({
+          clientFeePercentage: 10_00,
+          freelancerFeePercentage: 10_01,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'FeeTooHigh')
+    }
+  })
+
+  it('should not allow anyone else to update the config', async () => {
+    const stranger = Keypair.generate()
+
+    await program.provider.connection.requestAirdrop(
+      stranger.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    try {
+      await program.methods
+        .updateConfig({
+          clientFeePercentage: 0,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'Unauthorized')
+    }
+  })
+
+  it('should only update the fields that are set', async () => {
+    const before = await program.account.config.fetch(configPda)
+
+    await program.methods
+      .updateConfig({
+        clientFeePercentage: null,
+        freelancerFeePercentage: null,
+        clientFeeMin: null,
+        arbiter: before.arbiter,
+        maxDealOffer: null,
+        feeTiers: null,
+        refundGracePeriod: null,
+        roundFeesUp: null,
+        referrerCooldown: null,
+        deferFreelancerPayouts: null,
+        maxDeadlineHorizon: null,
+        freelancerCancelPenaltyBps: null,
+        requireApprovalAbove: null,
+        settlementDelay: null,
+        maxTvl: null,
+        clientCancelWindow: null,
+        disputeTimeout: null,
+        splitEscrow: null,
+      })
+      .accounts({
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+    const after = await program.account.config.fetch(configPda)
+
+    assert.ok(after.clientFeePercentage === before.clientFeePercentage)
+    assert.ok(after.freelancerFeePercentage === before.freelancerFeePercentage)
+    assert.ok(after.clientFeeMin.eq(before.clientFeeMin))
+    assert.ok(after.arbiter.equals(before.arbiter))
+  })
+
+  it('should not allow more fee tiers than the config can hold', async () => {
+    const feeTiers = Array.from({ length: 9 }, (_, index) => ({
+      minCompletedDeals: new BN(index * 10),
+      clientFeePercentage: 1_00,
+    }))
+
+    try {
+      await program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'TooManyFeeTiers')
+    }
+  })
+
+  it('should not allow a negative refund grace period', async () => {
+    try {
+      await program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: new BN(-1),
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidGracePeriod')
+    }
+  })
+
+  it('should not allow anyone else to set the referrer payout mint', async () => {
+    const stranger = Keypair.generate()
+
+    await program.provider.connection.requestAirdrop(
+      stranger.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    try {
+      await program.methods
+        .setReferrerPayoutMint({ mint: null })
+        .accounts({
+          config: configPda,
+          authority: stranger.publicKey,
+          mint: null,
+          referrerRewardsVault: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([stranger])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'Unauthorized')
+    }
+  })
+
+  describe('authority transfer', () => {
+    const newAuthority = Keypair.generate()
+
+    const acceptAuthority = (signer: Keypair) =>
+      program.methods
+        .acceptAuthority()
+        .accounts({
+          config: configPda,
+          newAuthority: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc()
+
+    it('should allow the authority to propose a new authority', async () => {
+      await program.methods
+        .transferAuthority({ newAuthority: newAuthority.publicKey })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+      const config = await program.account.config.fetch(configPda)
+
+      assert.ok(config.authority.equals(authority.publicKey))
+      assert.ok(config.pendingAuthority.equals(newAuthority.publicKey))
+    })
+
+    it('should not allow anyone else to accept the authority', async () => {
+      try {
+        await acceptAuthority(Keypair.generate())
+
+        assert.ok(false)
+      } catch (e) {
+        const err = e as AnchorError
+
+        assert.ok(err.error.errorCode.code === 'Unauthorized')
+      }
+    })
+
+    it('should allow the pending authority to accept', async () => {
+      await acceptAuthority(newAuthority)
+
+      const config = await program.account.config.fetch(configPda)
+
+      assert.ok(config.authority.equals(newAuthority.publicKey))
+      assert.ok(config.pendingAuthority === null)
+    })
+
+    after(async () => {
+      // Hand the authority back for the other specs.
+      await program.methods
+        .transferAuthority({ newAuthority: authority.publicKey })
+        .accounts({
+          config: configPda,
+          authority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc()
+
+      await acceptAuthority(authority)
+    })
+  })
+})
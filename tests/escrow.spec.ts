@@ -0,0 +1,1413 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+} from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import {
+  authority,
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let client = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+let [dealPda] = findProgramAddressSync(
+  [Buffer.from('deal'), client.publicKey.toBytes(), gigPda.toBytes()],
+  program.programId,
+)
+let stakedGigId = Keypair.generate()
+let [stakedGigPda] = findProgramAddressSync(
+  [
+    Buffer.from('gig'),
+    freelancer.publicKey.toBytes(),
+    stakedGigId.publicKey.toBytes().slice(0, 8),
+  ],
+  program.programId,
+)
+let [stakedDealPda] = findProgramAddressSync(
+  [Buffer.from('deal'), client.publicKey.toBytes(), stakedGigPda.toBytes()],
+  program.programId,
+)
+let lateClient = Keypair.generate()
+let [lateDealPda] = findProgramAddressSync(
+  [Buffer.from('deal'), lateClient.publicKey.toBytes(), stakedGigPda.toBytes()],
+  program.programId,
+)
+let windowClient = Keypair.generate()
+let [windowDealPda] = findProgramAddressSync(
+  [
+    Buffer.from('deal'),
+    windowClient.publicKey.toBytes(),
+    stakedGigPda.toBytes(),
+  ],
+  program.programId,
+)
+
+describe('Escrow balance', () => {
+  const offer = new BN(LAMPORTS_PER_SOL / 10)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+
+  before(async () => {
+    for (const wallet of [freelancer, client]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+
+    await program.methods
+      .createDeal({
+        offer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: null,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: null,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: dealPda,
+        gig: gigPda,
+        mint: null,
+        owner: client.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+  })
+
+  it('should not settle an escrow holding more than the deal deposited', async () => {
+    // Anyone can send funds to the escrow, they must not be paid out with the deal
+    await program.provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: freelancer.publicKey,
+          toPubkey: dealPda,
+          lamports: 1_000,
+        }),
+      ),
+      [freelancer],
+    )
+
+    try {
+      await program.methods
+        .cancelDeal()
+        .accounts({
+          stats: statsPda,
+          config: configPda,
+          client: client.publicKey,
+          freelancer: freelancer.publicKey,
+          freelancerTokenAccount: null,
+          deal: dealPda,
+          escrow: null,
+          feeEscrow: null,
+          gig: gigPda,
+          mint: null,
+          ownerWallet: null,
+          tokenProgram: null,
+          dealIndex: null,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'EscrowBalanceMismatch')
+    }
+  })
+
+  it('should escrow the freelancer stake on accept', async () => {
+    const requiredStake = new BN(LAMPORTS_PER_SOL / 100)
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake,
+        cancelFeeBps: 10_00,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: stakedGigId.publicKey,
+        gig: stakedGigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, stakedGigId])
+      .rpc()
+
+    await program.methods
+      .createDeal({
+        offer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: null,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: null,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: stakedDealPda,
+        gig: stakedGigPda,
+        mint: null,
+        owner: client.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+    await program.methods
+      .acceptDeal({ termsHash: Array.from(Buffer.alloc(32)) })
+      .accounts({
+        gig: stakedGigPda,
+        deal: stakedDealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(stakedDealPda)
+    const dealInfo =
+      await program.provider.connection.getAccountInfo(stakedDealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(deal.stakeAmount.eq(requiredStake))
+    assert.ok(
+      new BN(dealInfo.lamports - rent).eq(deal.totalEscrow.add(requiredStake)),
+    )
+  })
+
+  it('should pay the cancel fee and stake back when an accepted deal is cancelled', async () => {
+    const before = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const stakeAmount = (await program.account.deal.fetch(stakedDealPda))
+      .stakeAmount
+
+    await program.methods
+      .cancelDeal()
+      .accounts({
+        stats: statsPda,
+        config: configPda,
+        client: client.publicKey,
+        freelancer: freelancer.publicKey,
+        freelancerTokenAccount: null,
+        deal: stakedDealPda,
+        escrow: null,
+        feeEscrow: null,
+        gig: stakedGigPda,
+        mint: null,
+        ownerWallet: null,
+        tokenProgram: null,
+        dealIndex: null,
+      })
+      .signers([client])
+      .rpc()
+
+    const after = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const deal = await program.account.deal.fetch(stakedDealPda)
+    const cancelFee = offer.muln(10_00).divn(100_00)
+
+    assert.ok(deal.state === 5)
+    assert.ok(deal.cancelFeePaid.eq(cancelFee))
+    assert.ok(new BN(after - before).eq(cancelFee.add(stakeAmount)))
+  })
+
+  it('should refund an accepted deal in full within the cancel window', async () => {
+    const setWindow = (clientCancelWindow: BN) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+    await program.provider.connection.requestAirdrop(
+      windowClient.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    await setWindow(new BN(60 * 60))
+
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash: Array.from(Buffer.alloc(32)),
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: null,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(windowClient.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: windowDealPda,
+          gig: stakedGigPda,
+          mint: null,
+          owner: windowClient.publicKey,
+          ownerWallet: null,
+          escrow: null,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+          associatedTokenProgram: null,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([windowClient])
+        .rpc()
+
+      await program.methods
+        .acceptDeal({ termsHash: Array.from(Buffer.alloc(32)) })
+        .accounts({
+          gig: stakedGigPda,
+          deal: windowDealPda,
+          freelancer: freelancer.publicKey,
+          config: configPda,
+          reputation: findReputationPda(freelancer.publicKey),
+          escrow: null,
+          freelancerWallet: null,
+          mint: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+        })
+        .signers([freelancer])
+        .rpc()
+
+      const before = await program.provider.connection.getBalance(
+        freelancer.publicKey,
+      )
+      const stakeAmount = (await program.account.deal.fetch(windowDealPda))
+        .stakeAmount
+
+      await program.methods
+        .cancelDeal()
+        .accounts({
+          stats: statsPda,
+          config: configPda,
+          client: windowClient.publicKey,
+          freelancer: freelancer.publicKey,
+          freelancerTokenAccount: null,
+          deal: windowDealPda,
+          escrow: null,
+          feeEscrow: null,
+          gig: stakedGigPda,
+          mint: null,
+          ownerWallet: null,
+          tokenProgram: null,
+          dealIndex: null,
+        })
+        .signers([windowClient])
+        .rpc()
+
+      const after = await program.provider.connection.getBalance(
+        freelancer.publicKey,
+      )
+      const deal = await program.account.deal.fetch(windowDealPda)
+
+      // Only the stake goes back to the freelancer, the cancel fee is waived.
+      assert.ok(deal.state === 5)
+      assert.ok(deal.cancelFeePaid.eqn(0))
+      assert.ok(new BN(after - before).eq(stakeAmount))
+    } finally {
+      await setWindow(new BN(0))
+    }
+  })
+
+  it('should start the deadline when the deal is accepted', async () => {
+    const duration = new BN(60 * 24 * 3)
+
+    await program.provider.connection.requestAirdrop(
+      lateClient.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    await program.methods
+      .createDeal({
+        offer,
+        deadline: new BN(0),
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: duration,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: null,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(lateClient.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: lateDealPda,
+        gig: stakedGigPda,
+        mint: null,
+        owner: lateClient.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([lateClient])
+      .rpc()
+
+    assert.ok((await program.account.deal.fetch(lateDealPda)).deadline.isZero())
+
+    await program.methods
+      .acceptDeal({ termsHash: Array.from(Buffer.alloc(32)) })
+      .accounts({
+        gig: stakedGigPda,
+        deal: lateDealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(lateDealPda)
+
+    assert.ok(deal.deadline.eq(deal.timeAccepted.add(duration)))
+  })
+
+  it('should hand the deal over to a substitute freelancer', async () => {
+    const substitute = Keypair.generate()
+    const substituteGigId = Keypair.generate()
+    const [substituteGigPda] = findProgramAddressSync(
+      [
+        Buffer.from('gig'),
+        substitute.publicKey.toBytes(),
+        substituteGigId.publicKey.toBytes().slice(0, 8),
+      ],
+      program.programId,
+    )
+
+    await program.provider.connection.requestAirdrop(
+      substitute.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: substituteGigId.publicKey,
+        gig: substituteGigPda,
+        mint: null,
+        owner: substitute.publicKey,
+        payer: substitute.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([substitute, substituteGigId])
+      .rpc()
+
+    const before = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const { stakeAmount } = await program.account.deal.fetch(lateDealPda)
+
+    await program.methods
+      .reassignFreelancer()
+      .accounts({
+        deal: lateDealPda,
+        gig: stakedGigPda,
+        newGig: substituteGigPda,
+        client: lateClient.publicKey,
+        freelancer: freelancer.publicKey,
+        newFreelancer: substitute.publicKey,
+        newFreelancerBlacklist: findBlacklistPda(substitute.publicKey),
+        newReputation: findReputationPda(substitute.publicKey),
+        escrow: null,
+        feeEscrow: null,
+        freelancerWallet: null,
+        newFreelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([lateClient, freelancer, substitute])
+      .rpc()
+
+    const after = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const deal = await program.account.deal.fetch(lateDealPda)
+
+    assert.ok(deal.freelancer.equals(substitute.publicKey))
+    assert.ok(deal.gig.equals(substituteGigPda))
+    assert.ok(deal.originGig.equals(stakedGigPda))
+    assert.ok(deal.stakeAmount.isZero())
+    assert.ok(new BN(after - before).eq(stakeAmount))
+  })
+
+  it('should leave a deal with the current layout as it is', async () => {
+    const before = await program.provider.connection.getAccountInfo(dealPda)
+
+    await program.methods
+      .migrateDeal()
+      .accounts({
+        deal: dealPda,
+        gig: gigPda,
+        signer: client.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc()
+
+    const after = await program.provider.connection.getAccountInfo(dealPda)
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.version === 10)
+    assert.ok(after.data.length === before.data.length)
+    assert.ok(after.lamports === before.lamports)
+  })
+
+  it('should not allow anyone else to migrate the deal', async () => {
+    const stranger = Keypair.generate()
+
+    await program.provider.connection.requestAirdrop(
+      stranger.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    try {
+      await program.methods
+        .migrateDeal()
+        .accounts({
+          deal: dealPda,
+          gig: gigPda,
+          signer: stranger.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([stranger])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'Unauthorized')
+    }
+  })
+
+  const findNonceDealPda = (nonce: BN) =>
+    findProgramAddressSync(
+      [
+        Buffer.from('deal'),
+        client.publicKey.toBytes(),
+        gigPda.toBytes(),
+        nonce.toArrayLike(Buffer, 'le', 8),
+      ],
+      program.programId,
+    )[0]
+  const createNonceDeal = (
+    nonce: BN,
+    clientExemption: PublicKey | null = null,
+    dealOffer = offer,
+    dealIndex: PublicKey | null = null,
+  ) =>
+    program.methods
+      .createDeal({
+        offer: dealOffer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: null,
+        nonce,
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption,
+        freelancerTokenAccount: null,
+        referrerActivity: null,
+        dealIndex,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: findNonceDealPda(nonce),
+        gig: gigPda,
+        mint: null,
+        owner: client.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+  it('should allow the client several deals for the same gig', async () => {
+    const nonce = new BN(1)
+    const nonceDealPda = findNonceDealPda(nonce)
+    const createDeal = () => createNonceDeal(nonce)
+
+    await createDeal()
+
+    const deal = await program.account.deal.fetch(nonceDealPda)
+
+    assert.ok(deal.nonce.eq(nonce))
+    assert.ok(deal.client.equals(client.publicKey))
+    assert.ok(deal.gig.equals(gigPda))
+    assert.ok((await program.account.deal.fetchNullable(dealPda)) !== null)
+
+    // The nonce is taken as long as the deal exists
+    try {
+      await createDeal()
+
+      assert.ok(false)
+    } catch (e) {
+      assert.ok(e.logs.some((log: string) => log.includes('already in use')))
+    }
+  })
+
+  it('should charge exempted clients the fee set for them', async () => {
+    const nonce = new BN(2)
+    const [exemptionPda] = findProgramAddressSync(
+      [Buffer.from('exemption'), client.publicKey.toBytes()],
+      program.programId,
+    )
+    const setExemption = (overrideFeeBps: number | null) =>
+      program.methods
+        .setExemption({ client: client.publicKey, overrideFeeBps })
+        .accounts({
+          exemption: exemptionPda,
+          config: configPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc()
+
+    await setExemption(0)
+
+    try {
+      await createNonceDeal(nonce, exemptionPda)
+
+      const deal = await program.account.deal.fetch(findNonceDealPda(nonce))
+
+      assert.ok(deal.clientFee.eqn(0))
+      assert.ok(deal.totalEscrow.eq(offer))
+
+      const quote = await program.methods
+        .quoteDeal({ offer })
+        .accounts({
+          gig: gigPda,
+          mint: null,
+          config: configPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientExemption: exemptionPda,
+        })
+        .view()
+
+      assert.ok(quote.clientFee.eqn(0))
+    } finally {
+      await setExemption(null)
+    }
+
+    const exemption = await program.account.exemption.fetch(exemptionPda)
+
+    assert.ok(exemption.overrideFeeBps === null)
+  })
+
+  const acceptNonceDeal = (nonce: BN) =>
+    program.methods
+      .acceptDeal({ termsHash: Array.from(Buffer.alloc(32)) })
+      .accounts({
+        gig: gigPda,
+        deal: findNonceDealPda(nonce),
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+  const completeNonceDeal = (
+    nonce: BN,
+    freelancerVault: PublicKey | null = null,
+    dealIndex: PublicKey | null = null,
+  ) =>
+    program.methods
+      .completeDeal()
+      .accounts({
+        client: client.publicKey,
+        deal: findNonceDealPda(nonce),
+        escrow: null,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: null,
+        signer: client.publicKey,
+        referrer: null,
+        clientTokenAccount: null,
+        referrerTokenAccount: null,
+        referrerRewardsVault: null,
+        referrerPayoutMint: null,
+        priceFeed: null,
+        config: configPda,
+        stats: statsPda,
+        freelancer: freelancer.publicKey,
+        freelancerTokenAccount: null,
+        freelancerVault,
+        freelancerVaultTokenAccount: null,
+        dealIndex,
+        treasury: configPda,
+        treasuryTokenAccount: null,
+        reputation: findReputationPda(freelancer.publicKey),
+        systemProgram: SystemProgram.programId,
+        associatedTokenProgram: null,
+        tokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+  it('should leave nothing in escrow when the fees do not divide evenly', async () => {
+    const nonce = new BN(3)
+    const nonceDealPda = findNonceDealPda(nonce)
+
+    // Neither fee percentage divides the offer, the split rounds down
+    await createNonceDeal(nonce, null, offer.addn(33))
+
+    await acceptNonceDeal(nonce)
+    await completeNonceDeal(nonce)
+
+    const deal = await program.account.deal.fetch(nonceDealPda)
+    const dealInfo =
+      await program.provider.connection.getAccountInfo(nonceDealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(deal.state === 11)
+    assert.ok(dealInfo.lamports === rent)
+  })
+
+  const [vaultPda] = findProgramAddressSync(
+    [Buffer.from('freelancer_vault'), freelancer.publicKey.toBytes()],
+    program.programId,
+  )
+
+  const setDeferred = (deferFreelancerPayouts: boolean) =>
+    program.methods
+      .updateConfig({
+        clientFeePercentage: null,
+        freelancerFeePercentage: null,
+        clientFeeMin: null,
+        arbiter: null,
+        maxDealOffer: null,
+        feeTiers: null,
+        refundGracePeriod: null,
+        roundFeesUp: null,
+        referrerCooldown: null,
+        deferFreelancerPayouts,
+        maxDeadlineHorizon: null,
+        freelancerCancelPenaltyBps: null,
+        requireApprovalAbove: null,
+        settlementDelay: null,
+        maxTvl: null,
+        clientCancelWindow: null,
+        disputeTimeout: null,
+        splitEscrow: null,
+      })
+      .accounts({
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+  it('should collect the pay in the freelancer vault while payouts are deferred', async () => {
+    const nonce = new BN(4)
+
+    await createNonceDeal(nonce)
+    await acceptNonceDeal(nonce)
+    await setDeferred(true)
+
+    try {
+      await completeNonceDeal(nonce, vaultPda)
+    } finally {
+      await setDeferred(false)
+    }
+
+    const deal = await program.account.deal.fetch(findNonceDealPda(nonce))
+    const vault = await program.account.freelancerVault.fetch(vaultPda)
+
+    assert.ok(vault.freelancer.equals(freelancer.publicKey))
+    assert.ok(vault.totalDeposited.eq(deal.freelancerNet))
+
+    const before = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+
+    await program.methods
+      .withdrawEarnings()
+      .accounts({
+        freelancerVault: vaultPda,
+        freelancer: freelancer.publicKey,
+        mint: null,
+        vaultTokenAccount: null,
+        destinationTokenAccount: null,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const after = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const withdrawn = await program.account.freelancerVault.fetch(vaultPda)
+
+    assert.ok(withdrawn.totalWithdrawn.eq(deal.freelancerNet))
+    assert.ok(after > before)
+  })
+
+  it('should list the deal in the freelancer index until it is settled', async () => {
+    const nonce = new BN(5)
+    const nonceDealPda = findNonceDealPda(nonce)
+    const [dealIndexPda] = findProgramAddressSync(
+      [Buffer.from('deal_index'), freelancer.publicKey.toBytes()],
+      program.programId,
+    )
+
+    await createNonceDeal(nonce, null, offer, dealIndexPda)
+
+    const created = await program.account.dealIndex.fetch(dealIndexPda)
+    const entry = () =>
+      program.account.dealIndex
+        .fetch(dealIndexPda)
+        .then((index) =>
+          index.entries.find((entry) => entry.deal.equals(nonceDealPda)),
+        )
+
+    assert.ok(created.freelancer.equals(freelancer.publicKey))
+    assert.ok((await entry()).state === 1)
+
+    await acceptNonceDeal(nonce)
+    await completeNonceDeal(nonce, null, dealIndexPda)
+
+    const index = await program.account.dealIndex.fetch(dealIndexPda)
+
+    assert.ok(index.entries.length === created.entries.length)
+    assert.ok((await entry()).state === 11)
+  })
+
+  it('should hold the payout until the settlement delay has passed', async () => {
+    const nonce = new BN(6)
+    const nonceDealPda = findNonceDealPda(nonce)
+    const setDelay = (settlementDelay: BN) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+    const finalizeSettlement = () =>
+      program.methods
+        .finalizeSettlement()
+        .accounts({
+          client: client.publicKey,
+          deal: nonceDealPda,
+          escrow: null,
+          feeEscrow: null,
+          gig: gigPda,
+          mint: null,
+          signer: freelancer.publicKey,
+          referrer: null,
+          clientTokenAccount: null,
+          referrerTokenAccount: null,
+          referrerRewardsVault: null,
+          referrerPayoutMint: null,
+          priceFeed: null,
+          config: configPda,
+          stats: statsPda,
+          freelancer: freelancer.publicKey,
+          freelancerTokenAccount: null,
+          freelancerVault: null,
+          freelancerVaultTokenAccount: null,
+          dealIndex: null,
+          treasury: configPda,
+          treasuryTokenAccount: null,
+          reputation: findReputationPda(freelancer.publicKey),
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: null,
+          tokenProgram: null,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([freelancer])
+        .rpc()
+
+    await createNonceDeal(nonce)
+    await acceptNonceDeal(nonce)
+    await setDelay(new BN(2))
+
+    try {
+      await completeNonceDeal(nonce)
+    } finally {
+      await setDelay(new BN(0))
+    }
+
+    const pending = await program.account.deal.fetch(nonceDealPda)
+
+    assert.ok(pending.state === 15)
+    assert.ok(pending.settleAfter.gt(pending.timeCompleted))
+
+    try {
+      await finalizeSettlement()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'SettlementDelayNotElapsed')
+    }
+
+    await wait(3000)
+    await finalizeSettlement()
+
+    const deal = await program.account.deal.fetch(nonceDealPda)
+
+    assert.ok(deal.state === 11)
+    assert.ok(deal.timeCompleted.eq(pending.timeCompleted))
+  })
+
+  const mutualSettleNonceDeal = (
+    nonce: BN,
+    freelancerVault: PublicKey | null = null,
+  ) =>
+    program.methods
+      .mutualSettle({ freelancerBps: 5000 })
+      .accounts({
+        deal: findNonceDealPda(nonce),
+        gig: gigPda,
+        client: client.publicKey,
+        freelancer: freelancer.publicKey,
+        mint: null,
+        escrow: null,
+        feeEscrow: null,
+        ownerWallet: null,
+        freelancerTokenAccount: null,
+        freelancerVault,
+        freelancerVaultTokenAccount: null,
+        treasury: configPda,
+        treasuryTokenAccount: null,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        dealIndex: null,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client, freelancer])
+      .rpc()
+
+  it('should settle on a split both parties agreed to', async () => {
+    const nonce = new BN(7)
+    const nonceDealPda = findNonceDealPda(nonce)
+
+    await createNonceDeal(nonce)
+    await acceptNonceDeal(nonce)
+
+    const clientBefore = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    await mutualSettleNonceDeal(nonce)
+
+    const deal = await program.account.deal.fetch(nonceDealPda)
+    const dealInfo =
+      await program.provider.connection.getAccountInfo(nonceDealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+    const clientAfter = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    assert.ok(deal.state === 11)
+    assert.ok(dealInfo.lamports === rent)
+    assert.ok(clientAfter - clientBefore >= offer.divn(2).toNumber())
+  })
+
+  it('should collect the settled share in the freelancer vault while payouts are deferred', async () => {
+    const nonce = new BN(8)
+
+    await createNonceDeal(nonce)
+    await acceptNonceDeal(nonce)
+
+    const reputationPda = findReputationPda(freelancer.publicKey)
+    const vaultBefore = await program.account.freelancerVault.fetch(vaultPda)
+    const reputationBefore = await program.account.reputation.fetch(
+      reputationPda,
+    )
+
+    await setDeferred(true)
+
+    try {
+      await mutualSettleNonceDeal(nonce, vaultPda)
+    } finally {
+      await setDeferred(false)
+    }
+
+    const deal = await program.account.deal.fetch(findNonceDealPda(nonce))
+    const vault = await program.account.freelancerVault.fetch(vaultPda)
+    const reputation = await program.account.reputation.fetch(reputationPda)
+    const earned = reputation.totalEarned.sub(reputationBefore.totalEarned)
+
+    assert.ok(deal.state === 11)
+    assert.ok(earned.gtn(0))
+    assert.ok(vault.totalDeposited.sub(vaultBefore.totalDeposited).eq(earned))
+  })
+
+  it('should not allow clients without enough completed deals', async () => {
+    const vettedGigId = Keypair.generate()
+    const [vettedGigPda] = findProgramAddressSync(
+      [
+        Buffer.from('gig'),
+        freelancer.publicKey.toBytes(),
+        vettedGigId.publicKey.toBytes().slice(0, 8),
+      ],
+      program.programId,
+    )
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 1,
+      })
+      .accounts({
+        id: vettedGigId.publicKey,
+        gig: vettedGigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, vettedGigId])
+      .rpc()
+
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash: Array.from(Buffer.alloc(32)),
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: null,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: findProgramAddressSync(
+            [
+              Buffer.from('deal'),
+              client.publicKey.toBytes(),
+              vettedGigPda.toBytes(),
+            ],
+            program.programId,
+          )[0],
+          gig: vettedGigPda,
+          mint: null,
+          owner: client.publicKey,
+          ownerWallet: null,
+          escrow: null,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+          associatedTokenProgram: null,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'ClientReputationTooLow')
+    }
+  })
+
+  it('should forfeit part of the stake when the freelancer cancels', async () => {
+    const nonce = new BN(1)
+    const [nonceDealPda] = findProgramAddressSync(
+      [
+        Buffer.from('deal'),
+        client.publicKey.toBytes(),
+        stakedGigPda.toBytes(),
+        nonce.toArrayLike(Buffer, 'le', 8),
+      ],
+      program.programId,
+    )
+    const setPenalty = (freelancerCancelPenaltyBps: number) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+    await program.methods
+      .createDeal({
+        offer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: null,
+        nonce,
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: null,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: nonceDealPda,
+        gig: stakedGigPda,
+        mint: null,
+        owner: client.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+    await program.methods
+      .acceptDeal({ termsHash: Array.from(Buffer.alloc(32)) })
+      .accounts({
+        gig: stakedGigPda,
+        deal: nonceDealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const { totalEscrow, stakeAmount } = await program.account.deal.fetch(
+      nonceDealPda,
+    )
+    const { cancelledDeals } = await program.account.reputation.fetch(
+      findReputationPda(freelancer.publicKey),
+    )
+    const before = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    await setPenalty(50_00)
+
+    try {
+      await program.methods
+        .freelancerCancel()
+        .accounts({
+          deal: nonceDealPda,
+          gig: stakedGigPda,
+          freelancer: freelancer.publicKey,
+          client: client.publicKey,
+          freelancerTokenAccount: null,
+          escrow: null,
+          feeEscrow: null,
+          ownerWallet: null,
+          mint: null,
+          config: configPda,
+          reputation: findReputationPda(freelancer.publicKey),
+          dealIndex: null,
+          stats: statsPda,
+          tokenProgram: null,
+        })
+        .signers([freelancer])
+        .rpc()
+    } finally {
+      await setPenalty(0)
+    }
+
+    const after = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+    const deal = await program.account.deal.fetch(nonceDealPda)
+    const reputation = await program.account.reputation.fetch(
+      findReputationPda(freelancer.publicKey),
+    )
+
+    assert.ok(deal.state === 5)
+    assert.ok(new BN(after - before).eq(totalEscrow.add(stakeAmount.divn(2))))
+    assert.ok(reputation.cancelledDeals.eq(cancelledDeals.addn(1)))
+  })
+})
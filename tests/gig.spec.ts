@@ -2,6 +2,8 @@ import { AnchorError } from '@coral-xyz/anchor'
 import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
   TOKEN_PROGRAM_ID,
 } from '@solana/spl-token'
 import {
@@ -14,10 +16,20 @@ import {
 import { BN } from 'bn.js'
 import { assert } from 'chai'
 import { TokenMint } from '../scripts/utils'
-import { authority, configPda, program, treasury, wait } from './common'
+import {
+  authority,
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  mockStrategy,
+  program,
+  statsPda,
+  wait,
+} from './common'
 
 let freelancer = Keypair.generate()
 let client = Keypair.generate()
+let referrer = Keypair.generate()
 let gigId = Keypair.generate()
 let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
 let [gigPda] = findProgramAddressSync(
@@ -37,6 +49,7 @@ let usdc: TokenMint
 describe('Gig & Deal interaction', () => {
   const offer = new BN(100_000_000)
   const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+  const termsHash = Array.from(Buffer.alloc(32, 3))
 
   before(async () => {
     const airdropAmount = 2 * LAMPORTS_PER_SOL
@@ -63,15 +76,124 @@ describe('Gig & Deal interaction', () => {
 
     clientUsdc = await usdc.getAssociatedTokenAccount(client.publicKey)
     freelancerUsdc = await usdc.getAssociatedTokenAccount(freelancer.publicKey)
-    treasuryUsdc = await usdc.getAssociatedTokenAccount(treasury.publicKey)
+    // Fees are held by the config PDA
+    treasuryUsdc = (
+      await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        authority,
+        usdc.token,
+        configPda,
+        true,
+      )
+    ).address
     dealEscrowPda = await usdc.getAssociatedTokenAccount(dealPda, true)
 
     await usdc.mintInto(clientUsdc, 1_000_000_000)
   })
 
+  it('should not allow a gig without an asking price', async () => {
+    const id = Keypair.generate()
+    const [pda] = findProgramAddressSync(
+      [
+        Buffer.from('gig'),
+        freelancer.publicKey.toBytes(),
+        id.publicKey.toBytes().slice(0, 8),
+      ],
+      program.programId,
+    )
+
+    try {
+      await program.methods
+        .createGig({
+          asking: new BN(0),
+          category: 0,
+          skills: new BN(0),
+          minCompletionTime: new BN(60 * 24),
+          metadataHash: Array.from(Buffer.alloc(32)),
+          metadataUriLen: 0,
+          metadataUri: Array.from(Buffer.alloc(128)),
+          maxConcurrentDeals: 0,
+          maxAsking: null,
+          latePenaltyBps: 0,
+          requiredStake: new BN(0),
+          cancelFeeBps: 0,
+          minClientCompletedDeals: 0,
+        })
+        .accounts({
+          id: id.publicKey,
+          gig: pda,
+          mint: usdc.token,
+          owner: freelancer.publicKey,
+          payer: freelancer.publicKey,
+          config: configPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([freelancer, id])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidAsking')
+    }
+  })
+
+  it('should not allow a metadata URI with bytes past its length', async () => {
+    const id = Keypair.generate()
+    const [pda] = findProgramAddressSync(
+      [
+        Buffer.from('gig'),
+        freelancer.publicKey.toBytes(),
+        id.publicKey.toBytes().slice(0, 8),
+      ],
+      program.programId,
+    )
+
+    try {
+      await program.methods
+        .createGig({
+          asking: new BN(100_000_000),
+          category: 0,
+          skills: new BN(0),
+          minCompletionTime: new BN(60 * 24),
+          metadataHash: Array.from(Buffer.alloc(32)),
+          metadataUriLen: 4,
+          metadataUri: Array.from(Buffer.alloc(128, 1)),
+          maxConcurrentDeals: 0,
+          maxAsking: null,
+          latePenaltyBps: 0,
+          requiredStake: new BN(0),
+          cancelFeeBps: 0,
+          minClientCompletedDeals: 0,
+        })
+        .accounts({
+          id: id.publicKey,
+          gig: pda,
+          mint: usdc.token,
+          owner: freelancer.publicKey,
+          payer: freelancer.publicKey,
+          config: configPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([freelancer, id])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidMetadataUri')
+    }
+  })
+
   it('should allow the freelancer to create a gig', async () => {
     const asking = new BN(100_000_000)
     const minCompletionTime = new BN(60 * 24)
+    const metadataHash = Array.from(Buffer.alloc(32, 7))
+    const uri = Buffer.from('ar://deezjobs-gig-metadata')
+    const metadataUri = Buffer.alloc(128)
+    uri.copy(metadataUri)
 
     await program.methods
       .createGig({
@@ -79,6 +201,15 @@ describe('Gig & Deal interaction', () => {
         category: 0,
         skills: new BN(0),
         minCompletionTime,
+        metadataHash,
+        metadataUriLen: uri.length,
+        metadataUri: Array.from(metadataUri),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
       })
       .accounts({
         id: gigId.publicKey,
@@ -86,6 +217,7 @@ describe('Gig & Deal interaction', () => {
         mint: usdc.token,
         owner: freelancer.publicKey,
         payer: freelancer.publicKey,
+        config: configPda,
         systemProgram: SystemProgram.programId,
       })
       .signers([freelancer, gigId])
@@ -95,89 +227,119 @@ describe('Gig & Deal interaction', () => {
 
     assert.ok(gig.asking.eq(asking))
     assert.ok(gig.minCompletionTime.eq(minCompletionTime))
+    assert.deepEqual(gig.metadataHash, metadataHash)
+    assert.ok(
+      Buffer.from(gig.metadataUri.slice(0, gig.metadataUriLen)).equals(uri),
+    )
   })
 
-  it('should allow the client to offer a deal', async () => {
+  it('should create a gig from a template with overrides', async () => {
+    const templateId = new BN(1)
+    const [templatePda] = findProgramAddressSync(
+      [
+        Buffer.from('gig_template'),
+        freelancer.publicKey.toBytes(),
+        templateId.toArrayLike(Buffer, 'le', 8),
+      ],
+      program.programId,
+    )
+    const templateGigId = Keypair.generate()
+    const [templateGigPda] = findProgramAddressSync(
+      [
+        Buffer.from('gig'),
+        freelancer.publicKey.toBytes(),
+        templateGigId.publicKey.toBytes().slice(0, 8),
+      ],
+      program.programId,
+    )
+
     await program.methods
-      .createDeal({
-        offer,
-        deadline,
-        referrer: null,
+      .updateTemplate({
+        templateId,
+        category: 2,
+        asking: offer,
+        minCompletionTime: new BN(60 * 24),
+        latePenaltyBps: 500,
+        cancelFeeBps: 1000,
       })
       .accounts({
-        config: configPda,
-        deal: dealPda,
-        gig: gigPda,
+        template: templatePda,
+        owner: freelancer.publicKey,
         mint: usdc.token,
-        owner: client.publicKey,
-        ownerWallet: clientUsdc,
-        escrow: dealEscrowPda,
+        config: configPda,
         systemProgram: SystemProgram.programId,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        rent: SYSVAR_RENT_PUBKEY,
       })
-      .signers([client])
+      .signers([freelancer])
       .rpc()
 
-    const deal = await program.account.deal.fetch(dealPda)
-
-    const escrowBalance =
-      await program.provider.connection.getTokenAccountBalance(dealEscrowPda)
-
-    const clientBalance =
-      await program.provider.connection.getTokenAccountBalance(clientUsdc)
-
-    assert.ok(deal.offer.eq(offer))
-    assert.ok(deal.deadline.eq(deadline))
-    assert.ok(escrowBalance.value.uiAmountString === '103')
-    assert.ok(clientBalance.value.uiAmountString === '897')
-  })
-
-  it('should allow the client to cancel a deal', async () => {
     await program.methods
-      .closeDeal()
+      .createGigFromTemplate({
+        category: null,
+        asking: offer.muln(2),
+        minCompletionTime: null,
+        latePenaltyBps: null,
+        cancelFeeBps: 0,
+        skills: new BN(0),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        requiredStake: new BN(0),
+        minClientCompletedDeals: 0,
+      })
       .accounts({
-        client: client.publicKey,
-        deal: dealPda,
-        escrow: dealEscrowPda,
-        gig: gigPda,
-        mint: usdc.token,
-        ownerWallet: clientUsdc,
-        signer: client.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        gig: templateGigPda,
+        template: templatePda,
+        payer: freelancer.publicKey,
+        owner: freelancer.publicKey,
+        id: templateGigId.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
       })
-      .signers([client])
+      .signers([freelancer, templateGigId])
       .rpc()
 
-    const deal = await program.account.deal.fetchNullable(dealPda)
-
-    const dealEscrow = await program.provider.connection.getAccountInfo(
-      dealEscrowPda,
-    )
-
-    const clientBalance =
-      await program.provider.connection.getTokenAccountBalance(clientUsdc)
+    const gig = await program.account.gig.fetch(templateGigPda)
 
-    assert.ok(dealEscrow === null)
-    assert.ok(deal === null)
-    assert.ok(clientBalance.value.uiAmountString === '1000')
+    assert.ok(gig.mint.equals(usdc.token))
+    assert.ok(gig.category === 2)
+    assert.ok(gig.asking.eq(offer.muln(2)))
+    assert.ok(gig.latePenaltyBps === 500)
+    assert.ok(gig.cancelFeeBps === 0)
   })
 
-  it('should allow the client to reopen the deal', async () => {
-    await program.methods
+  const createDealWithReferrer = (referrer: PublicKey) =>
+    program.methods
       .createDeal({
         offer,
         deadline,
-        referrer: null,
+        referrer,
+        milestones: [],
+        maxClientFee: null,
+        termsHash,
+        deadlineFromAccept: null,
+        nonce: new BN(0),
       })
       .accounts({
         config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: freelancerUsdc,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
         deal: dealPda,
         gig: gigPda,
         mint: usdc.token,
         owner: client.publicKey,
         ownerWallet: clientUsdc,
         escrow: dealEscrowPda,
+        feeEscrow: null,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
         rent: SYSVAR_RENT_PUBKEY,
@@ -185,70 +347,139 @@ describe('Gig & Deal interaction', () => {
       .signers([client])
       .rpc()
 
-    const deal = await program.account.deal.fetch(dealPda)
-
-    const escrowBalance =
-      await program.provider.connection.getTokenAccountBalance(dealEscrowPda)
+  it('should not allow the client to refer a deal to themselves', async () => {
+    try {
+      await createDealWithReferrer(client.publicKey)
 
-    const clientBalance =
-      await program.provider.connection.getTokenAccountBalance(clientUsdc)
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
 
-    assert.ok(deal.offer.eq(offer))
-    assert.ok(deal.deadline.eq(deadline))
-    assert.ok(escrowBalance.value.uiAmountString === '103')
-    assert.ok(clientBalance.value.uiAmountString === '897')
+      assert.ok(err.error.errorCode.code === 'InvalidReferrer')
+    }
   })
 
-  it('should allow the freelancer to accept a deal', async () => {
-    await program.methods
-      .acceptDeal()
-      .accounts({
-        deal: dealPda,
-        freelancer: freelancer.publicKey,
-        gig: gigPda,
-      })
-      .signers([freelancer])
-      .rpc()
+  it('should not allow the freelancer to refer a deal to themselves', async () => {
+    try {
+      await createDealWithReferrer(freelancer.publicKey)
 
-    const deal = await program.account.deal.fetch(dealPda)
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
 
-    assert.ok((deal.state & 2) === 2)
+      assert.ok(err.error.errorCode.code === 'InvalidReferrer')
+    }
   })
 
-  it('should not allow the freelancer to close a gig with a pending deal', async () => {
-    try {
-      await program.methods
-        .closeGig()
+  it('should only allow the referrers the freelancer approved', async () => {
+    const setAllowedReferrers = (allowedReferrers: PublicKey[]) =>
+      program.methods
+        .updateGig({ asking: null, paused: null, allowedReferrers, mint: null })
         .accounts({
           gig: gigPda,
           owner: freelancer.publicKey,
-          payer: freelancer.publicKey,
           config: configPda,
         })
         .signers([freelancer])
         .rpc()
 
+    await setAllowedReferrers([referrer.publicKey])
+
+    try {
+      await createDealWithReferrer(Keypair.generate().publicKey)
+
       assert.ok(false)
     } catch (e) {
       const err = e as AnchorError
 
-      assert.ok(err.error.errorCode.code === 'ConstraintRaw')
+      assert.ok(err.error.errorCode.code === 'ReferrerNotAllowed')
+    } finally {
+      await setAllowedReferrers([])
     }
   })
 
-  it('should not allow the client to close an accepted deal', async () => {
+  it('should reject a zero offer', async () => {
     try {
       await program.methods
-        .closeDeal()
+        .createDeal({
+          offer: new BN(0),
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash,
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
         .accounts({
-          client: client.publicKey,
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: freelancerUsdc,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
           deal: dealPda,
+          gig: gigPda,
+          mint: usdc.token,
+          owner: client.publicKey,
+          ownerWallet: clientUsdc,
           escrow: dealEscrowPda,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidOffer')
+    }
+  })
+
+  it('should reject a freelancer token account of another wallet', async () => {
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash,
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: clientUsdc,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: dealPda,
           gig: gigPda,
           mint: usdc.token,
+          owner: client.publicKey,
           ownerWallet: clientUsdc,
-          signer: client.publicKey,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
         })
         .signers([client])
         .rpc()
@@ -257,44 +488,1010 @@ describe('Gig & Deal interaction', () => {
     } catch (e) {
       const err = e as AnchorError
 
-      assert.ok(err.error.errorCode.code === 'ConstraintRaw')
+      assert.ok(err.error.errorCode.code === 'ConstraintTokenOwner')
     }
   })
 
-  it('should allow the client to complete the deal', async () => {
-    await program.methods
-      .completeDeal()
-      .accounts({
-        client: client.publicKey,
-        deal: dealPda,
-        escrow: dealEscrowPda,
-        gig: gigPda,
-        mint: usdc.token,
-        signer: client.publicKey,
-        referrer: null,
-        referrerTokenAccount: null,
-        config: configPda,
-        freelancer: freelancer.publicKey,
-        freelancerTokenAccount: freelancerUsdc,
-        treasury: treasury.publicKey,
-        treasuryTokenAccount: treasuryUsdc,
-        systemProgram: SystemProgram.programId,
-        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        rent: SYSVAR_RENT_PUBKEY,
-      })
-      .signers([client])
-      .rpc()
+  it('should reject an owner wallet of another mint', async () => {
+    const otherMint = await createMint(
+      program.provider.connection,
+      authority,
+      authority.publicKey,
+      null,
+      6,
+    )
+    const clientOtherWallet = (
+      await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        authority,
+        otherMint,
+        client.publicKey,
+      )
+    ).address
 
-    const freelancerBalance =
-      await program.provider.connection.getTokenAccountBalance(freelancerUsdc)
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash,
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: freelancerUsdc,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: dealPda,
+          gig: gigPda,
+          mint: usdc.token,
+          owner: client.publicKey,
+          ownerWallet: clientOtherWallet,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([client])
+        .rpc()
 
-    assert.ok(freelancerBalance.value.uiAmountString === '95')
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'MintMismatch')
+    }
+  })
+
+  it('should reject an owner wallet of another wallet', async () => {
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash,
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: freelancerUsdc,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: dealPda,
+          gig: gigPda,
+          mint: usdc.token,
+          owner: client.publicKey,
+          ownerWallet: freelancerUsdc,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'WalletOwnerMismatch')
+    }
+  })
+
+  it('should reject a client without the funds for the offer', async () => {
+    const poorClient = Keypair.generate()
+    const [poorDealPda] = findProgramAddressSync(
+      [Buffer.from('deal'), poorClient.publicKey.toBytes(), gigPda.toBytes()],
+      program.programId,
+    )
+
+    await program.provider.connection.requestAirdrop(
+      poorClient.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    const poorClientUsdc = await usdc.getAssociatedTokenAccount(
+      poorClient.publicKey,
+    )
+
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash,
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: freelancerUsdc,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(poorClient.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: poorDealPda,
+          gig: gigPda,
+          mint: usdc.token,
+          owner: poorClient.publicKey,
+          ownerWallet: poorClientUsdc,
+          escrow: await usdc.getAssociatedTokenAccount(poorDealPda, true),
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([poorClient])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InsufficientFunds')
+    }
+  })
+
+  it('should reject offers that overflow the escrow amount', async () => {
+    try {
+      await program.methods
+        .createDeal({
+          offer: new BN('18446744073709551000'),
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash,
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: freelancerUsdc,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: dealPda,
+          gig: gigPda,
+          mint: usdc.token,
+          owner: client.publicKey,
+          ownerWallet: clientUsdc,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'MathOverflow')
+    }
+  })
+
+  it('should reject a client fee above the max the client accepts', async () => {
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: new BN(0),
+          termsHash,
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: freelancerUsdc,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: dealPda,
+          gig: gigPda,
+          mint: usdc.token,
+          owner: client.publicKey,
+          ownerWallet: clientUsdc,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'FeeExceedsMax')
+    }
+  })
+
+  it('should allow the client to offer a deal', async () => {
+    await createDealWithReferrer(referrer.publicKey)
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    const escrowBalance =
+      await program.provider.connection.getTokenAccountBalance(dealEscrowPda)
+
+    const clientBalance =
+      await program.provider.connection.getTokenAccountBalance(clientUsdc)
+
+    assert.ok(deal.offer.eq(offer))
+    assert.ok(deal.deadline.eq(deadline))
+    assert.ok(deal.referrer.equals(referrer.publicKey))
+    assert.ok(deal.freelancerNet.eqn(95_000_000))
+    assert.ok(deal.mint.equals(usdc.token))
+    assert.ok(escrowBalance.value.uiAmountString === '103')
+    assert.ok(clientBalance.value.uiAmountString === '897')
+  })
+
+  it('should allow the client to fix the referrer before acceptance', async () => {
+    const setReferrer = (referrer: PublicKey | null) =>
+      program.methods
+        .setReferrer({ referrer })
+        .accounts({
+          deal: dealPda,
+          gig: gigPda,
+          client: client.publicKey,
+          config: configPda,
+          referrerActivity: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc()
+
+    try {
+      await setReferrer(freelancer.publicKey)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidReferrer')
+    }
+
+    await setReferrer(null)
+
+    let deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.referrer === null)
+
+    await setReferrer(referrer.publicKey)
+
+    deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.referrer.equals(referrer.publicKey))
+  })
+
+  it('should allow the client to cancel a deal', async () => {
+    await program.methods
+      .closeDeal()
+      .accounts({
+        stats: statsPda,
+        client: client.publicKey,
+        deal: dealPda,
+        escrow: dealEscrowPda,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: usdc.token,
+        ownerWallet: clientUsdc,
+        signer: client.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([client])
+      .rpc()
+
+    const deal = await program.account.deal.fetchNullable(dealPda)
+
+    const dealEscrow = await program.provider.connection.getAccountInfo(
+      dealEscrowPda,
+    )
+
+    const clientBalance =
+      await program.provider.connection.getTokenAccountBalance(clientUsdc)
+
+    assert.ok(dealEscrow === null)
+    assert.ok(deal === null)
+    assert.ok(clientBalance.value.uiAmountString === '1000')
+  })
+
+  it('should allow the client to reopen the deal', async () => {
+    await program.methods
+      .createDeal({
+        offer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash,
+        deadlineFromAccept: null,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: freelancerUsdc,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: dealPda,
+        gig: gigPda,
+        mint: usdc.token,
+        owner: client.publicKey,
+        ownerWallet: clientUsdc,
+        escrow: dealEscrowPda,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    const escrowBalance =
+      await program.provider.connection.getTokenAccountBalance(dealEscrowPda)
+
+    const clientBalance =
+      await program.provider.connection.getTokenAccountBalance(clientUsdc)
+
+    assert.ok(deal.offer.eq(offer))
+    assert.ok(deal.deadline.eq(deadline))
+    assert.ok(escrowBalance.value.uiAmountString === '103')
+    assert.ok(clientBalance.value.uiAmountString === '897')
+  })
+
+  it('should not allow anyone else to accept a deal', async () => {
+    try {
+      await program.methods
+        .acceptDeal({ termsHash })
+        .accounts({
+          deal: dealPda,
+          freelancer: client.publicKey,
+          config: configPda,
+          gig: gigPda,
+          reputation: findReputationPda(freelancer.publicKey),
+          escrow: null,
+          freelancerWallet: null,
+          mint: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'Unauthorized')
+    }
+  })
+
+  it('should not allow accepting different terms than the deal was created with', async () => {
+    try {
+      await program.methods
+        .acceptDeal({ termsHash: Array.from(Buffer.alloc(32, 4)) })
+        .accounts({
+          deal: dealPda,
+          freelancer: freelancer.publicKey,
+          config: configPda,
+          gig: gigPda,
+          reputation: findReputationPda(freelancer.publicKey),
+          escrow: null,
+          freelancerWallet: null,
+          mint: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+        })
+        .signers([freelancer])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'TermsMismatch')
+    }
+  })
+
+  it('should allow the freelancer to accept a deal', async () => {
+    await program.methods
+      .acceptDeal({ termsHash })
+      .accounts({
+        deal: dealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        gig: gigPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.state === 3)
+  })
+
+  it('should park the escrow in the yield strategy and pay the yield to the treasury', async () => {
+    const setStrategy = (strategy: PublicKey | null) =>
+      program.methods
+        .setYieldStrategy({ strategy })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+    const [vaultAuthority] = findProgramAddressSync(
+      [Buffer.from('vault'), dealPda.toBytes()],
+      mockStrategy.programId,
+    )
+    const vault = (
+      await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        authority,
+        usdc.token,
+        vaultAuthority,
+        true,
+      )
+    ).address
+    const strategyAccounts = [
+      { pubkey: vaultAuthority, isSigner: false, isWritable: false },
+      { pubkey: vault, isSigner: false, isWritable: true },
+    ]
+
+    const balance = async (tokenAccount: PublicKey) =>
+      new BN(
+        (
+          await program.provider.connection.getTokenAccountBalance(tokenAccount)
+        ).value.amount,
+      )
+
+    const escrowBefore = await balance(dealEscrowPda)
+    const treasuryBefore = await balance(treasuryUsdc)
+    const yieldAmount = 1_000_000
+
+    await setStrategy(mockStrategy.programId)
+
+    try {
+      await program.methods
+        .depositEscrowToStrategy({ amount: escrowBefore })
+        .accounts({
+          deal: dealPda,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          mint: usdc.token,
+          config: configPda,
+          authority: authority.publicKey,
+          strategy: mockStrategy.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(strategyAccounts)
+        .signers([authority])
+        .rpc()
+
+      let deal = await program.account.deal.fetch(dealPda)
+
+      assert.ok(deal.strategyDeposited.eq(escrowBefore))
+      assert.ok((await balance(dealEscrowPda)).eqn(0))
+
+      await usdc.mintInto(vault, yieldAmount)
+
+      await program.methods
+        .withdrawEscrowFromStrategy()
+        .accounts({
+          deal: dealPda,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          mint: usdc.token,
+          config: configPda,
+          signer: client.publicKey,
+          strategy: mockStrategy.programId,
+          treasury: configPda,
+          treasuryTokenAccount: treasuryUsdc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(strategyAccounts)
+        .signers([client])
+        .rpc()
+
+      deal = await program.account.deal.fetch(dealPda)
+
+      assert.ok(deal.strategyDeposited.eqn(0))
+      assert.ok((await balance(dealEscrowPda)).eq(escrowBefore))
+      assert.ok(
+        (await balance(treasuryUsdc)).eq(treasuryBefore.addn(yieldAmount)),
+      )
+
+      // Keeps the fee assertions of the later tests exact
+      await program.methods
+        .withdrawTreasuryFees({ amount: new BN(yieldAmount) })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+          mint: usdc.token,
+          treasuryTokenAccount: treasuryUsdc,
+          destination: authority.publicKey,
+          destinationTokenAccount: await usdc.getAssociatedTokenAccount(
+            authority.publicKey,
+          ),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc()
+    } finally {
+      await setStrategy(null)
+    }
+  })
+
+  it('should not allow the freelancer to close a gig with a pending deal', async () => {
+    try {
+      await program.methods
+        .closeGig({ reclaimRent: true })
+        .accounts({
+          gig: gigPda,
+          owner: freelancer.publicKey,
+          payer: freelancer.publicKey,
+          config: configPda,
+        })
+        .signers([freelancer])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'GigHasActiveDeals')
+    }
+  })
+
+  it('should not allow the client to close an accepted deal', async () => {
+    try {
+      await program.methods
+        .closeDeal()
+        .accounts({
+          stats: statsPda,
+          client: client.publicKey,
+          deal: dealPda,
+          escrow: dealEscrowPda,
+          feeEscrow: null,
+          gig: gigPda,
+          mint: usdc.token,
+          ownerWallet: clientUsdc,
+          signer: client.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([client])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'ConstraintRaw')
+    }
+  })
+
+  it('should allow the freelancer to submit the work', async () => {
+    const deliveryHash = Array.from(Buffer.alloc(32, 1))
+
+    await program.methods
+      .submitDeliverable({ deliveryHash })
+      .accounts({
+        deal: dealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.state === 4)
+    assert.deepEqual(deal.deliveryHash, deliveryHash)
+  })
+
+  it('should allow the client to request a revision', async () => {
+    await program.methods
+      .requestRevision()
+      .accounts({
+        deal: dealPda,
+        client: client.publicKey,
+        config: configPda,
+      })
+      .signers([client])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.state === 3)
+    assert.ok(deal.revisionCount === 1)
+  })
+
+  it('should allow the freelancer to resubmit the work', async () => {
+    const deliveryHash = Array.from(Buffer.alloc(32, 2))
+
+    await program.methods
+      .submitDeliverable({ deliveryHash })
+      .accounts({
+        deal: dealPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.state === 4)
+    assert.deepEqual(deal.deliveryHash, deliveryHash)
+  })
+
+  it('should not allow finalizing before the review window ends', async () => {
+    try {
+      await program.methods
+        .finalizeDelivery()
+        .accounts({
+          client: client.publicKey,
+          deal: dealPda,
+          escrow: dealEscrowPda,
+          gig: gigPda,
+          mint: usdc.token,
+          signer: freelancer.publicKey,
+          clientTokenAccount: null,
+          referrer: null,
+          referrerTokenAccount: null,
+          referrerRewardsVault: null,
+          referrerPayoutMint: null,
+          priceFeed: null,
+          config: configPda,
+          stats: statsPda,
+          freelancer: freelancer.publicKey,
+          freelancerTokenAccount: freelancerUsdc,
+          freelancerVault: null,
+          freelancerVaultTokenAccount: null,
+          treasury: configPda,
+          treasuryTokenAccount: treasuryUsdc,
+          reputation: findReputationPda(freelancer.publicKey),
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          dealIndex: null,
+        })
+        .signers([freelancer])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'ReviewWindowOpen')
+    }
+  })
+
+  const setFees = (clientFeePercentage: number, freelancerFeePercentage: number) =>
+    program.methods
+      .updateConfig({
+        clientFeePercentage,
+        freelancerFeePercentage,
+        clientFeeMin: null,
+        arbiter: null,
+        maxDealOffer: null,
+        feeTiers: null,
+        refundGracePeriod: null,
+        roundFeesUp: null,
+        referrerCooldown: null,
+        deferFreelancerPayouts: null,
+        maxDeadlineHorizon: null,
+        freelancerCancelPenaltyBps: null,
+        requireApprovalAbove: null,
+        settlementDelay: null,
+        maxTvl: null,
+        clientCancelWindow: null,
+        disputeTimeout: null,
+        splitEscrow: null,
+      })
+      .accounts({
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+  it('should keep the deposited fees when the config changes mid-flight', async () => {
+    const config = await program.account.config.fetch(configPda)
+
+    await setFees(
+      config.clientFeePercentage * 2,
+      config.freelancerFeePercentage * 2,
+    )
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.clientFee.eqn(3_000_000))
+    assert.ok(deal.totalEscrow.eqn(103_000_000))
+  })
+
+  it('should allow the client to complete the deal', async () => {
+    await program.methods
+      .completeDeal()
+      .accounts({
+        client: client.publicKey,
+        deal: dealPda,
+        escrow: dealEscrowPda,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: usdc.token,
+        signer: client.publicKey,
+        referrer: null,
+        clientTokenAccount: null,
+        referrerTokenAccount: null,
+        referrerRewardsVault: null,
+        referrerPayoutMint: null,
+        priceFeed: null,
+        config: configPda,
+        stats: statsPda,
+        freelancer: freelancer.publicKey,
+        freelancerTokenAccount: freelancerUsdc,
+        freelancerVault: null,
+        freelancerVaultTokenAccount: null,
+        treasury: configPda,
+        treasuryTokenAccount: treasuryUsdc,
+        reputation: findReputationPda(freelancer.publicKey),
+        systemProgram: SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        dealIndex: null,
+      })
+      .signers([client])
+      .rpc()
+
+    const freelancerBalance =
+      await program.provider.connection.getTokenAccountBalance(freelancerUsdc)
+
+    const reputation = await program.account.reputation.fetch(
+      findReputationPda(freelancer.publicKey),
+    )
+
+    const treasuryBalance =
+      await program.provider.connection.getTokenAccountBalance(treasuryUsdc)
+
+    // Settled with the fees deposited at creation, not the doubled ones.
+    assert.ok(freelancerBalance.value.uiAmountString === '95')
+    assert.ok(treasuryBalance.value.uiAmountString === '8')
+    assert.ok((await program.account.deal.fetch(dealPda)).penaltyApplied.eqn(0))
+    assert.ok(reputation.completedDeals.eqn(1))
+    assert.ok(reputation.totalEarned.eqn(95_000_000))
+
+    const config = await program.account.config.fetch(configPda)
+
+    await setFees(
+      config.clientFeePercentage / 2,
+      config.freelancerFeePercentage / 2,
+    )
+  })
+
+  const leaveReview = (stars: number) =>
+    program.methods
+      .leaveReview({
+        stars,
+        commentHash: Array.from(Buffer.alloc(32, 3)),
+      })
+      .accounts({
+        deal: dealPda,
+        review: findProgramAddressSync(
+          [Buffer.from('review'), dealPda.toBytes()],
+          program.programId,
+        )[0],
+        reputation: findReputationPda(freelancer.publicKey),
+        client: client.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc()
+
+  it('should not allow a rating above 5 stars', async () => {
+    try {
+      await leaveReview(6)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidRating')
+    }
+  })
+
+  it('should allow the client to review a completed deal', async () => {
+    await leaveReview(5)
+
+    const reputation = await program.account.reputation.fetch(
+      findReputationPda(freelancer.publicKey),
+    )
+
+    assert.ok(reputation.reviewCount.eqn(1))
+    assert.ok(reputation.totalStars.eqn(5))
+  })
+
+  it('should only allow one review per deal', async () => {
+    // The review account already exists, so the system program rejects it before the program runs
+    let failed = false
+
+    try {
+      await leaveReview(4)
+    } catch (e) {
+      failed = true
+    }
+
+    assert.ok(failed)
+  })
+
+  it('should allow the client to tip the freelancer', async () => {
+    await program.methods
+      .tipFreelancer({ amount: new BN(5_000_000) })
+      .accounts({
+        deal: dealPda,
+        gig: gigPda,
+        client: client.publicKey,
+        freelancer: freelancer.publicKey,
+        mint: usdc.token,
+        ownerWallet: clientUsdc,
+        freelancerTokenAccount: freelancerUsdc,
+        reputation: findReputationPda(freelancer.publicKey),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+    const freelancerBalance =
+      await program.provider.connection.getTokenAccountBalance(freelancerUsdc)
+
+    const reputation = await program.account.reputation.fetch(
+      findReputationPda(freelancer.publicKey),
+    )
+
+    // Tips skip the platform fee and aren't counted as earnings.
+    assert.ok(freelancerBalance.value.uiAmountString === '100')
+    assert.ok(reputation.tipsReceived.eqn(5_000_000))
+    assert.ok(reputation.totalEarned.eqn(95_000_000))
+  })
+
+  it('should not allow withdrawing more than the treasury holds', async () => {
+    const treasuryBalance =
+      await program.provider.connection.getTokenAccountBalance(treasuryUsdc)
+
+    try {
+      await program.methods
+        .withdrawTreasuryFees({
+          amount: new BN(treasuryBalance.value.amount).addn(1),
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+          mint: usdc.token,
+          treasuryTokenAccount: treasuryUsdc,
+          destination: authority.publicKey,
+          destinationTokenAccount: await usdc.getAssociatedTokenAccount(
+            authority.publicKey,
+          ),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InsufficientTreasuryBalance')
+    }
+  })
+
+  it('should allow the authority to withdraw the collected fees', async () => {
+    const treasuryBalance =
+      await program.provider.connection.getTokenAccountBalance(treasuryUsdc)
+
+    await program.methods
+      .withdrawTreasuryFees({
+        amount: new BN(treasuryBalance.value.amount),
+      })
+      .accounts({
+        config: configPda,
+        authority: authority.publicKey,
+        mint: usdc.token,
+        treasuryTokenAccount: treasuryUsdc,
+        destination: authority.publicKey,
+        destinationTokenAccount: await usdc.getAssociatedTokenAccount(
+          authority.publicKey,
+        ),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc()
+
+    const treasuryBalanceAfter =
+      await program.provider.connection.getTokenAccountBalance(treasuryUsdc)
+
+    assert.ok(treasuryBalance.value.amount !== '0')
+    assert.ok(treasuryBalanceAfter.value.amount === '0')
   })
 
   it('should allow the freelancer to close a gig', async () => {
     await program.methods
-      .closeGig()
+      .closeGig({ reclaimRent: true })
       .accounts({
         gig: gigPda,
         owner: freelancer.publicKey,
@@ -0,0 +1,1448 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js'
+import { ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID } from '@solana/spl-token'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import { TokenMint } from '../scripts/utils'
+import {
+  authority,
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let client = Keypair.generate()
+let otherClient = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+const findDealPda = (client: PublicKey) =>
+  findProgramAddressSync(
+    [Buffer.from('deal'), client.toBytes(), gigPda.toBytes()],
+    program.programId,
+  )[0]
+let dealPda = findDealPda(client.publicKey)
+const findReferrerActivityPda = (referrer: PublicKey, client: Keypair) =>
+  findProgramAddressSync(
+    [
+      Buffer.from('referrer_activity'),
+      referrer.toBytes(),
+      client.publicKey.toBytes(),
+    ],
+    program.programId,
+  )[0]
+
+describe('Native SOL deals', () => {
+  const offer = new BN(LAMPORTS_PER_SOL / 10)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+
+  const createDeal = (
+    owner = client,
+    dealOffer = offer,
+    referrer: PublicKey | null = null,
+  ) =>
+    program.methods
+      .createDeal({
+        offer: dealOffer,
+        deadline,
+        referrer,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: null,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: null,
+        referrerActivity: referrer && findReferrerActivityPda(referrer, owner),
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(owner.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: findDealPda(owner.publicKey),
+        gig: gigPda,
+        mint: null,
+        owner: owner.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner])
+      .rpc()
+
+  before(async () => {
+    const airdropAmount = 2 * LAMPORTS_PER_SOL
+
+    await program.provider.connection.requestAirdrop(
+      freelancer.publicKey,
+      airdropAmount,
+    )
+
+    await wait(500)
+
+    await program.provider.connection.requestAirdrop(
+      client.publicKey,
+      airdropAmount,
+    )
+
+    await wait(500)
+
+    await program.provider.connection.requestAirdrop(
+      otherClient.publicKey,
+      airdropAmount,
+    )
+
+    await wait(500)
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 1,
+        maxAsking: offer.muln(2),
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+  })
+
+  it('should not allow offers above the max asking price', async () => {
+    try {
+      await createDeal(otherClient, offer.muln(3))
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'OfferTooHigh')
+    }
+  })
+
+  it('should not allow the freelancer to make a deal on their own gig', async () => {
+    try {
+      await createDeal(freelancer)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'SelfDeal')
+    }
+  })
+
+  it('should not take the token path for a gig without a mint', async () => {
+    const usdc = await TokenMint.init(
+      program.provider.connection,
+      authority,
+      authority,
+    )
+    const dealPda = findDealPda(otherClient.publicKey)
+
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [],
+          maxClientFee: null,
+          termsHash: Array.from(Buffer.alloc(32)),
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: null,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(otherClient.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: dealPda,
+          gig: gigPda,
+          mint: usdc.token,
+          owner: otherClient.publicKey,
+          ownerWallet: await usdc.getAssociatedTokenAccount(
+            otherClient.publicKey,
+          ),
+          escrow: await usdc.getAssociatedTokenAccount(dealPda, true),
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([otherClient])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'GigHasNoMint')
+    }
+  })
+
+  it('should not allow a milestone late penalty above 100%', async () => {
+    try {
+      await program.methods
+        .createDeal({
+          offer,
+          deadline,
+          referrer: null,
+          milestones: [
+            {
+              amount: offer,
+              descriptionHash: Array.from(Buffer.alloc(32)),
+              dueTs: deadline,
+              latePenaltyBps: 10001,
+            },
+          ],
+          maxClientFee: null,
+          termsHash: Array.from(Buffer.alloc(32)),
+          deadlineFromAccept: null,
+          nonce: new BN(0),
+        })
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          freelancerTokenAccount: null,
+          referrerActivity: null,
+          dealIndex: null,
+          clientBlacklist: findBlacklistPda(otherClient.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          deal: findDealPda(otherClient.publicKey),
+          gig: gigPda,
+          mint: null,
+          owner: otherClient.publicKey,
+          ownerWallet: null,
+          escrow: null,
+          feeEscrow: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+          associatedTokenProgram: null,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([otherClient])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidLatePenalty')
+    }
+  })
+
+  it('should not allow offers above the platform cap', async () => {
+    const setCap = (maxDealOffer: BN) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+    await setCap(offer.subn(1))
+
+    try {
+      await createDeal()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'OfferExceedsCap')
+    } finally {
+      await setCap(new BN(0))
+    }
+  })
+
+  it('should not allow deals past the escrow cap', async () => {
+    const setTvl = (maxTvl: BN) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+    const stats = await program.account.stats.fetch(statsPda)
+
+    await setTvl(stats.activeEscrowTotal.add(offer).subn(1))
+
+    try {
+      await createDeal()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'TvlCapExceeded')
+    } finally {
+      await setTvl(new BN(0))
+    }
+  })
+
+  it('should not allow deadlines past the platform horizon', async () => {
+    const setHorizon = (maxDeadlineHorizon: BN) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+    // Half as far out as the deadline of the deal
+    await setHorizon(new BN(60 * 24))
+
+    try {
+      await createDeal()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'DeadlineTooFar')
+    } finally {
+      await setHorizon(new BN(0))
+    }
+  })
+
+  const setThreshold = (requireApprovalAbove: BN) =>
+    program.methods
+      .updateConfig({
+        clientFeePercentage: null,
+        freelancerFeePercentage: null,
+        clientFeeMin: null,
+        arbiter: null,
+        maxDealOffer: null,
+        feeTiers: null,
+        refundGracePeriod: null,
+        roundFeesUp: null,
+        referrerCooldown: null,
+        deferFreelancerPayouts: null,
+        maxDeadlineHorizon: null,
+        freelancerCancelPenaltyBps: null,
+        requireApprovalAbove,
+        settlementDelay: null,
+        maxTvl: null,
+        clientCancelWindow: null,
+        disputeTimeout: null,
+        splitEscrow: null,
+      })
+      .accounts({
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+  const fundDeal = () =>
+    program.methods
+      .fundDeal()
+      .accounts({
+        deal: dealPda,
+        escrow: null,
+        feeEscrow: null,
+        ownerWallet: null,
+        mint: null,
+        gig: gigPda,
+        client: client.publicKey,
+        config: configPda,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([client])
+      .rpc()
+
+  it('should hold deals above the approval threshold until approved', async () => {
+    await setThreshold(offer.subn(1))
+
+    try {
+      await createDeal()
+    } finally {
+      await setThreshold(new BN(0))
+    }
+
+    let dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok((await program.account.deal.fetch(dealPda)).state === 13)
+    assert.ok(dealInfo.lamports === rent)
+
+    try {
+      await fundDeal()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InvalidState')
+    }
+
+    await program.methods
+      .approveDeal()
+      .accounts({
+        deal: dealPda,
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+    await fundDeal()
+
+    const deal = await program.account.deal.fetch(dealPda)
+    dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+
+    assert.ok(deal.state === 1)
+    assert.ok(deal.totalEscrow.eq(offer.add(deal.clientFee)))
+    assert.ok(new BN(dealInfo.lamports - rent).eq(deal.totalEscrow))
+
+    // Withdrawn so the deal can be created again below
+    await program.methods
+      .closeDeal()
+      .accounts({
+        stats: statsPda,
+        client: client.publicKey,
+        deal: dealPda,
+        escrow: null,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: null,
+        ownerWallet: null,
+        signer: client.publicKey,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+      })
+      .signers([client])
+      .rpc()
+  })
+
+  let createDealTx: string
+
+  it('should escrow lamports on top of the deal rent', async () => {
+    createDealTx = await createDeal()
+
+    const dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(dealInfo.lamports - rent > offer.toNumber())
+  })
+
+  it('should quote the same fee the deal was charged', async () => {
+    const quote = await program.methods
+      .quoteDeal({ offer })
+      .accounts({
+        gig: gigPda,
+        mint: null,
+        config: configPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientExemption: null,
+      })
+      .view()
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(quote.clientFee.eq(deal.clientFee))
+    assert.ok(quote.totalEscrow.eq(deal.totalEscrow))
+  })
+
+  it('should quote several offers the same as one at a time', async () => {
+    const accounts = {
+      gig: gigPda,
+      mint: null,
+      config: configPda,
+      priceFeed: null,
+      freelancerReputation: null,
+      clientExemption: null,
+    }
+    const offers = [offer, offer.muln(2), offer.addn(7)]
+
+    const quotes = await program.methods
+      .quoteDeals({ offers })
+      .accounts(accounts)
+      .view()
+
+    assert.ok(quotes.length === offers.length)
+
+    for (const [i, amount] of offers.entries()) {
+      const quote = await program.methods
+        .quoteDeal({ offer: amount })
+        .accounts(accounts)
+        .view()
+
+      assert.ok(quotes[i].clientFee.eq(quote.clientFee))
+      assert.ok(quotes[i].totalEscrow.eq(quote.totalEscrow))
+    }
+  })
+
+  it('should round fees up only when the config asks for it', async () => {
+    const setRounding = (roundFeesUp: boolean) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+    const quote = (amount: BN) =>
+      program.methods
+        .quoteDeal({ offer: amount })
+        .accounts({
+          gig: gigPda,
+          mint: null,
+          config: configPda,
+          priceFeed: null,
+          freelancerReputation: null,
+          clientExemption: null,
+        })
+        .view()
+
+    const config = await program.account.config.fetch(configPda)
+    const amounts = [offer, offer.addn(1), offer.addn(7), offer.addn(9_999)]
+
+    const roundedDown = []
+    for (const amount of amounts) {
+      roundedDown.push((await quote(amount)).clientFee)
+    }
+
+    await setRounding(true)
+
+    try {
+      for (const [i, amount] of amounts.entries()) {
+        const product = amount.muln(config.clientFeePercentage)
+        const floor = product.divn(100_00)
+        const ceil = product.addn(100_00 - 1).divn(100_00)
+        const { clientFee, totalEscrow } = await quote(amount)
+
+        assert.ok(roundedDown[i].eq(floor))
+        assert.ok(clientFee.eq(ceil))
+        assert.ok(clientFee.lte(amount))
+        assert.ok(totalEscrow.eq(amount.add(clientFee)))
+      }
+    } finally {
+      await setRounding(false)
+    }
+  })
+
+  it('should return the deal address from the creation', async () => {
+    await program.provider.connection.confirmTransaction(
+      createDealTx,
+      'confirmed',
+    )
+
+    const tx = await program.provider.connection.getTransaction(createDealTx, {
+      commitment: 'confirmed',
+    })
+    const prefix = `Program return: ${program.programId.toBase58()} `
+    const returnLog = tx.meta.logMessages.find((log) => log.startsWith(prefix))
+    const returned = new PublicKey(
+      Buffer.from(returnLog.slice(prefix.length), 'base64'),
+    )
+
+    assert.ok(returned.equals(dealPda))
+  })
+
+  it('should create a gig and its first deal at once', async () => {
+    const newGigId = Keypair.generate()
+    const [newGigPda] = findProgramAddressSync(
+      [
+        Buffer.from('gig'),
+        freelancer.publicKey.toBytes(),
+        newGigId.publicKey.toBytes().slice(0, 8),
+      ],
+      program.programId,
+    )
+    const [newDealPda] = findProgramAddressSync(
+      [Buffer.from('deal'), client.publicKey.toBytes(), newGigPda.toBytes()],
+      program.programId,
+    )
+    const createGigAndDeal = (dealOffer: BN) =>
+      program.methods
+        .createGigAndDeal({
+          gig: {
+            asking: offer,
+            category: 0,
+            skills: new BN(0),
+            minCompletionTime: new BN(60 * 24),
+            metadataHash: Array.from(Buffer.alloc(32)),
+            metadataUriLen: 0,
+            metadataUri: Array.from(Buffer.alloc(128)),
+            maxConcurrentDeals: 0,
+            maxAsking: null,
+            latePenaltyBps: 0,
+            requiredStake: new BN(0),
+            cancelFeeBps: 0,
+            minClientCompletedDeals: 0,
+          },
+          deal: {
+            offer: dealOffer,
+            deadline,
+            referrer: null,
+            milestones: [],
+            maxClientFee: null,
+            termsHash: Array.from(Buffer.alloc(32)),
+            deadlineFromAccept: null,
+            nonce: new BN(0),
+          },
+        })
+        .accounts({
+          gig: newGigPda,
+          owner: freelancer.publicKey,
+          id: newGigId.publicKey,
+          deal: newDealPda,
+          client: client.publicKey,
+          escrow: null,
+          feeEscrow: null,
+          ownerWallet: null,
+          freelancerTokenAccount: null,
+          mint: null,
+          config: configPda,
+          clientBlacklist: findBlacklistPda(client.publicKey),
+          freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+          priceFeed: null,
+          freelancerReputation: null,
+          clientReputation: null,
+          clientExemption: null,
+          referrerActivity: null,
+          dealIndex: null,
+          stats: statsPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: null,
+          associatedTokenProgram: null,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([freelancer, newGigId, client])
+        .rpc()
+
+    // The deal checks still apply, and the gig isn't created without its deal
+    try {
+      await createGigAndDeal(offer.subn(1))
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'InsufficientOffer')
+    }
+
+    assert.ok((await program.account.gig.fetchNullable(newGigPda)) === null)
+
+    await createGigAndDeal(offer)
+
+    const gig = await program.account.gig.fetch(newGigPda)
+    const deal = await program.account.deal.fetch(newDealPda)
+    const dealInfo = await program.provider.connection.getAccountInfo(
+      newDealPda,
+    )
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(gig.owner.equals(freelancer.publicKey))
+    assert.ok(gig.activeDeals === 1)
+    assert.ok(deal.gig.equals(newGigPda))
+    assert.ok(deal.state === 1)
+    assert.ok(new BN(dealInfo.lamports - rent).eq(deal.totalEscrow))
+  })
+
+  it('should count the deal in the global stats', async () => {
+    const stats = await program.account.stats.fetch(statsPda)
+
+    assert.ok(stats.totalDealsCreated.gten(1))
+    assert.ok(stats.totalVolume.gte(offer))
+    assert.ok(stats.activeDeals.gten(1))
+  })
+
+  const increaseOffer = (amount: BN) =>
+    program.methods
+      .increaseOffer({ amount })
+      .accounts({
+        deal: dealPda,
+        gig: gigPda,
+        client: client.publicKey,
+        escrow: null,
+        feeEscrow: null,
+        ownerWallet: null,
+        mint: null,
+        config: configPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientExemption: null,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([client])
+      .rpc()
+
+  it('should allow the client to increase the offer before acceptance', async () => {
+    const amount = offer.divn(10)
+    const before = await program.account.deal.fetch(dealPda)
+
+    await increaseOffer(amount)
+
+    const deal = await program.account.deal.fetch(dealPda)
+    const dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(deal.offer.eq(before.offer.add(amount)))
+    assert.ok(deal.clientFee.gte(before.clientFee))
+    assert.ok(new BN(dealInfo.lamports - rent).eq(deal.totalEscrow))
+  })
+
+  it('should hold an offer raised above the approval threshold until approved', async () => {
+    const before = await program.account.deal.fetch(dealPda)
+
+    await setThreshold(before.offer)
+
+    try {
+      await increaseOffer(new BN(1))
+    } finally {
+      await setThreshold(new BN(0))
+    }
+
+    let deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.state === 13)
+    assert.ok(deal.totalEscrow.eq(before.totalEscrow))
+
+    await program.methods
+      .approveDeal()
+      .accounts({
+        deal: dealPda,
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+    await fundDeal()
+
+    deal = await program.account.deal.fetch(dealPda)
+    const dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(deal.state === 1)
+    assert.ok(deal.totalEscrow.eq(deal.offer.add(deal.clientFee)))
+    assert.ok(new BN(dealInfo.lamports - rent).eq(deal.totalEscrow))
+  })
+
+  it('should allocate enough space for a fully populated deal', async () => {
+    const dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+    const deal = await program.account.deal.fetch(dealPda)
+    const max = new BN('18446744073709551615')
+    const hash = Array.from(Buffer.alloc(32, 255))
+
+    // Every optional field set and the milestones at MAX_MILESTONES
+    const encoded = await program.coder.accounts.encode('Deal', {
+      ...deal,
+      referrer: Keypair.generate().publicKey,
+      milestones: Array.from({ length: 8 }, () => ({
+        amount: max,
+        released: true,
+        descriptionHash: hash,
+      })),
+      milestoneDues: Array.from({ length: 8 }, () => ({
+        dueTs: max,
+        latePenaltyBps: 10000,
+        penaltyApplied: true,
+      })),
+      deliveryHash: hash,
+    })
+
+    assert.ok(encoded.length <= dealInfo.data.length)
+  })
+
+  it('should not allow more deals than the gig can take', async () => {
+    try {
+      await createDeal(otherClient)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'GigAtCapacity')
+    }
+  })
+
+  it('should not allow deals with a blacklisted freelancer', async () => {
+    const blacklist = findBlacklistPda(freelancer.publicKey)
+
+    await program.methods
+      .blacklistUser({ user: freelancer.publicKey })
+      .accounts({
+        blacklist,
+        config: configPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc()
+
+    try {
+      await createDeal(otherClient)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'UserBlacklisted')
+    }
+
+    await program.methods
+      .unblacklistUser()
+      .accounts({
+        blacklist,
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+    assert.ok(
+      (await program.provider.connection.getAccountInfo(blacklist)) === null,
+    )
+  })
+
+  it('should skip deals which have not expired yet in a batch refund', async () => {
+    // Placeholder for the escrow, owner wallet, reputation and fee escrow which native SOL
+    // offers don't need
+    const none = { pubkey: program.programId, isSigner: false, isWritable: false }
+
+    const refunded = await program.methods
+      .batchRefundExpired()
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        tokenProgram: null,
+      })
+      .remainingAccounts([
+        { pubkey: dealPda, isSigner: false, isWritable: false },
+        { pubkey: gigPda, isSigner: false, isWritable: false },
+        { pubkey: client.publicKey, isSigner: false, isWritable: false },
+        none,
+        none,
+        none,
+        none,
+      ])
+      .view()
+
+    assert.ok(refunded === 0)
+  })
+
+  it('should refund the lamports when the client cancels', async () => {
+    const balanceBefore = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    await program.methods
+      .closeDeal()
+      .accounts({
+        stats: statsPda,
+        client: client.publicKey,
+        deal: dealPda,
+        escrow: null,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: null,
+        ownerWallet: null,
+        signer: client.publicKey,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+      })
+      .signers([client])
+      .rpc()
+
+    const deal = await program.account.deal.fetchNullable(dealPda)
+    const balanceAfter = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    assert.ok(deal === null)
+    assert.ok(balanceAfter - balanceBefore > offer.toNumber())
+  })
+
+  it('should allow the client to cancel an offer', async () => {
+    await createDeal()
+
+    const balanceBefore = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    await program.methods
+      .cancelDeal()
+      .accounts({
+        stats: statsPda,
+        config: configPda,
+        client: client.publicKey,
+        freelancer: freelancer.publicKey,
+        freelancerTokenAccount: null,
+        deal: dealPda,
+        escrow: null,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: null,
+        ownerWallet: null,
+        tokenProgram: null,
+        dealIndex: null,
+      })
+      .signers([client])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+    const balanceAfter = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    assert.ok(deal.state === 5)
+    assert.ok(balanceAfter - balanceBefore > offer.toNumber())
+  })
+
+  it('should give back the rent once the cancelled deal is closed', async () => {
+    const balanceBefore = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    await program.methods
+      .closeDeal()
+      .accounts({
+        stats: statsPda,
+        client: client.publicKey,
+        deal: dealPda,
+        escrow: null,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: null,
+        ownerWallet: null,
+        signer: client.publicKey,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+      })
+      .signers([client])
+      .rpc()
+
+    const deal = await program.account.deal.fetchNullable(dealPda)
+    const balanceAfter = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+
+    assert.ok(deal === null)
+    assert.ok(balanceAfter > balanceBefore)
+  })
+
+  it('should not credit the same referrer twice within the cooldown', async () => {
+    const referrer = Keypair.generate().publicKey
+    const setCooldown = (referrerCooldown: BN) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout: null,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+    const closeDeal = () =>
+      program.methods
+        .closeDeal()
+        .accounts({
+          stats: statsPda,
+          client: client.publicKey,
+          deal: dealPda,
+          escrow: null,
+          feeEscrow: null,
+          gig: gigPda,
+          mint: null,
+          ownerWallet: null,
+          signer: client.publicKey,
+          tokenProgram: null,
+          associatedTokenProgram: null,
+        })
+        .signers([client])
+        .rpc()
+
+    await setCooldown(new BN(60 * 60))
+
+    try {
+      await createDeal(client, offer, referrer)
+
+      const referred = await program.account.deal.fetch(dealPda)
+      const activity = await program.account.referrerActivity.fetch(
+        findReferrerActivityPda(referrer, client),
+      )
+
+      assert.ok(referred.referrer.equals(referrer))
+      assert.ok(activity.lastReferralTs.eq(referred.timeCreated))
+
+      await closeDeal()
+      await createDeal(client, offer, referrer)
+
+      const repeated = await program.account.deal.fetch(dealPda)
+
+      assert.ok(repeated.referrer === null)
+
+      await closeDeal()
+    } finally {
+      await setCooldown(new BN(0))
+    }
+  })
+
+  it('should not allow a counter above the max asking price', async () => {
+    await createDeal(otherClient)
+
+    try {
+      await program.methods
+        .counterOffer({ newOffer: offer.muln(3) })
+        .accounts({
+          deal: findDealPda(otherClient.publicKey),
+          gig: gigPda,
+          config: configPda,
+          freelancer: freelancer.publicKey,
+        })
+        .signers([freelancer])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'OfferTooHigh')
+    }
+  })
+
+  it('should allow the freelancer to counter an offer', async () => {
+    await program.methods
+      .counterOffer({ newOffer: offer.muln(2) })
+      .accounts({
+        deal: findDealPda(otherClient.publicKey),
+        gig: gigPda,
+        config: configPda,
+        freelancer: freelancer.publicKey,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(
+      findDealPda(otherClient.publicKey),
+    )
+
+    assert.ok(deal.state === 9)
+    assert.ok(deal.counterAmount.eq(offer.muln(2)))
+  })
+
+  const acceptCounter = (maxClientFee: BN | null) =>
+    program.methods
+      .acceptCounter({ maxClientFee })
+      .accounts({
+        deal: findDealPda(otherClient.publicKey),
+        gig: gigPda,
+        client: otherClient.publicKey,
+        escrow: null,
+        feeEscrow: null,
+        ownerWallet: null,
+        mint: null,
+        config: configPda,
+        priceFeed: null,
+        clientExemption: null,
+        reputation: findReputationPda(freelancer.publicKey),
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([otherClient])
+      .rpc()
+
+  it('should not accept a counter offer above the max client fee', async () => {
+    try {
+      await acceptCounter(new BN(0))
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'FeeExceedsMax')
+    }
+  })
+
+  it('should top up the escrow when the client accepts the counter offer', async () => {
+    const otherDealPda = findDealPda(otherClient.publicKey)
+
+    await acceptCounter(null)
+
+    const deal = await program.account.deal.fetch(otherDealPda)
+    const dealInfo = await program.provider.connection.getAccountInfo(
+      otherDealPda,
+    )
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(deal.state === 3)
+    assert.ok(deal.offer.eq(offer.muln(2)))
+    assert.ok(dealInfo.lamports - rent >= offer.muln(2).toNumber())
+  })
+
+  it('should allow the freelancer to reprice and pause the gig', async () => {
+    const asking = offer.muln(2)
+
+    await program.methods
+      .updateGig({ asking, paused: true, allowedReferrers: null, mint: null })
+      .accounts({
+        gig: gigPda,
+        owner: freelancer.publicKey,
+        config: configPda,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const gig = await program.account.gig.fetch(gigPda)
+
+    assert.ok(gig.asking.eq(asking))
+    assert.ok(gig.state === 2)
+  })
+
+  it('should keep a closed gig with active deals until they settle', async () => {
+    await program.methods
+      .closeGig({ reclaimRent: false })
+      .accounts({
+        gig: gigPda,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const gig = await program.account.gig.fetch(gigPda)
+
+    assert.ok(gig.state === 3)
+    assert.ok(gig.activeDeals === 1)
+  })
+
+  it('should not close the gig account while it has active deals', async () => {
+    try {
+      await program.methods
+        .closeGigAccount()
+        .accounts({
+          gig: gigPda,
+          payer: freelancer.publicKey,
+          owner: freelancer.publicKey,
+        })
+        .signers([freelancer])
+        .rpc()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'GigNotClosable')
+    }
+  })
+
+  it('should not allow new deals on a closed gig', async () => {
+    const lateClient = Keypair.generate()
+
+    await program.provider.connection.requestAirdrop(
+      lateClient.publicKey,
+      LAMPORTS_PER_SOL,
+    )
+
+    await wait(500)
+
+    try {
+      await createDeal(lateClient)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'GigNotOpen')
+    }
+  })
+
+  const resolveTimeout = () =>
+    program.methods
+      .resolveTimeout()
+      .accounts({
+        deal: findDealPda(otherClient.publicKey),
+        gig: gigPda,
+        client: otherClient.publicKey,
+        freelancer: freelancer.publicKey,
+        mint: null,
+        escrow: null,
+        feeEscrow: null,
+        ownerWallet: null,
+        freelancerTokenAccount: null,
+        freelancerVault: null,
+        freelancerVaultTokenAccount: null,
+        treasury: configPda,
+        treasuryTokenAccount: null,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        arbiter: freelancer.publicKey,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+        dealIndex: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+  it('should not settle a dispute by timeout while the arbiter can resolve it', async () => {
+    const otherDealPda = findDealPda(otherClient.publicKey)
+
+    await program.methods
+      .disputeDeal()
+      .accounts({
+        deal: otherDealPda,
+        signer: otherClient.publicKey,
+      })
+      .signers([otherClient])
+      .rpc()
+
+    try {
+      await resolveTimeout()
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'DisputeTimeoutNotReached')
+    }
+  })
+
+  it('should settle a dispute by timeout once the arbiter let it lapse', async () => {
+    const setDisputeTimeout = (disputeTimeout: BN) =>
+      program.methods
+        .updateConfig({
+          clientFeePercentage: null,
+          freelancerFeePercentage: null,
+          clientFeeMin: null,
+          arbiter: null,
+          maxDealOffer: null,
+          feeTiers: null,
+          refundGracePeriod: null,
+          roundFeesUp: null,
+          referrerCooldown: null,
+          deferFreelancerPayouts: null,
+          maxDeadlineHorizon: null,
+          freelancerCancelPenaltyBps: null,
+          requireApprovalAbove: null,
+          settlementDelay: null,
+          maxTvl: null,
+          clientCancelWindow: null,
+          disputeTimeout,
+          splitEscrow: null,
+        })
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc()
+
+    const config = await program.account.config.fetch(configPda)
+    const dealPda = findDealPda(otherClient.publicKey)
+
+    await setDisputeTimeout(new BN(1))
+
+    try {
+      await wait(2000)
+
+      const before = await program.account.deal.fetch(dealPda)
+      const treasuryBefore =
+        await program.provider.connection.getBalance(configPda)
+
+      await resolveTimeout()
+
+      const deal = await program.account.deal.fetch(dealPda)
+      const treasuryAfter =
+        await program.provider.connection.getBalance(configPda)
+      const freelancerShare = before.offer.sub(
+        before.offer.muln(config.disputeDefaultClientBps).divn(100_00),
+      )
+      // The freelancer fee is taken from the freelancer's share like on completion
+      const freelancerFee = freelancerShare
+        .muln(config.freelancerFeePercentage)
+        .divn(100_00)
+
+      assert.ok(deal.state === 8)
+      assert.ok(
+        new BN(treasuryAfter - treasuryBefore).eq(
+          before.clientFee.add(freelancerFee),
+        ),
+      )
+    } finally {
+      await setDisputeTimeout(config.disputeTimeout)
+    }
+  })
+})
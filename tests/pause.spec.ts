@@ -0,0 +1,208 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import {
+  authority,
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let client = Keypair.generate()
+let lateClient = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+
+const findDealPda = (client: PublicKey) =>
+  findProgramAddressSync(
+    [Buffer.from('deal'), client.toBytes(), gigPda.toBytes()],
+    program.programId,
+  )[0]
+
+const setPaused = (paused: boolean) =>
+  program.methods
+    .setPaused({ paused })
+    .accounts({
+      config: configPda,
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc()
+
+describe('Pause', () => {
+  const offer = new BN(LAMPORTS_PER_SOL / 10)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+
+  const createDeal = (owner: Keypair) =>
+    program.methods
+      .createDeal({
+        offer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash: Array.from(Buffer.alloc(32)),
+        deadlineFromAccept: null,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: null,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(owner.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal: findDealPda(owner.publicKey),
+        gig: gigPda,
+        mint: null,
+        owner: owner.publicKey,
+        ownerWallet: null,
+        escrow: null,
+        feeEscrow: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner])
+      .rpc()
+
+  before(async () => {
+    for (const wallet of [freelancer, client, lateClient]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+
+    await createDeal(client)
+
+    await program.methods
+      .acceptDeal({ termsHash: Array.from(Buffer.alloc(32)) })
+      .accounts({
+        gig: gigPda,
+        deal: findDealPda(client.publicKey),
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    await setPaused(true)
+  })
+
+  after(async () => {
+    await setPaused(false)
+  })
+
+  it('should not allow new deals while paused', async () => {
+    try {
+      await createDeal(lateClient)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'ProgramPaused')
+    }
+  })
+
+  it('should still allow completing an in-flight deal while paused', async () => {
+    const dealPda = findDealPda(client.publicKey)
+
+    await program.methods
+      .completeDeal()
+      .accounts({
+        client: client.publicKey,
+        deal: dealPda,
+        escrow: null,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: null,
+        signer: client.publicKey,
+        referrer: null,
+        clientTokenAccount: null,
+        referrerTokenAccount: null,
+        referrerRewardsVault: null,
+        referrerPayoutMint: null,
+        priceFeed: null,
+        config: configPda,
+        stats: statsPda,
+        freelancer: freelancer.publicKey,
+        freelancerTokenAccount: null,
+        freelancerVault: null,
+        freelancerVaultTokenAccount: null,
+        treasury: configPda,
+        treasuryTokenAccount: null,
+        reputation: findReputationPda(freelancer.publicKey),
+        systemProgram: SystemProgram.programId,
+        associatedTokenProgram: null,
+        tokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+        dealIndex: null,
+      })
+      .signers([client])
+      .rpc()
+
+    const deal = await program.account.deal.fetch(dealPda)
+
+    assert.ok(deal.state === 11)
+  })
+})
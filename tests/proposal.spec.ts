@@ -0,0 +1,184 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import {
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let client = Keypair.generate()
+let otherClient = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+const findDealPda = (client: PublicKey) =>
+  findProgramAddressSync(
+    [Buffer.from('deal'), client.toBytes(), gigPda.toBytes()],
+    program.programId,
+  )[0]
+
+describe('Freelancer proposals', () => {
+  const offer = new BN(LAMPORTS_PER_SOL / 10)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+
+  const proposeDeal = (target: PublicKey) =>
+    program.methods
+      .proposeDeal({ client: target, offer, deadline })
+      .accounts({
+        deal: findDealPda(target),
+        gig: gigPda,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        clientBlacklist: findBlacklistPda(target),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        reputation: findReputationPda(freelancer.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer])
+      .rpc()
+
+  const fundProposal = (deal: PublicKey, owner: Keypair) =>
+    program.methods
+      .fundProposal()
+      .accounts({
+        deal,
+        escrow: null,
+        feeEscrow: null,
+        ownerWallet: null,
+        mint: null,
+        gig: gigPda,
+        client: owner.publicKey,
+        config: configPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientExemption: null,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner])
+      .rpc()
+
+  before(async () => {
+    for (const wallet of [freelancer, client, otherClient]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+  })
+
+  it('should allow the freelancer to propose a deal to a client', async () => {
+    await proposeDeal(client.publicKey)
+
+    const deal = await program.account.deal.fetch(findDealPda(client.publicKey))
+
+    assert.ok(deal.state === 12)
+    assert.ok(deal.client.equals(client.publicKey))
+    assert.ok(deal.offer.eq(offer))
+  })
+
+  it('should not allow anyone else to fund the proposal', async () => {
+    try {
+      await fundProposal(findDealPda(client.publicKey), otherClient)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'Unauthorized')
+    }
+  })
+
+  it('should accept the deal once the client funds it', async () => {
+    const dealPda = findDealPda(client.publicKey)
+
+    await fundProposal(dealPda, client)
+
+    const deal = await program.account.deal.fetch(dealPda)
+    const gig = await program.account.gig.fetch(gigPda)
+    const dealInfo = await program.provider.connection.getAccountInfo(dealPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        dealInfo.data.length,
+      )
+
+    assert.ok(deal.state === 3)
+    assert.ok(gig.activeDeals === 1)
+    assert.ok(new BN(dealInfo.lamports - rent).eq(deal.totalEscrow))
+  })
+
+  it('should allow the freelancer to withdraw an unfunded proposal', async () => {
+    const dealPda = findDealPda(otherClient.publicKey)
+
+    await proposeDeal(otherClient.publicKey)
+
+    await program.methods
+      .closeDeal()
+      .accounts({
+        stats: statsPda,
+        client: otherClient.publicKey,
+        deal: dealPda,
+        escrow: null,
+        feeEscrow: null,
+        gig: gigPda,
+        mint: null,
+        ownerWallet: null,
+        signer: freelancer.publicKey,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    assert.ok((await program.account.deal.fetchNullable(dealPda)) === null)
+  })
+})
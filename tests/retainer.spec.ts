@@ -0,0 +1,205 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import { Keypair, LAMPORTS_PER_SOL, SystemProgram } from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import {
+  configPda,
+  findBlacklistPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let client = Keypair.generate()
+let cranker = Keypair.generate()
+let gigId = Keypair.generate()
+let gigNonce = gigId.publicKey.toBytes().slice(0, 8)
+let [gigPda] = findProgramAddressSync(
+  [Buffer.from('gig'), freelancer.publicKey.toBytes(), gigNonce],
+  program.programId,
+)
+const nonce = new BN(0)
+let [retainerPda] = findProgramAddressSync(
+  [
+    Buffer.from('retainer'),
+    client.publicKey.toBytes(),
+    gigPda.toBytes(),
+    nonce.toArrayLike(Buffer, 'le', 8),
+  ],
+  program.programId,
+)
+
+describe('Retainers', () => {
+  const perPeriodAmount = new BN(LAMPORTS_PER_SOL / 10)
+  const periodSeconds = new BN(5)
+
+  before(async () => {
+    for (const wallet of [freelancer, client, cranker]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    await program.methods
+      .createGig({
+        asking: perPeriodAmount,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig: gigPda,
+        mint: null,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+  })
+
+  const releasePeriod = async (signer: Keypair) => {
+    const config = await program.account.config.fetch(configPda)
+
+    return program.methods
+      .releasePeriod()
+      .accounts({
+        retainer: retainerPda,
+        signer: signer.publicKey,
+        client: client.publicKey,
+        freelancer: freelancer.publicKey,
+        treasury: config.treasury,
+        config: configPda,
+        stats: statsPda,
+      })
+      .signers([signer])
+      .rpc()
+  }
+
+  it('should escrow every period of the retainer upfront', async () => {
+    await program.methods
+      .createRetainer({
+        periodSeconds,
+        perPeriodAmount,
+        periodsTotal: 3,
+        allowCrank: true,
+        nonce,
+      })
+      .accounts({
+        retainer: retainerPda,
+        gig: gigPda,
+        client: client.publicKey,
+        config: configPda,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        freelancerReputation: null,
+        clientExemption: null,
+        stats: statsPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc()
+
+    const retainer = await program.account.retainer.fetch(retainerPda)
+    const balance = await program.provider.connection.getBalance(retainerPda)
+    const rent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        (await program.provider.connection.getAccountInfo(retainerPda))!.data
+          .length,
+      )
+
+    assert.ok(retainer.periodsTotal === 3)
+    assert.ok(retainer.periodsReleased === 0)
+    assert.ok(
+      new BN(balance - rent).eq(
+        perPeriodAmount.add(retainer.perPeriodClientFee).muln(3),
+      ),
+    )
+  })
+
+  it('should not release a period before it is due', async () => {
+    try {
+      await releasePeriod(client)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'PeriodNotDue')
+    }
+  })
+
+  it('should let anyone release a due period once the client allowed it', async () => {
+    await wait(periodSeconds.toNumber() * 1000 + 1000)
+
+    const before = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const config = await program.account.config.fetch(configPda)
+
+    await releasePeriod(cranker)
+
+    const after = await program.provider.connection.getBalance(
+      freelancer.publicKey,
+    )
+    const freelancerFee = perPeriodAmount
+      .muln(config.freelancerFeePercentage)
+      .divn(100_00)
+    const retainer = await program.account.retainer.fetch(retainerPda)
+
+    assert.ok(new BN(after - before).eq(perPeriodAmount.sub(freelancerFee)))
+    assert.ok(retainer.periodsReleased === 1)
+  })
+
+  it('should refund the periods not yet worked on cancellation', async () => {
+    const retainer = await program.account.retainer.fetch(retainerPda)
+    const period = perPeriodAmount.add(retainer.perPeriodClientFee)
+    const config = await program.account.config.fetch(configPda)
+    const before = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+    const balance = await program.provider.connection.getBalance(retainerPda)
+
+    await program.methods
+      .cancelRetainer()
+      .accounts({
+        retainer: retainerPda,
+        signer: freelancer.publicKey,
+        client: client.publicKey,
+        freelancer: freelancer.publicKey,
+        treasury: config.treasury,
+        config: configPda,
+        stats: statsPda,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const after = await program.provider.connection.getBalance(
+      client.publicKey,
+    )
+    // The freelancer is paid for the time into the second period
+    const earned = new BN(balance - (after - before))
+
+    assert.ok(earned.lt(period))
+    assert.ok(new BN(after - before).gt(period))
+    assert.ok(
+      (await program.account.retainer.fetchNullable(retainerPda)) === null,
+    )
+  })
+})
@@ -0,0 +1,465 @@
+import { AnchorError } from '@coral-xyz/anchor'
+import { findProgramAddressSync } from '@coral-xyz/anchor/dist/cjs/utils/pubkey'
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createMint,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  TOKEN_PROGRAM_ID,
+} from '@solana/spl-token'
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js'
+import { BN } from 'bn.js'
+import { assert } from 'chai'
+import { TokenMint } from '../scripts/utils'
+import {
+  authority,
+  configPda,
+  findBlacklistPda,
+  findReputationPda,
+  program,
+  statsPda,
+  wait,
+} from './common'
+
+let freelancer = Keypair.generate()
+let client = Keypair.generate()
+
+interface MintDeal {
+  mint: PublicKey
+  gig: PublicKey
+  deal: PublicKey
+  escrow: PublicKey
+  feeEscrow: PublicKey | null
+  freelancerWallet: PublicKey
+  treasuryWallet: PublicKey
+}
+
+describe('Treasury fees in several mints', () => {
+  const offer = new BN(100_000_000)
+  const deadline = new BN(new Date().getTime() / 1000 + 60 * 24 * 2)
+  const termsHash = Array.from(Buffer.alloc(32, 5))
+  let allowedMints: PublicKey[]
+  let priceFeeds: PublicKey[]
+  let usdcDeal: MintDeal
+  let otherDeal: MintDeal
+  let switchedDeal: MintDeal
+  let splitDeal: MintDeal
+
+  const balance = async (tokenAccount: PublicKey) =>
+    new BN(
+      (
+        await program.provider.connection.getTokenAccountBalance(tokenAccount)
+      ).value.amount,
+    )
+
+  const updateAllowedMints = (mints: PublicKey[], feeds: PublicKey[]) =>
+    program.methods
+      .updateAllowedMints({ allowedMints: mints, priceFeeds: feeds })
+      .accounts({
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+  const setSplitEscrow = (splitEscrow: boolean) =>
+    program.methods
+      .updateConfig({
+        clientFeePercentage: null,
+        freelancerFeePercentage: null,
+        clientFeeMin: null,
+        arbiter: null,
+        maxDealOffer: null,
+        feeTiers: null,
+        refundGracePeriod: null,
+        roundFeesUp: null,
+        referrerCooldown: null,
+        deferFreelancerPayouts: null,
+        maxDeadlineHorizon: null,
+        freelancerCancelPenaltyBps: null,
+        requireApprovalAbove: null,
+        settlementDelay: null,
+        maxTvl: null,
+        clientCancelWindow: null,
+        disputeTimeout: null,
+        splitEscrow,
+      })
+      .accounts({
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc()
+
+  // Opens a gig in `mint` with a deal the freelancer accepted, its client fee held in a fee
+  // escrow of its own with `splitEscrow`.
+  const openDeal = async (
+    mint: PublicKey,
+    splitEscrow = false,
+  ): Promise<MintDeal> => {
+    const gigId = Keypair.generate()
+    const [gig] = findProgramAddressSync(
+      [
+        Buffer.from('gig'),
+        freelancer.publicKey.toBytes(),
+        gigId.publicKey.toBytes().slice(0, 8),
+      ],
+      program.programId,
+    )
+    const [deal] = findProgramAddressSync(
+      [Buffer.from('deal'), client.publicKey.toBytes(), gig.toBytes()],
+      program.programId,
+    )
+    const connection = program.provider.connection
+    const clientWallet = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        client.publicKey,
+      )
+    ).address
+    const freelancerWallet = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        freelancer.publicKey,
+      )
+    ).address
+    // Fees are held by the config PDA, in one token account for each mint
+    const treasuryWallet = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        configPda,
+        true,
+      )
+    ).address
+    const escrow = getAssociatedTokenAddressSync(mint, deal, true)
+    const feeEscrow = splitEscrow
+      ? findProgramAddressSync(
+          [Buffer.from('fee_escrow'), deal.toBytes()],
+          program.programId,
+        )[0]
+      : null
+
+    await mintTo(
+      connection,
+      authority,
+      mint,
+      clientWallet,
+      authority,
+      200_000_000,
+    )
+
+    await program.methods
+      .createGig({
+        asking: offer,
+        category: 0,
+        skills: new BN(0),
+        minCompletionTime: new BN(60 * 24),
+        metadataHash: Array.from(Buffer.alloc(32)),
+        metadataUriLen: 0,
+        metadataUri: Array.from(Buffer.alloc(128)),
+        maxConcurrentDeals: 0,
+        maxAsking: null,
+        latePenaltyBps: 0,
+        requiredStake: new BN(0),
+        cancelFeeBps: 0,
+        minClientCompletedDeals: 0,
+      })
+      .accounts({
+        id: gigId.publicKey,
+        gig,
+        mint,
+        owner: freelancer.publicKey,
+        payer: freelancer.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freelancer, gigId])
+      .rpc()
+
+    await program.methods
+      .createDeal({
+        offer,
+        deadline,
+        referrer: null,
+        milestones: [],
+        maxClientFee: null,
+        termsHash,
+        deadlineFromAccept: null,
+        nonce: new BN(0),
+      })
+      .accounts({
+        config: configPda,
+        stats: statsPda,
+        priceFeed: null,
+        freelancerReputation: null,
+        clientReputation: null,
+        clientExemption: null,
+        freelancerTokenAccount: freelancerWallet,
+        referrerActivity: null,
+        dealIndex: null,
+        clientBlacklist: findBlacklistPda(client.publicKey),
+        freelancerBlacklist: findBlacklistPda(freelancer.publicKey),
+        deal,
+        gig,
+        mint,
+        owner: client.publicKey,
+        ownerWallet: clientWallet,
+        escrow,
+        feeEscrow,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([client])
+      .rpc()
+
+    await program.methods
+      .acceptDeal({ termsHash })
+      .accounts({
+        deal,
+        freelancer: freelancer.publicKey,
+        config: configPda,
+        gig,
+        reputation: findReputationPda(freelancer.publicKey),
+        escrow: null,
+        freelancerWallet: null,
+        mint: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: null,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    return {
+      mint,
+      gig,
+      deal,
+      escrow,
+      feeEscrow,
+      freelancerWallet,
+      treasuryWallet,
+    }
+  }
+
+  const completeDeal = (
+    mintDeal: MintDeal,
+    treasuryTokenAccount: PublicKey,
+  ) =>
+    program.methods
+      .completeDeal()
+      .accounts({
+        client: client.publicKey,
+        deal: mintDeal.deal,
+        escrow: mintDeal.escrow,
+        feeEscrow: mintDeal.feeEscrow,
+        gig: mintDeal.gig,
+        mint: mintDeal.mint,
+        signer: client.publicKey,
+        referrer: null,
+        clientTokenAccount: null,
+        referrerTokenAccount: null,
+        referrerRewardsVault: null,
+        referrerPayoutMint: null,
+        priceFeed: null,
+        config: configPda,
+        stats: statsPda,
+        freelancer: freelancer.publicKey,
+        freelancerTokenAccount: mintDeal.freelancerWallet,
+        freelancerVault: null,
+        freelancerVaultTokenAccount: null,
+        treasury: configPda,
+        treasuryTokenAccount,
+        reputation: findReputationPda(freelancer.publicKey),
+        systemProgram: SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        dealIndex: null,
+      })
+      .signers([client])
+      .rpc()
+
+  before(async () => {
+    for (const wallet of [freelancer, client]) {
+      await program.provider.connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL,
+      )
+
+      await wait(500)
+    }
+
+    const usdc = await TokenMint.init(
+      program.provider.connection,
+      authority,
+      authority,
+    )
+    const otherMint = await createMint(
+      program.provider.connection,
+      authority,
+      authority.publicKey,
+      null,
+      6,
+    )
+
+    const config = await program.account.config.fetch(configPda)
+    allowedMints = config.allowedMints
+    priceFeeds = config.priceFeeds
+
+    await updateAllowedMints(
+      [...allowedMints, otherMint],
+      [...priceFeeds, PublicKey.default],
+    )
+
+    usdcDeal = await openDeal(usdc.token)
+    otherDeal = await openDeal(otherMint)
+    switchedDeal = await openDeal(usdc.token)
+
+    await setSplitEscrow(true)
+
+    try {
+      splitDeal = await openDeal(usdc.token, true)
+    } finally {
+      await setSplitEscrow(false)
+    }
+  })
+
+  after(async () => {
+    await updateAllowedMints(allowedMints, priceFeeds)
+  })
+
+  it('should reject the treasury token account of another mint', async () => {
+    try {
+      await completeDeal(usdcDeal, otherDeal.treasuryWallet)
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'TreasuryMintMismatch')
+    }
+  })
+
+  it('should pay the fees of each deal into the treasury account of its mint', async () => {
+    for (const mintDeal of [usdcDeal, otherDeal]) {
+      const deal = await program.account.deal.fetch(mintDeal.deal)
+      const before = await balance(mintDeal.treasuryWallet)
+      const otherWallet = (mintDeal === usdcDeal ? otherDeal : usdcDeal)
+        .treasuryWallet
+      const otherBefore = await balance(otherWallet)
+
+      await completeDeal(mintDeal, mintDeal.treasuryWallet)
+
+      const fees = deal.totalEscrow.sub(deal.freelancerNet)
+
+      assert.ok((await balance(mintDeal.treasuryWallet)).eq(before.add(fees)))
+      assert.ok((await balance(otherWallet)).eq(otherBefore))
+    }
+  })
+
+  it('should settle in the mint of the deal after the gig switches mints', async () => {
+    await program.methods
+      .updateGig({
+        asking: null,
+        paused: null,
+        allowedReferrers: null,
+        mint: otherDeal.mint,
+      })
+      .accounts({
+        gig: switchedDeal.gig,
+        owner: freelancer.publicKey,
+        config: configPda,
+      })
+      .signers([freelancer])
+      .rpc()
+
+    const gig = await program.account.gig.fetch(switchedDeal.gig)
+    const deal = await program.account.deal.fetch(switchedDeal.deal)
+
+    assert.ok(gig.mint.equals(otherDeal.mint))
+    assert.ok(deal.mint.equals(usdcDeal.mint))
+
+    // The token accounts of the gig's new mint don't match the escrow.
+    try {
+      await completeDeal(
+        {
+          ...switchedDeal,
+          mint: otherDeal.mint,
+          freelancerWallet: otherDeal.freelancerWallet,
+        },
+        otherDeal.treasuryWallet,
+      )
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'MintMismatch')
+    }
+
+    const before = await balance(switchedDeal.freelancerWallet)
+
+    await completeDeal(switchedDeal, switchedDeal.treasuryWallet)
+
+    assert.ok(
+      (await balance(switchedDeal.freelancerWallet)).eq(
+        before.add(deal.freelancerNet),
+      ),
+    )
+  })
+
+  it('should hold the client fee of a split escrow apart from the offer', async () => {
+    const deal = await program.account.deal.fetch(splitDeal.deal)
+
+    assert.ok(deal.feeEscrow.equals(splitDeal.feeEscrow))
+    assert.ok((await balance(splitDeal.escrow)).eq(deal.offer))
+    assert.ok((await balance(splitDeal.feeEscrow)).eq(deal.clientFee))
+
+    // Without the fee escrow the client fee can't be paid out.
+    try {
+      await completeDeal(
+        { ...splitDeal, feeEscrow: null },
+        splitDeal.treasuryWallet,
+      )
+
+      assert.ok(false)
+    } catch (e) {
+      const err = e as AnchorError
+
+      assert.ok(err.error.errorCode.code === 'FeeEscrowMismatch')
+    }
+
+    const freelancerBefore = await balance(splitDeal.freelancerWallet)
+    const treasuryBefore = await balance(splitDeal.treasuryWallet)
+
+    await completeDeal(splitDeal, splitDeal.treasuryWallet)
+
+    const connection = program.provider.connection
+
+    assert.ok(
+      (await balance(splitDeal.freelancerWallet)).eq(
+        freelancerBefore.add(deal.freelancerNet),
+      ),
+    )
+    assert.ok(
+      (await balance(splitDeal.treasuryWallet)).eq(
+        treasuryBefore.add(deal.totalEscrow.sub(deal.freelancerNet)),
+      ),
+    )
+    assert.ok((await connection.getAccountInfo(splitDeal.escrow)) === null)
+    assert.ok((await connection.getAccountInfo(splitDeal.feeEscrow)) === null)
+  })
+})